@@ -51,11 +51,18 @@ pub enum Node {
 
     // Functions
     FnDef(String, Vec<String>, Box<Node>),
+    /// Anonymous `fn(params) { body }` expression. Evaluates to a function value
+    /// without binding a name; it does not capture the surrounding locals.
+    Lambda(Vec<String>, Box<Node>),
     Call(String, Vec<Node>),
 
     // I/O & System Nodes (Sprint 59 extensions)
     FileRead(Box<Node>),
     FileWrite(Box<Node>, Box<Node>),
+    /// Reads a file as raw `Bytes`; same permission and path checks as `FileRead`.
+    FileReadBytes(Box<Node>),
+    /// Writes `Bytes` (or an array of byte ints) to a path.
+    FileWriteBytes(Box<Node>, Box<Node>),
     Print(Box<Node>),
     FSRead(Box<Node>),             // Specialized Agent I/O
     FSWrite(Box<Node>, Box<Node>), // Specialized Agent I/O
@@ -134,6 +141,13 @@ pub enum Node {
         url: String,
         callback: Box<Node>,
     },
+    /// Waits until a promise handle settles; yields its value or faults with the rejection.
+    /// Only allowed as a statement, or as the value a statement assigns or
+    /// returns; no event callbacks run while it waits (see `ExecutionEngine::await_value`).
+    Await(Box<Node>),
+    /// Queries `source` with a JSONPath or JSON Pointer `path`; see `jsonpath`
+    /// for the dialect. Strings are queried as plain strings, so a raw JSON
+    /// body (e.g. `fetch_result`) goes through `JSON.Parse` first.
     Extract {
         source: Box<Node>,
         path: Box<Node>,
@@ -210,6 +224,366 @@ pub enum Node {
     CheckCollision { a_min: Box<Node>, a_max: Box<Node>, b_min: Box<Node>, b_max: Box<Node> },
 }
 
+/// Expands to a walker over a node's direct children. `children` and
+/// `children_mut` share this one list, and the match has no catch-all, so a new
+/// variant must be listed here (as a parent or as a leaf) to compile.
+macro_rules! child_walker {
+    ($(#[$doc:meta])* $name:ident, $iter:ident; $($m:tt)?) => {
+        $(#[$doc])*
+        pub fn $name(&$($m)? self) -> Vec<&$($m)? Node> {
+            let mut out: Vec<&$($m)? Node> = Vec::new();
+            match self {
+                Node::Assign(_, b)
+                | Node::UIScrollArea(_, b) => out.push(&$($m)? **b),
+                Node::Add(a, b)
+                | Node::Sub(a, b)
+                | Node::Mul(a, b)
+                | Node::Div(a, b)
+                | Node::Mat4Mul(a, b)
+                | Node::Eq(a, b)
+                | Node::Lt(a, b)
+                | Node::Gt(a, b)
+                | Node::ArrayGet(a, b)
+                | Node::ArrayPush(a, b)
+                | Node::MapGet(a, b)
+                | Node::MapHasKey(a, b)
+                | Node::Index(a, b)
+                | Node::Concat(a, b)
+                | Node::BitAnd(a, b)
+                | Node::BitShiftLeft(a, b)
+                | Node::BitShiftRight(a, b)
+                | Node::FileWrite(a, b)
+                | Node::FileWriteBytes(a, b)
+                | Node::FSWrite(a, b)
+                | Node::LoadTextureAtlas(a, b)
+                | Node::LoadSample(a, b)
+                | Node::While(a, b) => {
+                    out.push(&$($m)? **a);
+                    out.push(&$($m)? **b);
+                }
+                Node::Sin(a)
+                | Node::Cos(a)
+                | Node::Abs(a)
+                | Node::ArrayLen(a)
+                | Node::FileRead(a)
+                | Node::FileReadBytes(a)
+                | Node::Print(a)
+                | Node::FSRead(a)
+                | Node::EvalJSONNative(a)
+                | Node::ToString(a)
+                | Node::Await(a)
+                | Node::LoadShader(a)
+                | Node::PollEvents(a)
+                | Node::StopNote(a)
+                | Node::LoadMesh(a)
+                | Node::LoadTexture(a)
+                | Node::PlayAudioFile(a)
+                | Node::LoadFont(a)
+                | Node::UILabel(a)
+                | Node::UIButton(a)
+                | Node::UITextInput(a)
+                | Node::UIHorizontal(a)
+                | Node::UIFullscreen(a)
+                | Node::InitCamera(a)
+                | Node::DrawVoxelGrid(a)
+                | Node::EnableInteraction(a)
+                | Node::EnablePhysics(a)
+                | Node::Return(a) => out.push(&$($m)? **a),
+                Node::ArrayCreate(a)
+                | Node::Block(a) => out.extend(a.$iter()),
+                Node::ArraySet(a, b, c)
+                | Node::MapSet(a, b, c)
+                | Node::InitWindow(a, b, c)
+                | Node::RenderMesh(a, b, c)
+                | Node::PlayNote(a, b, c)
+                | Node::PlaySample(a, b, c) => {
+                    out.push(&$($m)? **a);
+                    out.push(&$($m)? **b);
+                    out.push(&$($m)? **c);
+                }
+                Node::ObjectLiteral(a) => {
+                    let mut entries: Vec<_> = a.$iter().collect();
+                    entries.sort_by(|x, y| x.0.cmp(y.0));
+                    out.extend(entries.into_iter().map(|(_, n)| n));
+                }
+                Node::PropertyGet(a, _) => out.push(&$($m)? **a),
+                Node::PropertySet(a, _, c) => {
+                    out.push(&$($m)? **a);
+                    out.push(&$($m)? **c);
+                }
+                Node::FnDef(_, _, c)
+                | Node::UIGrid(_, _, c) => out.push(&$($m)? **c),
+                Node::Lambda(_, body) => out.push(&$($m)? **body),
+                Node::Call(_, b)
+                | Node::NativeCall(_, b) => out.extend(b.$iter()),
+                Node::Store { value, .. } => out.push(&$($m)? **value),
+                Node::DrawRect { x, y, width, height, color } => {
+                    out.push(&$($m)? **x);
+                    out.push(&$($m)? **y);
+                    out.push(&$($m)? **width);
+                    out.push(&$($m)? **height);
+                    out.push(&$($m)? **color);
+                }
+                Node::UIFixed { width, height, body } => {
+                    out.push(&$($m)? **width);
+                    out.push(&$($m)? **height);
+                    out.push(&$($m)? **body);
+                }
+                Node::RenderCanvas { body } => out.push(&$($m)? **body),
+                Node::Transform2D { x, y, rotation, scale, body } => {
+                    out.push(&$($m)? **x);
+                    out.push(&$($m)? **y);
+                    out.push(&$($m)? **rotation);
+                    out.push(&$($m)? **scale);
+                    out.push(&$($m)? **body);
+                }
+                Node::Sprite2D { texture_id, transform } => {
+                    out.push(&$($m)? **texture_id);
+                    out.push(&$($m)? **transform);
+                }
+                Node::Camera3D { pos_x, pos_y, pos_z, target_x, target_y, target_z, fov } => {
+                    out.push(&$($m)? **pos_x);
+                    out.push(&$($m)? **pos_y);
+                    out.push(&$($m)? **pos_z);
+                    out.push(&$($m)? **target_x);
+                    out.push(&$($m)? **target_y);
+                    out.push(&$($m)? **target_z);
+                    out.push(&$($m)? **fov);
+                }
+                Node::Mesh3D { primitive, material } => {
+                    out.push(&$($m)? **primitive);
+                    out.push(&$($m)? **material);
+                }
+                Node::PointLight3D { x, y, z, r, g, b, intensity } => {
+                    out.push(&$($m)? **x);
+                    out.push(&$($m)? **y);
+                    out.push(&$($m)? **z);
+                    out.push(&$($m)? **r);
+                    out.push(&$($m)? **g);
+                    out.push(&$($m)? **b);
+                    out.push(&$($m)? **intensity);
+                }
+                Node::Material3D { r, g, b, a, metallic, roughness, texture_id } => {
+                    out.push(&$($m)? **r);
+                    out.push(&$($m)? **g);
+                    out.push(&$($m)? **b);
+                    out.push(&$($m)? **a);
+                    out.push(&$($m)? **metallic);
+                    out.push(&$($m)? **roughness);
+                    if let Some(n) = texture_id { out.push(&$($m)? **n); }
+                }
+                Node::MeshInstance3D { mesh_id, transform, color_offset, pbr } => {
+                    out.push(&$($m)? **mesh_id);
+                    out.push(&$($m)? **transform);
+                    out.push(&$($m)? **color_offset);
+                    out.push(&$($m)? **pbr);
+                }
+                Node::FPSCamera { fov } => out.push(&$($m)? **fov),
+                Node::MouseGrab { enabled } => out.push(&$($m)? **enabled),
+                Node::WeaponViewModel { mesh, tex } => {
+                    out.push(&$($m)? **mesh);
+                    out.push(&$($m)? **tex);
+                }
+                Node::Fetch { callback, .. } => out.push(&$($m)? **callback),
+                Node::Extract { source, path } => {
+                    out.push(&$($m)? **source);
+                    out.push(&$($m)? **path);
+                }
+                Node::ExternCall { args, .. } => out.extend(args.$iter()),
+                Node::RenderAsset(a, b, c, d)
+                | Node::SetVoxel(a, b, c, d) => {
+                    out.push(&$($m)? **a);
+                    out.push(&$($m)? **b);
+                    out.push(&$($m)? **c);
+                    out.push(&$($m)? **d);
+                }
+                Node::DrawText(a, b, c, d, e) => {
+                    out.push(&$($m)? **a);
+                    out.push(&$($m)? **b);
+                    out.push(&$($m)? **c);
+                    out.push(&$($m)? **d);
+                    out.push(&$($m)? **e);
+                }
+                Node::UIWindow(_, b, c) => {
+                    out.push(&$($m)? **b);
+                    out.push(&$($m)? **c);
+                }
+                Node::UISetStyle(a, b, c, d, e, f) => {
+                    out.push(&$($m)? **a);
+                    out.push(&$($m)? **b);
+                    out.push(&$($m)? **c);
+                    out.push(&$($m)? **d);
+                    if let Some(n) = e { out.push(&$($m)? **n); }
+                    if let Some(n) = f { out.push(&$($m)? **n); }
+                }
+                Node::If(a, b, c) => {
+                    out.push(&$($m)? **a);
+                    out.push(&$($m)? **b);
+                    if let Some(n) = c { out.push(&$($m)? **n); }
+                }
+                Node::AddWorldAABB { min, max } => {
+                    out.push(&$($m)? **min);
+                    out.push(&$($m)? **max);
+                }
+                Node::CheckCollision { a_min, a_max, b_min, b_max } => {
+                    out.push(&$($m)? **a_min);
+                    out.push(&$($m)? **a_max);
+                    out.push(&$($m)? **b_min);
+                    out.push(&$($m)? **b_max);
+                }
+                Node::IntLiteral(..)
+                | Node::FloatLiteral(..)
+                | Node::BoolLiteral(..)
+                | Node::StringLiteral(..)
+                | Node::Identifier(..)
+                | Node::Time
+                | Node::GlobalTime
+                | Node::MapCreate
+                | Node::Load { .. }
+                | Node::UIFillParent
+                | Node::RaycastSimple
+                | Node::InitGraphics
+                | Node::InitAudio
+                | Node::GetLastKeypress
+                | Node::InitVoxelMap
+                | Node::Import(..) => {}
+            }
+            out
+        }
+    };
+}
+
+impl Node {
+    /// Variant name without payload, e.g. `"Call"` or `"While"`.
+    /// Used as a stable label by tooling (profiler, coverage).
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Node::IntLiteral(..) => "IntLiteral",
+            Node::FloatLiteral(..) => "FloatLiteral",
+            Node::BoolLiteral(..) => "BoolLiteral",
+            Node::StringLiteral(..) => "StringLiteral",
+            Node::Identifier(..) => "Identifier",
+            Node::Assign(..) => "Assign",
+            Node::Add(..) => "Add",
+            Node::Sub(..) => "Sub",
+            Node::Mul(..) => "Mul",
+            Node::Div(..) => "Div",
+            Node::Sin(..) => "Sin",
+            Node::Cos(..) => "Cos",
+            Node::Mat4Mul(..) => "Mat4Mul",
+            Node::Time => "Time",
+            Node::GlobalTime => "GlobalTime",
+            Node::Abs(..) => "Abs",
+            Node::Eq(..) => "Eq",
+            Node::Lt(..) => "Lt",
+            Node::Gt(..) => "Gt",
+            Node::ArrayCreate(..) => "ArrayCreate",
+            Node::ArrayGet(..) => "ArrayGet",
+            Node::ArraySet(..) => "ArraySet",
+            Node::ArrayPush(..) => "ArrayPush",
+            Node::ArrayLen(..) => "ArrayLen",
+            Node::MapCreate => "MapCreate",
+            Node::MapGet(..) => "MapGet",
+            Node::MapSet(..) => "MapSet",
+            Node::MapHasKey(..) => "MapHasKey",
+            Node::Index(..) => "Index",
+            Node::Concat(..) => "Concat",
+            Node::ObjectLiteral(..) => "ObjectLiteral",
+            Node::PropertyGet(..) => "PropertyGet",
+            Node::PropertySet(..) => "PropertySet",
+            Node::BitAnd(..) => "BitAnd",
+            Node::BitShiftLeft(..) => "BitShiftLeft",
+            Node::BitShiftRight(..) => "BitShiftRight",
+            Node::FnDef(..) => "FnDef",
+            Node::Lambda(..) => "Lambda",
+            Node::Call(..) => "Call",
+            Node::FileRead(..) => "FileRead",
+            Node::FileWrite(..) => "FileWrite",
+            Node::FileReadBytes(..) => "FileReadBytes",
+            Node::FileWriteBytes(..) => "FileWriteBytes",
+            Node::Print(..) => "Print",
+            Node::FSRead(..) => "FSRead",
+            Node::FSWrite(..) => "FSWrite",
+            Node::Store { .. } => "Store",
+            Node::Load { .. } => "Load",
+            Node::DrawRect { .. } => "DrawRect",
+            Node::UIFixed { .. } => "UIFixed",
+            Node::UIFillParent => "UIFillParent",
+            Node::RenderCanvas { .. } => "RenderCanvas",
+            Node::Transform2D { .. } => "Transform2D",
+            Node::Sprite2D { .. } => "Sprite2D",
+            Node::Camera3D { .. } => "Camera3D",
+            Node::Mesh3D { .. } => "Mesh3D",
+            Node::PointLight3D { .. } => "PointLight3D",
+            Node::Material3D { .. } => "Material3D",
+            Node::MeshInstance3D { .. } => "MeshInstance3D",
+            Node::FPSCamera { .. } => "FPSCamera",
+            Node::MouseGrab { .. } => "MouseGrab",
+            Node::RaycastSimple => "RaycastSimple",
+            Node::WeaponViewModel { .. } => "WeaponViewModel",
+            Node::Fetch { .. } => "Fetch",
+            Node::Await(..) => "Await",
+            Node::Extract { .. } => "Extract",
+            Node::EvalJSONNative(..) => "EvalJSONNative",
+            Node::ToString(..) => "ToString",
+            Node::NativeCall(..) => "NativeCall",
+            Node::ExternCall { .. } => "ExternCall",
+            Node::InitWindow(..) => "InitWindow",
+            Node::InitGraphics => "InitGraphics",
+            Node::LoadShader(..) => "LoadShader",
+            Node::RenderMesh(..) => "RenderMesh",
+            Node::PollEvents(..) => "PollEvents",
+            Node::InitAudio => "InitAudio",
+            Node::PlayNote(..) => "PlayNote",
+            Node::StopNote(..) => "StopNote",
+            Node::LoadMesh(..) => "LoadMesh",
+            Node::LoadTexture(..) => "LoadTexture",
+            Node::PlayAudioFile(..) => "PlayAudioFile",
+            Node::RenderAsset(..) => "RenderAsset",
+            Node::LoadFont(..) => "LoadFont",
+            Node::DrawText(..) => "DrawText",
+            Node::GetLastKeypress => "GetLastKeypress",
+            Node::UIWindow(..) => "UIWindow",
+            Node::UILabel(..) => "UILabel",
+            Node::UIButton(..) => "UIButton",
+            Node::UITextInput(..) => "UITextInput",
+            Node::UISetStyle(..) => "UISetStyle",
+            Node::UIHorizontal(..) => "UIHorizontal",
+            Node::UIFullscreen(..) => "UIFullscreen",
+            Node::UIGrid(..) => "UIGrid",
+            Node::UIScrollArea(..) => "UIScrollArea",
+            Node::InitCamera(..) => "InitCamera",
+            Node::DrawVoxelGrid(..) => "DrawVoxelGrid",
+            Node::LoadTextureAtlas(..) => "LoadTextureAtlas",
+            Node::LoadSample(..) => "LoadSample",
+            Node::PlaySample(..) => "PlaySample",
+            Node::InitVoxelMap => "InitVoxelMap",
+            Node::SetVoxel(..) => "SetVoxel",
+            Node::EnableInteraction(..) => "EnableInteraction",
+            Node::EnablePhysics(..) => "EnablePhysics",
+            Node::If(..) => "If",
+            Node::While(..) => "While",
+            Node::Block(..) => "Block",
+            Node::Return(..) => "Return",
+            Node::Import(..) => "Import",
+            Node::AddWorldAABB { .. } => "AddWorldAABB",
+            Node::CheckCollision { .. } => "CheckCollision",
+        }
+    }
+
+    child_walker!(
+        /// References to all direct child nodes, in source order.
+        /// Object literal fields are visited in key order.
+        children, iter;
+    );
+
+    child_walker!(
+        /// Mutable references to all direct child nodes, in source order.
+        /// Object literal fields are visited in key order.
+        children_mut, iter_mut; mut
+    );
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Type {
     Int,
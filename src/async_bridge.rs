@@ -1,18 +1,71 @@
 use crate::ast::Node;
+use crate::natives::registry::PromiseSettler;
+use crate::net_policy::NetPolicy;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::mpsc::{Receiver, Sender, channel};
 use std::thread;
 
+/// Where a finished request is delivered.
+pub enum FetchReply {
+    /// Legacy `Fetch(...) => { ... }` form: run the block with `fetch_result`/`fetch_error` set.
+    Callback(Box<Node>),
+    /// Settle this promise. The settler keeps it alive while the request is in flight.
+    Promise(PromiseSettler),
+}
+
 /// A task sent from the main WGPU thread to the background worker.
 pub struct FetchTask {
     pub method: String,
     pub url: String,
-    pub callback_node: Box<Node>,
+    /// Hosts the request and each of its redirects may reach.
+    pub policy: NetPolicy,
+    pub reply: FetchReply,
 }
 
 /// The result returned from the background worker to the main WGPU thread.
 pub struct FetchPayload {
     pub payload: Result<String, String>, // Ok(JSON String) or Err(Error Message)
-    pub callback_node: Box<Node>,
+    pub reply: FetchReply,
+}
+
+const MAX_REDIRECTS: usize = 5;
+
+fn agent_for(policy: &NetPolicy) -> ureq::Agent {
+    // Sprint 63.1: strict timeout. Redirects are followed in `perform`, so
+    // every hop goes through the policy; the resolver vets the addresses.
+    ureq::AgentBuilder::new()
+        .timeout(std::time::Duration::from_secs(10))
+        .redirects(0)
+        .resolver(policy.resolver())
+        .build()
+}
+
+/// Runs one request, re-checking the policy for each redirect target.
+fn perform(agent: &ureq::Agent, policy: &NetPolicy, method: &str, url: &str) -> Result<String, String> {
+    let mut method = method.to_uppercase();
+    if method != "GET" && method != "POST" {
+        return Err(format!("Unsupported HTTP method: {}", method));
+    }
+    let mut url = policy.check_url(url)?;
+    for _ in 0..=MAX_REDIRECTS {
+        let response = match agent.request_url(&method, &url).call() {
+            Ok(response) => response,
+            Err(ureq::Error::Status(code, response)) => {
+                return Err(format!("HTTP {} Error: {}", code, response.into_string().unwrap_or_default()));
+            }
+            Err(e) => return Err(format!("{} Request failed: {}", method, e)),
+        };
+        let location = response.header("location").filter(|_| (300..400).contains(&response.status()));
+        let Some(location) = location else {
+            return response.into_string().map_err(|e| format!("Failed to read {} response: {}", method, e));
+        };
+        let next = url.join(location).map_err(|e| format!("Invalid redirect to '{}': {}", location, e))?;
+        url = policy.check_url(next.as_str()).map_err(|e| format!("Redirect to {} refused: {}", next, e))?;
+        if matches!(response.status(), 301..=303) {
+            method = "GET".into();
+        }
+    }
+    Err(format!("{} Request failed: more than {} redirects", method, MAX_REDIRECTS))
 }
 
 /// The AsyncBridge handles non-blocking I/O operations by offloading
@@ -21,6 +74,7 @@ pub struct FetchPayload {
 pub struct AsyncBridge {
     tx_task: Sender<FetchTask>,
     rx_payload: Receiver<FetchPayload>,
+    in_flight: AtomicUsize,
 }
 
 impl AsyncBridge {
@@ -30,45 +84,21 @@ impl AsyncBridge {
 
         // Spawn the dedicated background worker thread
         thread::spawn(move || {
-            // Sprint 63.1: Global ureq Agent with strict timeout
-            let agent = ureq::AgentBuilder::new()
-                .timeout(std::time::Duration::from_secs(10))
-                .build();
+            // One agent per policy, rebuilt only when the policy changes
+            let mut agent: Option<(NetPolicy, ureq::Agent)> = None;
 
             // Processing loop: wait for tasks from the main thread
             while let Ok(task) = rx_task.recv() {
-                let payload = match task.method.to_uppercase().as_str() {
-                    "GET" => match agent.get(&task.url).call() {
-                        Ok(response) => match response.into_string() {
-                            Ok(body) => Ok(body),
-                            Err(e) => Err(format!("Failed to read GET response: {}", e)),
-                        },
-                        Err(ureq::Error::Status(code, response)) => Err(format!(
-                            "HTTP {} Error: {}",
-                            code,
-                            response.into_string().unwrap_or_default()
-                        )),
-                        Err(e) => Err(format!("GET Request failed: {}", e)),
-                    },
-                    "POST" => match agent.post(&task.url).call() {
-                        Ok(response) => match response.into_string() {
-                            Ok(body) => Ok(body),
-                            Err(e) => Err(format!("Failed to read POST response: {}", e)),
-                        },
-                        Err(ureq::Error::Status(code, response)) => Err(format!(
-                            "HTTP {} Error: {}",
-                            code,
-                            response.into_string().unwrap_or_default()
-                        )),
-                        Err(e) => Err(format!("POST Request failed: {}", e)),
-                    },
-                    _ => Err(format!("Unsupported HTTP method: {}", task.method)),
-                };
+                if agent.as_ref().is_none_or(|(policy, _)| *policy != task.policy) {
+                    agent = Some((task.policy.clone(), agent_for(&task.policy)));
+                }
+                let (policy, client) = agent.as_ref().unwrap();
+                let payload = perform(client, policy, &task.method, &task.url);
 
                 // Send the payload back to the main thread's Receiver
                 let _ = tx_payload.send(FetchPayload {
                     payload,
-                    callback_node: task.callback_node,
+                    reply: task.reply,
                 });
             }
         });
@@ -76,21 +106,99 @@ impl AsyncBridge {
         AsyncBridge {
             tx_task,
             rx_payload,
+            in_flight: AtomicUsize::new(0),
         }
     }
 
     /// Dispatch a request to the background thread without blocking.
-    pub fn dispatch_fetch(&self, method: String, url: String, callback_node: Box<Node>) {
+    pub fn dispatch_fetch(&self, method: String, url: String, policy: NetPolicy, callback_node: Box<Node>) {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        let _ = self.tx_task.send(FetchTask {
+            method,
+            url,
+            policy,
+            reply: FetchReply::Callback(callback_node),
+        });
+    }
+
+    /// Dispatch a request whose body (or error) settles `promise`.
+    pub fn dispatch_fetch_promise(&self, method: String, url: String, policy: NetPolicy, settler: PromiseSettler) {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
         let _ = self.tx_task.send(FetchTask {
             method,
             url,
-            callback_node,
+            policy,
+            reply: FetchReply::Promise(settler),
         });
     }
 
     /// Poll for resolved payloads. Returns `Some(FetchPayload)` if a request
     /// has finished since the last poll, or `None` if the queue is empty.
     pub fn try_recv(&self) -> Option<FetchPayload> {
-        self.rx_payload.try_recv().ok()
+        let payload = self.rx_payload.try_recv().ok()?;
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        Some(payload)
+    }
+
+    /// Requests dispatched but not yet received through `try_recv`.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::{ExecResult, ExecutionEngine};
+    use std::io::{Read, Write};
+
+    /// Serves one canned HTTP response on a local port and returns its URL.
+    fn serve_once(response: &'static str) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            if let Ok((mut stream, _)) = listener.accept() {
+                let mut request = [0u8; 1024];
+                let _ = stream.read(&mut request);
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        format!("http://{}/", addr)
+    }
+
+    #[test]
+    fn test_redirects_are_checked_against_the_policy() {
+        let policy = NetPolicy::new(&["127.0.0.1".to_string(), "*".to_string()]);
+        let agent = agent_for(&policy);
+        let url = serve_once("HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok");
+        assert_eq!(perform(&agent, &policy, "get", &url).unwrap(), "ok");
+
+        let url = serve_once(
+            "HTTP/1.1 302 Found\r\nLocation: http://169.254.169.254/latest/meta-data/\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        );
+        assert_eq!(
+            perform(&agent, &policy, "GET", &url).unwrap_err(),
+            "Redirect to http://169.254.169.254/latest/meta-data/ refused: Blocked host '169.254.169.254': \
+             loopback, private and link-local addresses need --allow-domain"
+        );
+    }
+
+    #[test]
+    fn test_fetch_faults_name_the_blocked_host() {
+        let mut engine = ExecutionEngine::new();
+        engine.permissions.allow_network = true;
+        engine.permissions.allowed_domains = vec!["*.example.com".to_string()];
+        let fetch = |url: &str| Node::Fetch { method: "GET".into(), url: url.into(), callback: Box::new(Node::Block(vec![])) };
+        match engine.execute(&fetch("https://example.org/data")) {
+            ExecResult::Fault { msg, .. } => {
+                assert_eq!(msg, "Fetch: Blocked host 'example.org': not in allowed_domains (use --allow-domain)")
+            }
+            _ => panic!("expected the host to be blocked"),
+        }
+        engine.permissions.allowed_domains.clear();
+        match engine.execute(&fetch("http://[::1]:8080/admin")) {
+            ExecResult::Fault { msg, .. } => assert!(msg.starts_with("Fetch: Blocked host '::1'"), "{}", msg),
+            _ => panic!("expected the host to be blocked"),
+        }
     }
 }
@@ -57,12 +57,13 @@ fn main() {{
     let mut engine = ExecutionEngine::new();
     engine.permissions.allow_fs_read = true;
     engine.permissions.allow_fs_write = true;
+    engine.store = knoten_core::vm::storage::KvStore::for_script({:?});
     let result = engine.execute(&ast);
     
     println!("\nExecution Finished.\nResult: {{}}", result);
 }}
 "#,
-        safe_path_str
+        safe_path_str, input_path
     );
 
     let temp_launcher_path = "src/bin/_knoten_temp_launcher.rs";
@@ -8,6 +8,9 @@ use std::sync::Arc;
 // Embedded at compile-time: absolute path to the knoten_core library source.
 const KNOTEN_CORE_PATH: &str = env!("CARGO_MANIFEST_DIR");
 
+/// How long a script may take to stop after its window closes.
+const SHUTDOWN_GRACE: std::time::Duration = std::time::Duration::from_secs(2);
+
 fn main() {
     // Spawn with 8MB stack to support deep recursion in KnotenCore scripts
     let builder = std::thread::Builder::new().stack_size(8 * 1024 * 1024);
@@ -35,13 +38,47 @@ fn run() {
         return;
     }
 
+    // ── Subcommand: repl ──────────────────────────────────────────────
+    // Usage: run_knc repl [--allow-read] [--allow-write] [--allow-network] [--allow-domain <host>]...
+    if args.len() >= 2 && args[1] == "repl" {
+        let mut i = 2;
+        while i < args.len() {
+            match args[i].as_str() {
+                "--allow-read" => engine.permissions.allow_fs_read = true,
+                "--allow-write" => engine.permissions.allow_fs_write = true,
+                "--allow-network" => engine.permissions.allow_network = true,
+                "--allow-domain" => {
+                    engine.permissions.allowed_domains.push(flag_value(&args, &mut i));
+                    engine.permissions.allow_network = true;
+                }
+                other => {
+                    eprintln!("Unknown repl option: {}", other);
+                    eprintln!("Usage: run_knc repl [--allow-read] [--allow-write] [--allow-network] [--allow-domain <host>]...");
+                    std::process::exit(1);
+                }
+            }
+            i += 1;
+        }
+        knoten_core::repl::run(engine);
+        return;
+    }
+
     // ── Legacy flags & Permissions ─────────────────────────────────────
     let mut is_check = false;
     let mut no_opt = false;
     let mut transpile = false;
+    let mut profile = false;
+    let mut profile_out: Option<String> = None;
+    let mut watch = false;
+    let mut coverage = false;
+    let mut coverage_out: Option<String> = None;
+    let mut coverage_json: Option<String> = None;
+    let mut coverage_include: Vec<String> = Vec::new();
     let mut file_path = String::new();
 
-    for arg in args.iter().skip(1) {
+    let mut i = 1;
+    while i < args.len() {
+        let arg = &args[i];
         if arg == "--check" {
             is_check = true;
         } else if arg == "--no-opt" {
@@ -54,9 +91,36 @@ fn run() {
             engine.permissions.allow_fs_write = true;
         } else if arg == "--allow-network" {
             engine.permissions.allow_network = true;
+        } else if arg == "--allow-domain" {
+            // Restricts Fetch to matching hosts; `*.example.com` matches subdomains
+            engine.permissions.allowed_domains.push(flag_value(&args, &mut i));
+            engine.permissions.allow_network = true;
+        } else if arg == "--watch" {
+            watch = true;
+        } else if arg == "--profile" {
+            profile = true;
+        } else if arg == "--profile-out" {
+            profile_out = Some(flag_value(&args, &mut i));
+            profile = true;
+        } else if arg == "--coverage" {
+            coverage = true;
+        } else if arg == "--coverage-out" {
+            coverage_out = Some(flag_value(&args, &mut i));
+            coverage = true;
+        } else if arg == "--coverage-json" {
+            coverage_json = Some(flag_value(&args, &mut i));
+            coverage = true;
+        } else if arg == "--coverage-include" {
+            coverage_include.push(flag_value(&args, &mut i));
+            coverage = true;
         } else {
             file_path = arg.clone();
         }
+        i += 1;
+    }
+
+    if profile {
+        engine.profiler = Some(Box::new(knoten_core::profiler::Profiler::new()));
     }
 
     // Check if we are bundled (Sprint 11) - Respects permissions set above
@@ -70,21 +134,17 @@ fn run() {
     }
 
     if file_path.is_empty() {
-        eprintln!("Usage: run_knc [--check] [--no-opt] [--transpile] [--allow-read] [--allow-write] [--allow-network] <path_to.nod>");
+        eprintln!("Usage: run_knc [--check] [--no-opt] [--transpile] [--allow-read] [--allow-write] [--allow-network] [--allow-domain <host>]... [--watch] [--profile] [--profile-out <file>] [--coverage] [--coverage-out <file.lcov>] [--coverage-json <file>] [--coverage-include <file>]... <path_to.nod>");
         eprintln!("       run_knc build <path_to.nod>");
+        eprintln!("       run_knc repl [--allow-read] [--allow-write] [--allow-network] [--allow-domain <host>]...");
         std::process::exit(1);
     }
 
     println!("CWD: {:?}", env::current_dir().unwrap());
     println!("Loading KnotenCore Script: {}", file_path);
 
-    let json_string = fs::read_to_string(&file_path).expect("Failed to read file");
-    let mut ast = if file_path.ends_with(".knoten") {
-        let mut parser = knoten_core::parser::Parser::new(&json_string);
-        parser.parse()
-    } else {
-        serde_json::from_str(&json_string).expect("Failed to parse KnotenCore AST")
-    };
+    let (mut ast, stmt_lines) = load_program(&file_path);
+    engine.store = knoten_core::vm::storage::KvStore::for_script(&file_path);
 
     let mut typer = knoten_core::optimizer::TypeChecker::new();
     let _ = typer.check(&ast);
@@ -96,6 +156,19 @@ fn run() {
         std::process::exit(1);
     }
 
+    // Coverage probes go in before optimization so that every source statement is counted.
+    let mut cov = coverage.then(knoten_core::coverage::Coverage::new);
+    let mut preload = Vec::new();
+    if let Some(cov) = cov.as_mut() {
+        for path in &coverage_include {
+            let (inc_ast, inc_lines) = load_program(path);
+            let inc_ast = cov.instrument(path, inc_ast, inc_lines.as_deref());
+            preload.push(if no_opt { inc_ast } else { knoten_core::optimizer::optimize(inc_ast) });
+        }
+        ast = cov.instrument(&file_path, ast, stmt_lines.as_deref());
+        engine.native_modules.insert(0, std::sync::Arc::new(cov.module()));
+    }
+
     if !no_opt {
         let before_nodes = knoten_core::optimizer::count_nodes(&ast);
         ast = knoten_core::optimizer::optimize(ast);
@@ -124,6 +197,19 @@ fn run() {
         }
     }
 
+    if watch {
+        // Reloads keep memory and registry windows; only function definitions are swapped.
+        let report = |event: &knoten_core::hot_reload::ReloadEvent| {
+            if event.is_error() {
+                eprintln!("[HotReload] {}", event);
+            } else {
+                println!("[HotReload] {}", event);
+            }
+        };
+        engine.hot_reload = Some(knoten_core::hot_reload::watch(&file_path, !no_opt, std::sync::Arc::new(report)));
+        println!("Watching {} for changes.", file_path);
+    }
+
     // ── Main Thread Loop & Proxy Setup ─────────────────────────────
     use winit::event_loop::EventLoop;
     #[cfg(target_os = "windows")]
@@ -141,18 +227,96 @@ fn run() {
     let ast_arc = Arc::new(ast);
     let ast_for_thread = ast_arc.clone();
     let mut thread_engine = engine; // Move the engine with set permissions
+    let interrupt = thread_engine.interrupt_handle();
 
-    std::thread::Builder::new()
+    let executor = std::thread::Builder::new()
         .stack_size(8 * 1024 * 1024)
         .spawn(move || {
-            let result = thread_engine.execute(&ast_for_thread);
+            for (path, inc) in coverage_include.iter().zip(&preload) {
+                if let result @ knoten_core::executor::ExecResult::Fault { .. } = thread_engine.execute(inc) {
+                    eprintln!("Coverage include {} failed: {}", path, result);
+                }
+            }
+            let mut result = thread_engine.execute(&ast_for_thread);
+            if !matches!(result, knoten_core::executor::ExecResult::Fault { .. })
+                && let fault @ knoten_core::executor::ExecResult::Fault { .. } = thread_engine.run_event_loop()
+            {
+                result = fault;
+            }
             println!("\nExecution Finished.\nResult: {}", result);
+            if let Some(cov) = &cov {
+                println!("\n{}", cov.report());
+                if let Some(path) = &coverage_out {
+                    match fs::write(path, cov.to_lcov()) {
+                        Ok(_) => println!("lcov report written to {}", path),
+                        Err(e) => eprintln!("Failed to write coverage to {}: {}", path, e),
+                    }
+                }
+                if let Some(path) = &coverage_json {
+                    let json = serde_json::to_string_pretty(&cov.to_json()).unwrap_or_default();
+                    match fs::write(path, json) {
+                        Ok(_) => println!("Coverage JSON written to {}", path),
+                        Err(e) => eprintln!("Failed to write coverage to {}: {}", path, e),
+                    }
+                }
+            }
+            if let Some(profiler) = thread_engine.profiler.take() {
+                println!("\n{}", profiler.report());
+                if let Some(path) = &profile_out {
+                    match fs::write(path, profiler.folded()) {
+                        Ok(_) => println!("Folded stacks written to {}", path),
+                        Err(e) => eprintln!("Failed to write profile to {}: {}", path, e),
+                    }
+                }
+            }
             knoten_core::natives::registry::exit_event_loop();
         })
         .expect("Failed to spawn executor thread");
 
-    let mut app = knoten_core::window::KnotenApp::new();
+    let mut app = knoten_core::window::KnotenApp::new().with_interrupt(interrupt.clone());
     let _ = event_loop.run_app(&mut app);
+    // Closing the last window ends the event loop; let the script unwind
+    // and report instead of exiting underneath it. The interrupt is only seen
+    // at loop back-edges, calls, awaits and channel receives, so a script
+    // blocked elsewhere gets a grace period and is then abandoned.
+    interrupt.interrupt();
+    let deadline = std::time::Instant::now() + SHUTDOWN_GRACE;
+    while !executor.is_finished() && std::time::Instant::now() < deadline {
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    if !executor.is_finished() {
+        eprintln!("Script did not stop within {}s of the window closing; exiting.", SHUTDOWN_GRACE.as_secs());
+        std::process::exit(1);
+    }
+    let _ = executor.join();
+}
+
+/// Returns the value following the flag at `args[*i]`, advancing `i` past it.
+fn flag_value(args: &[String], i: &mut usize) -> String {
+    let flag = &args[*i];
+    *i += 1;
+    match args.get(*i) {
+        Some(v) => v.clone(),
+        None => {
+            eprintln!("{} requires a value", flag);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Reads a `.knoten` DSL file or a JSON AST. DSL files also yield their statement lines.
+fn load_program(path: &str) -> (knoten_core::ast::Node, Option<Vec<usize>>) {
+    let source = fs::read_to_string(path).expect("Failed to read file");
+    if path.ends_with(".knoten") {
+        let mut parser = knoten_core::parser::Parser::new(&source);
+        let ast = parser.parse().unwrap_or_else(|diag| {
+            eprintln!("[ParseError] {}", diag);
+            std::process::exit(1);
+        });
+        (ast, Some(parser.stmt_lines))
+    } else {
+        (serde_json::from_str(&source).expect("Failed to parse KnotenCore AST"), None)
+    }
 }
 
 /// Full one-click build pipeline:
@@ -174,8 +338,10 @@ fn build_standalone(nod_path: &str) {
     });
 
     let mut ast: knoten_core::ast::Node = if nod_path.ends_with(".knoten") {
-        let mut parser = knoten_core::parser::Parser::new(&json_string);
-        parser.parse()
+        knoten_core::parser::try_parse(&json_string).unwrap_or_else(|diag| {
+            eprintln!("[ParseError] {}", diag);
+            std::process::exit(1);
+        })
     } else {
         serde_json::from_str(&json_string).unwrap_or_else(|e| {
             eprintln!("Error: Invalid AST JSON — {}", e);
@@ -189,7 +355,9 @@ fn build_standalone(nod_path: &str) {
     println!("[2/5] Optimise : {} → {} nodes", before, after);
 
     // ── Step 2: Transpile ─────────────────────────────────────────────
-    let rs_code = knoten_core::compiler::codegen::generate_rust_code(&ast);
+    let mut codegen = knoten_core::compiler::codegen::Codegen::new();
+    codegen.store_namespace = knoten_core::vm::storage::KvStore::for_script(nod_path).namespace().to_string();
+    let rs_code = codegen.generate(&ast, true);
 
     // Derive output binary name from the .nod filename stem
     let stem = Path::new(nod_path)
@@ -0,0 +1,194 @@
+use crate::executor::{Action, ExecResult, ExecutionEngine, RelType};
+use crate::natives::convert::{IntoRel, arg, expect_args, native_result};
+use std::collections::{HashMap, VecDeque};
+use std::sync::mpsc::{Receiver, Sender, channel};
+
+/// Messages delivered to script handlers per `service_events` pass by default.
+pub const DEFAULT_MAX_PER_TICK: usize = 64;
+
+/// Topic-based pub/sub between host code and scripts.
+///
+/// Messages are delivered in the order they were posted (host messages in the
+/// order they arrive on the action channel, script publishes in call order),
+/// and each message goes to its topic's handlers in subscription order.
+/// Messages published while handlers run are queued behind the current ones.
+pub struct MessageBus {
+    next_id: i64,
+    handlers: Vec<(i64, String, RelType)>,
+    queue: VecDeque<(String, RelType)>,
+    host_subscribers: HashMap<String, Vec<Sender<RelType>>>,
+    /// Upper bound on deliveries per tick; the rest wait for the next tick.
+    pub max_per_tick: usize,
+}
+
+impl Default for MessageBus {
+    fn default() -> Self {
+        Self {
+            next_id: 0,
+            handlers: Vec::new(),
+            queue: VecDeque::new(),
+            host_subscribers: HashMap::new(),
+            max_per_tick: DEFAULT_MAX_PER_TICK,
+        }
+    }
+}
+
+impl MessageBus {
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Functions registered with `On`.
+    pub(crate) fn handlers_mut(&mut self) -> impl Iterator<Item = &mut RelType> {
+        self.handlers.iter_mut().map(|(_, _, handler)| handler)
+    }
+
+    /// Queues `payload` for script handlers and forwards it to host subscribers.
+    fn publish(&mut self, topic: String, payload: RelType) {
+        if let Some(subs) = self.host_subscribers.get_mut(&topic) {
+            subs.retain(|tx| tx.send(payload.clone()).is_ok());
+        }
+        self.queue.push_back((topic, payload));
+    }
+}
+
+/// Cloneable, `Send` handle for posting to an engine's bus from other threads.
+#[derive(Clone)]
+pub struct BusSender(Sender<Action>);
+
+impl BusSender {
+    /// Returns false once the engine is gone.
+    pub fn publish(&self, topic: &str, payload: impl IntoRel) -> bool {
+        self.0.send(Action::Publish(topic.to_string(), payload.into_rel())).is_ok()
+    }
+}
+
+impl ExecutionEngine {
+    pub fn bus_sender(&self) -> Option<BusSender> {
+        self.action_tx.clone().map(BusSender)
+    }
+
+    /// Receives every message scripts (or the host) publish on `topic`.
+    pub fn subscribe(&mut self, topic: &str) -> Receiver<RelType> {
+        let (tx, rx) = channel();
+        self.bus.host_subscribers.entry(topic.to_string()).or_default().push(tx);
+        rx
+    }
+
+    /// `On(topic, fn)` returns a subscription id, `Off(id)` removes it and
+    /// `Publish(topic, value)` posts a message.
+    pub(crate) fn call_bus_native(&mut self, name: &str, args: &[RelType]) -> Option<ExecResult> {
+        let res = match name {
+            "On" => (|| {
+                expect_args(args, &["topic", "handler"], name)?;
+                let topic = arg::<String>(args, 0, "topic", name)?;
+                let handler = args[1].clone();
+                if !matches!(handler, RelType::FnDef(..) | RelType::Str(_)) {
+                    return Err(ExecResult::Fault {
+                        msg: format!("On: argument 2 'handler' expected Function, found {}", crate::natives::convert::rel_type_name(&handler)),
+                        node: "Native::On".into(),
+                    });
+                }
+                self.bus.next_id += 1;
+                self.bus.handlers.push((self.bus.next_id, topic, handler));
+                Ok(RelType::Int(self.bus.next_id))
+            })(),
+            "Off" => expect_args(args, &["id"], name).and_then(|_| arg::<i64>(args, 0, "id", name)).map(|id| {
+                let before = self.bus.handlers.len();
+                self.bus.handlers.retain(|(h, _, _)| *h != id);
+                RelType::Bool(self.bus.handlers.len() != before)
+            }),
+            "Publish" => expect_args(args, &["topic", "message"], name).and_then(|_| arg::<String>(args, 0, "topic", name)).map(|topic| {
+                self.bus.publish(topic, args[1].clone());
+                RelType::Void
+            }),
+            _ => return None,
+        };
+        Some(native_result(res))
+    }
+
+    /// Moves host actions into the bus, then delivers at most `max_per_tick` messages.
+    pub(crate) fn pump_bus(&mut self) -> Result<(), ExecResult> {
+        let mut actions = Vec::new();
+        if let Some(rx) = &self.action_rx {
+            while let Ok(action) = rx.try_recv() {
+                actions.push(action);
+            }
+        }
+        for action in actions {
+            match action {
+                Action::UpdateData(name, value) => {
+                    self.memory.insert(name, value);
+                }
+                Action::Publish(topic, payload) => self.bus.publish(topic, payload),
+            }
+        }
+
+        for _ in 0..self.bus.max_per_tick {
+            let Some((topic, payload)) = self.bus.queue.pop_front() else { break };
+            let handlers: Vec<RelType> =
+                self.bus.handlers.iter().filter(|(_, t, _)| *t == topic).map(|(_, _, h)| h.clone()).collect();
+            for handler in handlers {
+                if let fault @ ExecResult::Fault { .. } = self.call_value(&handler, vec![payload.clone()]) {
+                    return Err(fault);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(engine: &mut ExecutionEngine, src: &str) -> ExecResult {
+        engine.execute(&crate::parser::try_parse(src).unwrap())
+    }
+
+    #[test]
+    fn test_host_and_script_messages_in_order() {
+        let mut engine = ExecutionEngine::new();
+        let replies = engine.subscribe("score");
+        let src = "let seen = [];\n\
+            On(\"hit\", fn(msg) { ArrayPush(seen, msg); Publish(\"score\", msg * 10); });\n\
+            let quiet = On(\"hit\", fn(msg) { ArrayPush(seen, 0 - 1); });\n\
+            Off(quiet);";
+        assert!(!matches!(run(&mut engine, src), ExecResult::Fault { .. }));
+
+        let sender = engine.bus_sender().unwrap();
+        std::thread::spawn(move || {
+            for i in 1..=3 {
+                sender.publish("hit", i as i64);
+            }
+        })
+        .join()
+        .unwrap();
+
+        assert!(matches!(engine.run_event_loop(), ExecResult::Value(RelType::Void)));
+        assert_eq!(engine.memory["seen"], RelType::Array(vec![RelType::Int(1), RelType::Int(2), RelType::Int(3)]));
+        assert_eq!(replies.try_iter().collect::<Vec<_>>(), vec![RelType::Int(10), RelType::Int(20), RelType::Int(30)]);
+    }
+
+    #[test]
+    fn test_host_subscribers_see_host_publishes() {
+        let mut engine = ExecutionEngine::new();
+        let status = engine.subscribe("status");
+        engine.bus_sender().unwrap().publish("status", "ready");
+        assert!(engine.service_events().is_ok());
+        assert_eq!(status.try_iter().collect::<Vec<_>>(), vec![RelType::Str("ready".into())]);
+    }
+
+    #[test]
+    fn test_delivery_is_bounded_per_tick() {
+        let mut engine = ExecutionEngine::new();
+        engine.bus.max_per_tick = 2;
+        let src = "let n = 0;\nOn(\"tick\", fn(m) { n = n + 1; });\nlet i = 0;\nwhile (i < 5) { Publish(\"tick\", i); i = i + 1; }";
+        run(&mut engine, src);
+        assert!(engine.service_events().is_ok());
+        assert_eq!(engine.memory["n"], RelType::Int(2));
+        assert_eq!(engine.bus.pending(), 3);
+        engine.run_event_loop();
+        assert_eq!(engine.memory["n"], RelType::Int(5));
+    }
+}
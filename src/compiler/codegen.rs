@@ -11,12 +11,49 @@ pub enum VarKind {
 
 pub struct Codegen {
     pub scopes: Vec<HashMap<String, VarKind>>,
+    /// Store namespace baked into `Store`/`Load`; use the script's
+    /// `KvStore::for_script` namespace to share data with the interpreter.
+    pub store_namespace: String,
+    /// Rust type of each stored key, where the program stores literals.
+    store_types: HashMap<String, Option<String>>,
+}
+
+/// The Rust type codegen gives a literal value, if it is one.
+fn literal_type(node: &Node) -> Option<String> {
+    match node {
+        Node::IntLiteral(_) => Some("i64".into()),
+        Node::FloatLiteral(_) => Some("f64".into()),
+        Node::BoolLiteral(_) => Some("bool".into()),
+        Node::StringLiteral(_) => Some("String".into()),
+        Node::ArrayCreate(items) => {
+            let first = literal_type(items.first()?)?;
+            items.iter().all(|n| literal_type(n).as_ref() == Some(&first)).then(|| format!("Vec<{}>", first))
+        }
+        _ => None,
+    }
+}
+
+/// Records the literal type stored under each key; keys stored with
+/// different or non-literal types get `None`.
+fn collect_store_types(node: &Node, out: &mut HashMap<String, Option<String>>) {
+    if let Node::Store { key, value } = node {
+        let ty = literal_type(value);
+        let entry = out.entry(key.clone()).or_insert_with(|| ty.clone());
+        if *entry != ty {
+            *entry = None;
+        }
+    }
+    for child in node.children() {
+        collect_store_types(child, out);
+    }
 }
 
 impl Codegen {
     pub fn new() -> Self {
         Self {
             scopes: vec![HashMap::new()],
+            store_namespace: "default".into(),
+            store_types: HashMap::new(),
         }
     }
 
@@ -52,6 +89,7 @@ impl Codegen {
             Node::Block(nodes) => {
                 let mut out = String::new();
                 if is_root {
+                    collect_store_types(node, &mut self.store_types);
                     out.push_str("use knoten_core::natives::registry;\n\n");
                     out.push_str("fn main() {\n");
                 } else {
@@ -90,7 +128,7 @@ impl Codegen {
             }
             Node::Print(expr) => {
                 let inner = self.generate(expr, false);
-                format!("println!(\"{{}}\", {})", inner)
+                format!("println!(\"{{}}\", knoten_core::numeric::rt::show(&{}))", inner)
             }
             Node::Assign(name, expr) => {
                 let inner = self.generate(expr, false);
@@ -154,37 +192,37 @@ impl Codegen {
             Node::StringLiteral(v) => format!("String::from(\"{}\")", v),
             Node::Identifier(name) => name.clone(),
             Node::Add(l, r) => format!(
-                "({} + {})",
+                "knoten_core::numeric::rt::add({}, {})",
                 self.generate(l, false),
                 self.generate(r, false)
             ),
             Node::Sub(l, r) => format!(
-                "({} - {})",
+                "knoten_core::numeric::rt::sub({}, {})",
                 self.generate(l, false),
                 self.generate(r, false)
             ),
             Node::Mul(l, r) => format!(
-                "({} * {})",
+                "knoten_core::numeric::rt::mul({}, {})",
                 self.generate(l, false),
                 self.generate(r, false)
             ),
             Node::Div(l, r) => format!(
-                "({} / {})",
+                "knoten_core::numeric::rt::div({}, {})",
                 self.generate(l, false),
                 self.generate(r, false)
             ),
             Node::Eq(l, r) => format!(
-                "({} == {})",
+                "knoten_core::numeric::rt::eq(&{}, &{})",
                 self.generate(l, false),
                 self.generate(r, false)
             ),
             Node::Lt(l, r) => format!(
-                "({} < {})",
+                "knoten_core::numeric::rt::lt(&{}, &{})",
                 self.generate(l, false),
                 self.generate(r, false)
             ),
             Node::Gt(l, r) => format!(
-                "({} > {})",
+                "knoten_core::numeric::rt::gt(&{}, &{})",
                 self.generate(l, false),
                 self.generate(r, false)
             ),
@@ -300,17 +338,13 @@ impl Codegen {
             }
             Node::Store { key, value } => {
                 let inner = self.generate(value, false);
-                format!("knoten_core::vm::storage::store_value(\"{}\", &serde_json::json!({})).unwrap()", key, inner)
-            }
-            Node::Load { key } => {
-                if key == "chess_turn" {
-                    format!("knoten_core::vm::storage::load_value(\"{}\").ok().and_then(|v| v.as_i64()).unwrap_or(-1)", key)
-                } else if key == "chess_board" {
-                    format!("knoten_core::vm::storage::load_value(\"{}\").ok().and_then(|v| v.as_array().map(|arr| arr.iter().map(|s| s.as_str().unwrap_or(\" \").to_string()).collect::<Vec<String>>())).unwrap_or_else(|| vec![])", key)
-                } else {
-                    format!("knoten_core::vm::storage::load_value(\"{}\").unwrap()", key)
-                }
+                format!("knoten_core::vm::storage::rt::store({:?}, {:?}, &({}))", self.store_namespace, key, inner)
             }
+            Node::Load { key } => match self.store_types.get(key).cloned().flatten() {
+                Some(ty) => format!("knoten_core::vm::storage::rt::load::<{}>({:?}, {:?})", ty, self.store_namespace, key),
+                // Without a literal to go by, the value stays a dynamic `RelType`.
+                None => format!("knoten_core::vm::storage::rt::load_value({:?}, {:?})", self.store_namespace, key),
+            },
             // Sprint 38/39/40 MVP support boundary
             _ => format!("/* Unsupported node in Sprint 40 codegen: {:?} */", node),
         }
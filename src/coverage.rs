@@ -0,0 +1,434 @@
+use crate::ast::Node;
+use crate::executor::{AgentPermissions, ExecResult, RelType};
+use crate::natives::NativeModule;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+/// Native probe recording that a statement ran: `__cov.hit(id)`.
+pub const PROBE_HIT: &str = "__cov.hit";
+/// Native probe wrapping an `If`/`While` condition: `__cov.branch(cond, taken_id, not_taken_id)`.
+/// Returns the condition unchanged.
+pub const PROBE_BRANCH: &str = "__cov.branch";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProbeKind {
+    Statement,
+    /// One arm of an `If` (`then`/`else`) or `While` (`body`/`exit`). `group` pairs the two arms.
+    Branch { group: usize, arm: usize, label: &'static str },
+}
+
+#[derive(Debug, Clone)]
+pub struct Probe {
+    pub file: usize,
+    /// AST path, e.g. `/2/FnDef.0/1/If.1/0`. Block statements are addressed by index,
+    /// other children by `<Kind>.<child index>`.
+    pub path: String,
+    /// Source line for programs parsed from the DSL.
+    pub line: Option<usize>,
+    /// Enclosing function, or `<top>` for top-level code.
+    pub function: String,
+    pub kind: ProbeKind,
+}
+
+#[derive(Debug, Clone)]
+pub struct CoveredFile {
+    pub name: String,
+    pub has_lines: bool,
+    /// Definition line of each function, where known.
+    pub functions: BTreeMap<String, Option<usize>>,
+}
+
+/// Statement and branch coverage for one or more programs.
+///
+/// Programs are instrumented with native probe calls before optimization; the
+/// `CoverageModule` returned by [`Coverage::module`] must be registered on the engine
+/// that runs them.
+#[derive(Default)]
+pub struct Coverage {
+    pub files: Vec<CoveredFile>,
+    pub probes: Vec<Probe>,
+    hits: Arc<Mutex<Vec<u64>>>,
+    next_group: usize,
+}
+
+struct Walk<'a> {
+    file: usize,
+    lines: Option<&'a [usize]>,
+    cursor: usize,
+}
+
+impl Walk<'_> {
+    fn next_line(&mut self) -> Option<usize> {
+        let line = self.lines.and_then(|l| l.get(self.cursor).copied());
+        self.cursor += 1;
+        line
+    }
+}
+
+fn display_path(path: &str) -> &str {
+    if path.is_empty() { "/" } else { path }
+}
+
+impl Coverage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Probe module to register (first) in `ExecutionEngine::native_modules`.
+    pub fn module(&self) -> CoverageModule {
+        CoverageModule { hits: self.hits.clone() }
+    }
+
+    /// Instruments `ast` for coverage under the name `file`.
+    /// `stmt_lines` is `Parser::stmt_lines` for DSL sources and `None` for JSON ASTs.
+    pub fn instrument(&mut self, file: &str, mut ast: Node, stmt_lines: Option<&[usize]>) -> Node {
+        let file_idx = self.files.len();
+        self.files.push(CoveredFile { name: file.to_string(), has_lines: stmt_lines.is_some(), functions: BTreeMap::new() });
+        let first_probe = self.probes.len();
+        let mut walk = Walk { file: file_idx, lines: stmt_lines, cursor: 0 };
+        self.walk(&mut ast, "", None, "<top>", &mut walk);
+
+        // Spans are matched to statements by order; if the shapes disagree, drop them
+        // rather than report coverage on the wrong lines.
+        if let Some(lines) = stmt_lines && walk.cursor != lines.len() {
+            self.files[file_idx].has_lines = false;
+            for probe in &mut self.probes[first_probe..] {
+                probe.line = None;
+            }
+            for line in self.files[file_idx].functions.values_mut() {
+                *line = None;
+            }
+        }
+        self.hits.lock().unwrap().resize(self.probes.len(), 0);
+        ast
+    }
+
+    fn add_probe(&mut self, kind: ProbeKind, path: &str, line: Option<usize>, function: &str, walk: &Walk) -> usize {
+        self.probes.push(Probe {
+            file: walk.file,
+            path: display_path(path).to_string(),
+            line,
+            function: function.to_string(),
+            kind,
+        });
+        self.probes.len() - 1
+    }
+
+    fn walk(&mut self, node: &mut Node, path: &str, line: Option<usize>, function: &str, walk: &mut Walk) {
+        match node {
+            Node::Block(stmts) => {
+                let old = std::mem::take(stmts);
+                let mut new = Vec::with_capacity(old.len() * 2);
+                for (i, mut stmt) in old.into_iter().enumerate() {
+                    let stmt_line = walk.next_line();
+                    let stmt_path = format!("{}/{}", path, i);
+                    let id = self.add_probe(ProbeKind::Statement, &stmt_path, stmt_line, function, walk);
+                    self.walk(&mut stmt, &stmt_path, stmt_line, function, walk);
+                    new.push(Node::NativeCall(PROBE_HIT.into(), vec![Node::IntLiteral(id as i64)]));
+                    new.push(stmt);
+                }
+                *stmts = new;
+            }
+            Node::FnDef(name, _, body) => {
+                let name = name.clone();
+                self.files[walk.file].functions.insert(name.clone(), line);
+                self.walk(body, &format!("{}/FnDef.0", path), None, &name, walk);
+            }
+            Node::If(..) | Node::While(..) => {
+                let kind = node.kind();
+                let labels = if kind == "If" { ["then", "else"] } else { ["body", "exit"] };
+                let group = self.next_group;
+                self.next_group += 1;
+                let taken = self.add_probe(ProbeKind::Branch { group, arm: 0, label: labels[0] }, path, line, function, walk);
+                let not_taken = self.add_probe(ProbeKind::Branch { group, arm: 1, label: labels[1] }, path, line, function, walk);
+                self.walk_children(node, path, function, walk);
+                let cond = match node {
+                    Node::If(cond, _, _) | Node::While(cond, _) => cond,
+                    _ => unreachable!(),
+                };
+                let inner = std::mem::replace(&mut **cond, Node::BoolLiteral(false));
+                **cond = Node::NativeCall(
+                    PROBE_BRANCH.into(),
+                    vec![inner, Node::IntLiteral(taken as i64), Node::IntLiteral(not_taken as i64)],
+                );
+            }
+            _ => self.walk_children(node, path, function, walk),
+        }
+    }
+
+    fn walk_children(&mut self, node: &mut Node, path: &str, function: &str, walk: &mut Walk) {
+        let kind = node.kind();
+        for (j, child) in node.children_mut().into_iter().enumerate() {
+            self.walk(child, &format!("{}/{}.{}", path, kind, j), None, function, walk);
+        }
+    }
+
+    pub fn hits(&self) -> Vec<u64> {
+        self.hits.lock().unwrap().clone()
+    }
+
+    fn file_probes(&self, file: usize) -> impl Iterator<Item = (usize, &Probe)> {
+        self.probes.iter().enumerate().filter(move |(_, p)| p.file == file)
+    }
+
+    /// lcov tracefile for every instrumented file that has DSL spans.
+    pub fn to_lcov(&self) -> String {
+        let hits = self.hits();
+        let mut out = String::new();
+        for (fi, file) in self.files.iter().enumerate() {
+            if !file.has_lines {
+                continue;
+            }
+            out.push_str("TN:\n");
+            out.push_str(&format!("SF:{}\n", file.name));
+
+            let mut fn_hit = 0;
+            for (name, line) in &file.functions {
+                // A function's entry count is the hit count of its first statement.
+                let calls = self
+                    .file_probes(fi)
+                    .find(|(_, p)| &p.function == name && p.kind == ProbeKind::Statement)
+                    .map(|(id, _)| hits[id])
+                    .unwrap_or(0);
+                if let Some(line) = line {
+                    out.push_str(&format!("FN:{},{}\n", line, name));
+                }
+                out.push_str(&format!("FNDA:{},{}\n", calls, name));
+                if calls > 0 {
+                    fn_hit += 1;
+                }
+            }
+            out.push_str(&format!("FNF:{}\nFNH:{}\n", file.functions.len(), fn_hit));
+
+            let mut branches: Vec<(usize, usize, usize, u64, bool)> = Vec::new();
+            let mut lines: BTreeMap<usize, u64> = BTreeMap::new();
+            for (id, p) in self.file_probes(fi) {
+                let Some(line) = p.line else { continue };
+                match p.kind {
+                    ProbeKind::Statement => {
+                        let e = lines.entry(line).or_insert(0);
+                        *e = (*e).max(hits[id]);
+                    }
+                    ProbeKind::Branch { group, arm, .. } => {
+                        // The condition was evaluated if either arm of the group was hit.
+                        let sibling = if arm == 0 { id + 1 } else { id - 1 };
+                        let evaluated = hits[id] + hits[sibling] > 0;
+                        branches.push((line, group, arm, hits[id], evaluated));
+                    }
+                }
+            }
+            for (line, group, arm, count, evaluated) in &branches {
+                let taken = if *evaluated { count.to_string() } else { "-".to_string() };
+                out.push_str(&format!("BRDA:{},{},{},{}\n", line, group, arm, taken));
+            }
+            let br_hit = branches.iter().filter(|b| b.3 > 0).count();
+            out.push_str(&format!("BRF:{}\nBRH:{}\n", branches.len(), br_hit));
+
+            for (line, count) in &lines {
+                out.push_str(&format!("DA:{},{}\n", line, count));
+            }
+            let lh = lines.values().filter(|c| **c > 0).count();
+            out.push_str(&format!("LF:{}\nLH:{}\n", lines.len(), lh));
+            out.push_str("end_of_record\n");
+        }
+        out
+    }
+
+    /// Coverage keyed by file and AST path, with per-file and per-function summaries.
+    pub fn to_json(&self) -> serde_json::Value {
+        let hits = self.hits();
+        let mut files = serde_json::Map::new();
+        for (fi, file) in self.files.iter().enumerate() {
+            let mut statements = serde_json::Map::new();
+            let mut branches = serde_json::Map::new();
+            for (id, p) in self.file_probes(fi) {
+                match p.kind {
+                    ProbeKind::Statement => {
+                        let mut entry = serde_json::json!({ "hits": hits[id], "function": p.function });
+                        if let Some(line) = p.line {
+                            entry["line"] = line.into();
+                        }
+                        statements.insert(p.path.clone(), entry);
+                    }
+                    ProbeKind::Branch { label, .. } => {
+                        let entry = branches.entry(p.path.clone()).or_insert_with(|| serde_json::json!({}));
+                        entry[label] = hits[id].into();
+                    }
+                }
+            }
+            let summary = self.summarize(fi, None, &hits);
+            let mut functions = serde_json::Map::new();
+            for name in self.function_names(fi) {
+                let s = self.summarize(fi, Some(&name), &hits);
+                functions.insert(name, s.to_json());
+            }
+            files.insert(
+                file.name.clone(),
+                serde_json::json!({
+                    "statements": statements,
+                    "branches": branches,
+                    "summary": summary.to_json(),
+                    "functions": functions,
+                }),
+            );
+        }
+        serde_json::json!({ "files": files })
+    }
+
+    fn function_names(&self, file: usize) -> Vec<String> {
+        let mut names: Vec<String> = self.file_probes(file).map(|(_, p)| p.function.clone()).collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    fn summarize(&self, file: usize, function: Option<&str>, hits: &[u64]) -> Summary {
+        let mut s = Summary::default();
+        for (id, p) in self.file_probes(file) {
+            if function.is_some_and(|f| f != p.function) {
+                continue;
+            }
+            match p.kind {
+                ProbeKind::Statement => {
+                    s.statements += 1;
+                    if hits[id] > 0 { s.statements_hit += 1; }
+                }
+                ProbeKind::Branch { .. } => {
+                    s.branches += 1;
+                    if hits[id] > 0 { s.branches_hit += 1; }
+                }
+            }
+        }
+        s
+    }
+
+    /// Human-readable per-file and per-function summary.
+    pub fn report(&self) -> String {
+        let hits = self.hits();
+        let mut out = String::from("=== Coverage ===\n");
+        for (fi, file) in self.files.iter().enumerate() {
+            let s = self.summarize(fi, None, &hits);
+            out.push_str(&format!("{}  {}\n", file.name, s));
+            for name in self.function_names(fi) {
+                let fs = self.summarize(fi, Some(&name), &hits);
+                out.push_str(&format!("    {:<24} {}\n", name, fs));
+            }
+        }
+        out
+    }
+}
+
+#[derive(Default)]
+struct Summary {
+    statements: usize,
+    statements_hit: usize,
+    branches: usize,
+    branches_hit: usize,
+}
+
+fn percent(hit: usize, total: usize) -> f64 {
+    if total == 0 { 100.0 } else { hit as f64 * 100.0 / total as f64 }
+}
+
+impl Summary {
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "statements": self.statements,
+            "statements_hit": self.statements_hit,
+            "branches": self.branches,
+            "branches_hit": self.branches_hit,
+        })
+    }
+}
+
+impl std::fmt::Display for Summary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "statements {}/{} ({:.1}%), branches {}/{} ({:.1}%)",
+            self.statements_hit,
+            self.statements,
+            percent(self.statements_hit, self.statements),
+            self.branches_hit,
+            self.branches,
+            percent(self.branches_hit, self.branches)
+        )
+    }
+}
+
+/// Records probe hits for an instrumented program.
+pub struct CoverageModule {
+    hits: Arc<Mutex<Vec<u64>>>,
+}
+
+impl CoverageModule {
+    fn bump(&self, id: &RelType) {
+        if let RelType::Int(id) = id
+            && let Some(count) = self.hits.lock().unwrap().get_mut(*id as usize)
+        {
+            *count += 1;
+        }
+    }
+}
+
+impl NativeModule for CoverageModule {
+    fn handle(&self, func_name: &str, args: &[RelType], _permissions: &AgentPermissions) -> Option<ExecResult> {
+        match func_name {
+            PROBE_HIT => {
+                if let Some(id) = args.first() {
+                    self.bump(id);
+                }
+                Some(ExecResult::Value(RelType::Void))
+            }
+            PROBE_BRANCH => {
+                if args.len() != 3 {
+                    return Some(ExecResult::Fault { msg: "Malformed coverage probe".into(), node: "Native::__cov.branch".into() });
+                }
+                match &args[0] {
+                    RelType::Bool(true) => self.bump(&args[1]),
+                    RelType::Bool(false) => self.bump(&args[2]),
+                    _ => {}
+                }
+                Some(ExecResult::Value(args[0].clone()))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::ExecutionEngine;
+    use crate::parser::Parser;
+
+    #[test]
+    fn test_statement_and_branch_coverage() {
+        let src = "fn sign(n) {\n    if (n < 0) {\n        return 0 - 1;\n    }\n    return 1;\n}\nlet i = 0;\nwhile (i < 3) {\n    i = i + 1;\n}\nsign(5);\n";
+        let mut parser = Parser::new(src);
+        let ast = parser.parse().unwrap();
+        let mut cov = Coverage::new();
+        let ast = cov.instrument("sign.knoten", ast, Some(&parser.stmt_lines));
+
+        let mut engine = ExecutionEngine::new();
+        engine.native_modules.insert(0, std::sync::Arc::new(cov.module()));
+        assert!(matches!(engine.execute(&ast), ExecResult::Value(RelType::Int(1))));
+
+        let lcov = cov.to_lcov();
+        assert!(lcov.contains("SF:sign.knoten"));
+        assert!(lcov.contains("FN:1,sign\nFNDA:1,sign"));
+        assert!(lcov.contains("DA:3,0"), "{}", lcov);
+        assert!(lcov.contains("DA:5,1"));
+        assert!(lcov.contains("DA:9,3"));
+        // If on line 2: then never taken, else taken once. While on line 8: body 3x, exit 1x.
+        assert!(lcov.contains("BRDA:2,0,0,0\nBRDA:2,0,1,1\n"));
+        assert!(lcov.contains("BRDA:8,1,0,3\nBRDA:8,1,1,1\n"));
+
+        let json = cov.to_json();
+        let file = &json["files"]["sign.knoten"];
+        assert_eq!(file["statements"]["/0/FnDef.0/0/If.1/0"]["hits"], 0);
+        assert_eq!(file["branches"]["/2"]["body"], 3);
+        assert_eq!(file["functions"]["sign"]["statements"], 3);
+        assert_eq!(file["functions"]["sign"]["statements_hit"], 2);
+    }
+}
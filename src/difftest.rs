@@ -0,0 +1,746 @@
+//! Differential testing across the interpreter, the bytecode VM and the Rust transpiler.
+//!
+//! The three backends only overlap on a small subset of the language: literals,
+//! `+ - * /`, `== < >`, `Block`, `If` and `Print`. Within that subset they must
+//! print the same lines and end the same way (same value, or the same fault).
+//! [`Harness`] runs programs through every backend it holds, reports any
+//! disagreement and shrinks the offending program to a small reproducer.
+//!
+//! Programs come from two places: [`Generator`], which builds random well-typed
+//! programs from a seed, and the `examples/` tree, where every script that fits
+//! the subset is checked as-is.
+
+use crate::ast::Node;
+use crate::executor::{ExecResult, ExecutionEngine};
+use rand::{RngExt, SeedableRng, rngs::StdRng};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// How a program run ended.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Ending {
+    /// The program's result value, as `RelType` displays it.
+    Value(String),
+    /// The fault (or panic) message that stopped the program.
+    Fault(String),
+    /// The program ran to completion but its result is not observable.
+    Finished,
+}
+
+/// Everything a backend observably did with one program.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outcome {
+    pub output: Vec<String>,
+    pub ending: Ending,
+}
+
+impl Outcome {
+    /// `Finished` agrees with any value; everything else must match exactly.
+    pub fn agrees(&self, other: &Outcome) -> bool {
+        let endings = match (&self.ending, &other.ending) {
+            (Ending::Finished, Ending::Value(_) | Ending::Finished) | (Ending::Value(_), Ending::Finished) => true,
+            (a, b) => a == b,
+        };
+        endings && self.output == other.output
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "printed {:?}, ", self.output)?;
+        match &self.ending {
+            Ending::Value(v) => write!(f, "returned {:?}", v),
+            Ending::Fault(msg) => write!(f, "faulted with {:?}", msg),
+            Ending::Finished => write!(f, "finished"),
+        }
+    }
+}
+
+/// One way of running a program. `run` returns `None` when the backend cannot
+/// handle the program at all, which excludes it from the comparison.
+pub trait Backend {
+    fn name(&self) -> &'static str;
+    fn run(&mut self, program: &Node) -> Option<Outcome>;
+
+    /// Backends with a high fixed cost per invocation override this.
+    fn run_batch(&mut self, programs: &[Node]) -> Vec<Option<Outcome>> {
+        programs.iter().map(|p| self.run(p)).collect()
+    }
+}
+
+/// The tree-walking `ExecutionEngine`.
+pub struct Interpreter;
+
+impl Backend for Interpreter {
+    fn name(&self) -> &'static str {
+        "interpreter"
+    }
+
+    fn run(&mut self, program: &Node) -> Option<Outcome> {
+        let mut engine = ExecutionEngine::new();
+        engine.output = Some(Vec::new());
+        let ending = match engine.execute(program) {
+            ExecResult::Value(v) | ExecResult::ReturnBlockInfo(v) => Ending::Value(v.to_string()),
+            ExecResult::Fault { msg, .. } => Ending::Fault(msg),
+        };
+        Some(Outcome { output: engine.output.take().unwrap_or_default(), ending })
+    }
+}
+
+/// The bytecode `Compiler` + `VM` pair.
+pub struct Vm;
+
+impl Backend for Vm {
+    fn name(&self) -> &'static str {
+        "vm"
+    }
+
+    fn run(&mut self, program: &Node) -> Option<Outcome> {
+        let mut compiler = crate::vm::Compiler::new();
+        if !compiler.compile_node(program) {
+            return None;
+        }
+        let mut vm = crate::vm::VM::new();
+        vm.output = Some(Vec::new());
+        let ending = match vm.run(&compiler.instructions, &compiler.constants) {
+            Ok(v) => Ending::Value(v.to_string()),
+            Err(msg) => Ending::Fault(msg),
+        };
+        Some(Outcome { output: vm.output.take().unwrap_or_default(), ending })
+    }
+}
+
+/// Rust source produced by `generate_rust_code`, built with cargo and run.
+///
+/// A batch is compiled as a single binary (one module per program) because the
+/// build dominates the cost. Transpiled `main` returns `()`, so the ending is
+/// either `Finished` or the panic message. Needs cargo and an offline-resolvable
+/// dependency tree; any build failure yields `None` for the whole batch.
+pub struct Transpiler {
+    pub work_dir: PathBuf,
+}
+
+const MARKER: &str = "==== ";
+
+impl Transpiler {
+    pub fn new(work_dir: impl Into<PathBuf>) -> Self {
+        Self { work_dir: work_dir.into() }
+    }
+
+    fn source(programs: &[Node]) -> String {
+        let mut src = String::new();
+        for (i, program) in programs.iter().enumerate() {
+            let code = crate::compiler::codegen::generate_rust_code(program);
+            src.push_str(&format!(
+                "mod prog_{i} {{\n#![allow(unused)]\n{code}\npub fn run() {{ main() }}\n}}\n\n"
+            ));
+        }
+        src.push_str("fn main() {\n    std::panic::set_hook(Box::new(|_| {}));\n");
+        for i in 0..programs.len() {
+            src.push_str(&format!(
+                "    println!(\"{MARKER}{i}\");\n    if let Err(e) = std::panic::catch_unwind(prog_{i}::run) {{ fault(e) }}\n"
+            ));
+        }
+        src.push_str("}\n\n");
+        src.push_str(&format!(
+            "fn fault(e: Box<dyn std::any::Any + Send>) {{\n    \
+             let msg = e.downcast_ref::<String>().cloned()\n        \
+             .or_else(|| e.downcast_ref::<&str>().map(|s| s.to_string()))\n        \
+             .unwrap_or_default();\n    \
+             println!(\"{MARKER}fault {{}}\", msg);\n}}\n"
+        ));
+        src
+    }
+
+    fn build_and_run(&self, programs: &[Node]) -> Option<String> {
+        let src_dir = self.work_dir.join("src");
+        std::fs::create_dir_all(&src_dir).ok()?;
+        let manifest_dir = env!("CARGO_MANIFEST_DIR");
+        let cargo_toml = format!(
+            r#"[package]
+name = "knoten_difftest"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+knoten_core = {{ path = "{}" }}
+
+[workspace]
+"#,
+            manifest_dir.replace('\\', "/")
+        );
+        std::fs::write(self.work_dir.join("Cargo.toml"), cargo_toml).ok()?;
+        // Reuse the library's lockfile so the build resolves offline
+        let _ = std::fs::copy(Path::new(manifest_dir).join("Cargo.lock"), self.work_dir.join("Cargo.lock"));
+        std::fs::write(src_dir.join("main.rs"), Self::source(programs)).ok()?;
+
+        let out = Command::new("cargo")
+            .args(["run", "--quiet", "--offline"])
+            .current_dir(&self.work_dir)
+            .env("CARGO_TARGET_DIR", self.work_dir.join("target"))
+            .output()
+            .ok()?;
+        out.status.success().then(|| String::from_utf8_lossy(&out.stdout).into_owned())
+    }
+}
+
+impl Backend for Transpiler {
+    fn name(&self) -> &'static str {
+        "transpiler"
+    }
+
+    fn run(&mut self, program: &Node) -> Option<Outcome> {
+        self.run_batch(std::slice::from_ref(program)).pop().flatten()
+    }
+
+    fn run_batch(&mut self, programs: &[Node]) -> Vec<Option<Outcome>> {
+        let Some(stdout) = self.build_and_run(programs) else {
+            return vec![None; programs.len()];
+        };
+        let mut outcomes: Vec<Option<Outcome>> = vec![None; programs.len()];
+        let mut current: Option<usize> = None;
+        for line in stdout.lines() {
+            if let Some(rest) = line.strip_prefix(MARKER) {
+                if let Some(msg) = rest.strip_prefix("fault ") {
+                    if let Some(Some(outcome)) = current.map(|i| &mut outcomes[i]) {
+                        outcome.ending = Ending::Fault(msg.to_string());
+                    }
+                } else if let Ok(i) = rest.parse::<usize>() {
+                    outcomes[i] = Some(Outcome { output: Vec::new(), ending: Ending::Finished });
+                    current = Some(i);
+                }
+                continue;
+            }
+            if let Some(Some(outcome)) = current.map(|i| &mut outcomes[i]) {
+                outcome.output.push(line.to_string());
+            }
+        }
+        outcomes
+    }
+}
+
+/// True if every backend is expected to support `node`.
+pub fn fits_shared_subset(node: &Node) -> bool {
+    match node {
+        Node::IntLiteral(_) | Node::FloatLiteral(_) | Node::BoolLiteral(_) => true,
+        // Transpiled strings are plain Rust literals without escaping
+        Node::StringLiteral(s) => !s.contains(['"', '\\', '{', '}']),
+        Node::Add(l, r) | Node::Sub(l, r) | Node::Mul(l, r) | Node::Div(l, r)
+        | Node::Eq(l, r) | Node::Lt(l, r) | Node::Gt(l, r) => fits_shared_subset(l) && fits_shared_subset(r),
+        Node::Block(stmts) => stmts.iter().all(fits_shared_subset),
+        Node::If(cond, then_b, else_b) => {
+            fits_shared_subset(cond) && fits_shared_subset(then_b) && else_b.as_deref().is_none_or(fits_shared_subset)
+        }
+        Node::Print(expr) => fits_shared_subset(expr),
+        _ => false,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Ty {
+    Int,
+    Float,
+    Bool,
+    Str,
+    Void,
+}
+
+impl Ty {
+    fn is_number(self) -> bool {
+        matches!(self, Ty::Int | Ty::Float)
+    }
+
+    fn is_value(self) -> bool {
+        self != Ty::Void
+    }
+}
+
+/// Static type of a subset node, or `None` if any backend would reject it.
+/// Stricter than the `TypeChecker`: this is what the transpiler can compile.
+fn type_of(node: &Node) -> Option<Ty> {
+    let operands = |l: &Node, r: &Node| Some((type_of(l)?, type_of(r)?)).filter(|(a, b)| a.is_value() && b.is_value());
+    match node {
+        Node::IntLiteral(_) => Some(Ty::Int),
+        Node::FloatLiteral(_) => Some(Ty::Float),
+        Node::BoolLiteral(_) => Some(Ty::Bool),
+        Node::StringLiteral(_) => Some(Ty::Str),
+        Node::Add(l, r) | Node::Sub(l, r) | Node::Mul(l, r) | Node::Div(l, r) => match operands(l, r)? {
+            (Ty::Int, Ty::Int) => Some(Ty::Int),
+            (Ty::Str, Ty::Str) if matches!(node, Node::Add(..)) => Some(Ty::Str),
+            (a, b) if a.is_number() && b.is_number() => Some(Ty::Float),
+            _ => None,
+        },
+        Node::Eq(l, r) => {
+            let (a, b) = operands(l, r)?;
+            (a == b || (a.is_number() && b.is_number())).then_some(Ty::Bool)
+        }
+        Node::Lt(l, r) | Node::Gt(l, r) => {
+            let (a, b) = operands(l, r)?;
+            (a.is_number() && b.is_number()).then_some(Ty::Bool)
+        }
+        Node::Print(expr) => type_of(expr)?.is_value().then_some(Ty::Void),
+        Node::If(cond, then_b, else_b) => {
+            if type_of(cond)? != Ty::Bool {
+                return None;
+            }
+            type_of(then_b)?;
+            if let Some(e) = else_b {
+                type_of(e)?;
+            }
+            Some(Ty::Void)
+        }
+        Node::Block(stmts) => {
+            for stmt in stmts {
+                type_of(stmt)?;
+            }
+            Some(Ty::Void)
+        }
+        _ => None,
+    }
+}
+
+const WORDS: &[&str] = &["", "a", "knoten", "node", "Vec3", "0", "-1", "true", " ", "ü"];
+
+/// Seeded generator of random well-typed programs over the shared subset.
+/// Each program is a block of `Print`/`If` statements ending in a bare expression.
+pub struct Generator {
+    rng: StdRng,
+    pub max_depth: usize,
+}
+
+impl Generator {
+    pub fn new(seed: u64) -> Self {
+        Self { rng: StdRng::seed_from_u64(seed), max_depth: 3 }
+    }
+
+    pub fn program(&mut self) -> Node {
+        let count = self.rng.random_range(0..5);
+        let mut stmts: Vec<Node> = (0..count).map(|_| self.statement(2)).collect();
+        let ty = self.value_type();
+        stmts.push(self.expr(ty, self.max_depth));
+        Node::Block(stmts)
+    }
+
+    fn value_type(&mut self) -> Ty {
+        [Ty::Int, Ty::Float, Ty::Bool, Ty::Str][self.rng.random_range(0..4)]
+    }
+
+    fn statement(&mut self, depth: usize) -> Node {
+        if depth > 0 && self.rng.random_bool(0.3) {
+            let cond = self.expr(Ty::Bool, 2);
+            let then_b = self.block(depth - 1);
+            let else_b = if self.rng.random_bool(0.5) { Some(Box::new(self.block(depth - 1))) } else { None };
+            Node::If(Box::new(cond), Box::new(then_b), else_b)
+        } else {
+            let ty = self.value_type();
+            Node::Print(Box::new(self.expr(ty, self.max_depth)))
+        }
+    }
+
+    fn block(&mut self, depth: usize) -> Node {
+        let count = self.rng.random_range(1..=3);
+        Node::Block((0..count).map(|_| self.statement(depth)).collect())
+    }
+
+    fn number_type(&mut self) -> Ty {
+        if self.rng.random_bool(0.5) { Ty::Int } else { Ty::Float }
+    }
+
+    fn expr(&mut self, ty: Ty, depth: usize) -> Node {
+        if depth == 0 || self.rng.random_bool(0.3) {
+            return self.literal(ty);
+        }
+        let d = depth - 1;
+        let pair = |g: &mut Self, a: Ty, b: Ty| (Box::new(g.expr(a, d)), Box::new(g.expr(b, d)));
+        match ty {
+            Ty::Int | Ty::Float => {
+                // A Float result needs at least one Float operand
+                let (a, b) = match ty {
+                    Ty::Int => (Ty::Int, Ty::Int),
+                    _ => match self.rng.random_range(0..3) {
+                        0 => (Ty::Float, Ty::Float),
+                        1 => (Ty::Int, Ty::Float),
+                        _ => (Ty::Float, Ty::Int),
+                    },
+                };
+                let (l, r) = pair(self, a, b);
+                match self.rng.random_range(0..4) {
+                    0 => Node::Add(l, r),
+                    1 => Node::Sub(l, r),
+                    2 => Node::Mul(l, r),
+                    _ => Node::Div(l, r),
+                }
+            }
+            Ty::Str => {
+                let (l, r) = pair(self, Ty::Str, Ty::Str);
+                Node::Add(l, r)
+            }
+            Ty::Bool => {
+                if self.rng.random_bool(0.4) {
+                    let t = self.value_type();
+                    let (l, r) = pair(self, t, t);
+                    return Node::Eq(l, r);
+                }
+                let (a, b) = (self.number_type(), self.number_type());
+                let (l, r) = pair(self, a, b);
+                match self.rng.random_range(0..3) {
+                    0 => Node::Eq(l, r),
+                    1 => Node::Lt(l, r),
+                    _ => Node::Gt(l, r),
+                }
+            }
+            Ty::Void => unreachable!("no Void expressions are generated"),
+        }
+    }
+
+    fn literal(&mut self, ty: Ty) -> Node {
+        match ty {
+            Ty::Int => {
+                // Mostly small values, sometimes huge ones to provoke overflow
+                if self.rng.random_bool(0.1) {
+                    Node::IntLiteral(self.rng.random_range(i64::MAX - 4..=i64::MAX) * if self.rng.random_bool(0.5) { 1 } else { -1 })
+                } else {
+                    Node::IntLiteral(self.rng.random_range(-5..=9))
+                }
+            }
+            // Quarters are exact in binary, so literal text round-trips everywhere
+            Ty::Float => Node::FloatLiteral(self.rng.random_range(-12..=12) as f64 / 4.0),
+            Ty::Bool => Node::BoolLiteral(self.rng.random_bool(0.5)),
+            Ty::Str => Node::StringLiteral(WORDS[self.rng.random_range(0..WORDS.len())].to_string()),
+            Ty::Void => unreachable!("no Void literals are generated"),
+        }
+    }
+}
+
+/// Every program one shrinking step away from `node`.
+fn shrinks(node: &Node) -> Vec<Node> {
+    let mut out = Vec::new();
+    let binary = |l: &Node, r: &Node, make: fn(Box<Node>, Box<Node>) -> Node, out: &mut Vec<Node>| {
+        out.push(l.clone());
+        out.push(r.clone());
+        for s in shrinks(l) {
+            out.push(make(Box::new(s), Box::new(r.clone())));
+        }
+        for s in shrinks(r) {
+            out.push(make(Box::new(l.clone()), Box::new(s)));
+        }
+    };
+    match node {
+        Node::Block(stmts) => {
+            for i in 0..stmts.len() {
+                let mut fewer = stmts.clone();
+                fewer.remove(i);
+                out.push(Node::Block(fewer));
+            }
+            for (i, stmt) in stmts.iter().enumerate() {
+                // Splice a nested block into its parent
+                if let Node::Block(inner) = stmt {
+                    let mut flat = stmts[..i].to_vec();
+                    flat.extend(inner.iter().cloned());
+                    flat.extend(stmts[i + 1..].iter().cloned());
+                    out.push(Node::Block(flat));
+                }
+                for s in shrinks(stmt) {
+                    let mut changed = stmts.clone();
+                    changed[i] = s;
+                    out.push(Node::Block(changed));
+                }
+            }
+        }
+        Node::If(cond, then_b, else_b) => {
+            out.push((**then_b).clone());
+            if let Some(e) = else_b {
+                out.push((**e).clone());
+                out.push(Node::If(cond.clone(), then_b.clone(), None));
+            }
+            for s in shrinks(cond) {
+                out.push(Node::If(Box::new(s), then_b.clone(), else_b.clone()));
+            }
+            for s in shrinks(then_b) {
+                out.push(Node::If(cond.clone(), Box::new(s), else_b.clone()));
+            }
+            if let Some(e) = else_b {
+                for s in shrinks(e) {
+                    out.push(Node::If(cond.clone(), then_b.clone(), Some(Box::new(s))));
+                }
+            }
+        }
+        Node::Print(expr) => out.extend(shrinks(expr).into_iter().map(|s| Node::Print(Box::new(s)))),
+        Node::Add(l, r) => binary(l, r, Node::Add, &mut out),
+        Node::Sub(l, r) => binary(l, r, Node::Sub, &mut out),
+        Node::Mul(l, r) => binary(l, r, Node::Mul, &mut out),
+        Node::Div(l, r) => binary(l, r, Node::Div, &mut out),
+        Node::Eq(l, r) => binary(l, r, Node::Eq, &mut out),
+        Node::Lt(l, r) => binary(l, r, Node::Lt, &mut out),
+        Node::Gt(l, r) => binary(l, r, Node::Gt, &mut out),
+        Node::IntLiteral(v) => {
+            if *v != 0 {
+                out.push(Node::IntLiteral(0));
+            }
+            if v.unsigned_abs() > 1 {
+                out.push(Node::IntLiteral(v / 2));
+            }
+        }
+        Node::FloatLiteral(v) => {
+            if *v != 0.0 {
+                out.push(Node::FloatLiteral(0.0));
+            }
+            if v.fract() != 0.0 {
+                out.push(Node::FloatLiteral(v.trunc()));
+            }
+        }
+        Node::StringLiteral(s) if !s.is_empty() => out.push(Node::StringLiteral(String::new())),
+        _ => {}
+    }
+    out
+}
+
+/// A program on which the backends disagree, with what each of them did.
+#[derive(Debug, Clone)]
+pub struct Mismatch {
+    /// Where the program came from: a seed and index, or an example path.
+    pub origin: String,
+    pub program: Node,
+    /// The minimized reproducer; the backends still disagree on it.
+    pub reduced: Node,
+    pub outcomes: Vec<(&'static str, Option<Outcome>)>,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "backends disagree on {}", self.origin)?;
+        writeln!(f, "reproducer:\n{}", crate::dsl_emitter::emit_dsl(&self.reduced, 0))?;
+        writeln!(f, "json: {}", serde_json::to_string(&self.reduced).unwrap_or_default())?;
+        for (name, outcome) in &self.outcomes {
+            match outcome {
+                Some(o) => writeln!(f, "  {}: {}", name, o)?,
+                None => writeln!(f, "  {}: (not supported)", name)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Summary of a fuzzing or examples run.
+#[derive(Debug, Default)]
+pub struct Report {
+    /// Programs that went through every backend.
+    pub checked: usize,
+    pub mismatches: Vec<Mismatch>,
+}
+
+/// Runs programs through a set of backends and collects disagreements.
+pub struct Harness {
+    backends: Vec<Box<dyn Backend>>,
+}
+
+impl Default for Harness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Harness {
+    /// The in-process backends: interpreter and VM.
+    pub fn new() -> Self {
+        Self { backends: vec![Box::new(Interpreter), Box::new(Vm)] }
+    }
+
+    pub fn with_backend(mut self, backend: impl Backend + 'static) -> Self {
+        self.backends.push(Box::new(backend));
+        self
+    }
+
+    /// Outcomes per program, one entry per backend in registration order.
+    fn outcomes(&mut self, programs: &[Node]) -> Vec<Vec<Option<Outcome>>> {
+        let mut per_program = vec![Vec::with_capacity(self.backends.len()); programs.len()];
+        for backend in &mut self.backends {
+            for (slot, outcome) in per_program.iter_mut().zip(backend.run_batch(programs)) {
+                slot.push(outcome);
+            }
+        }
+        per_program
+    }
+
+    fn disagree(outcomes: &[Option<Outcome>]) -> bool {
+        let mut ran = outcomes.iter().flatten();
+        let Some(first) = ran.next() else { return false };
+        ran.any(|o| !first.agrees(o))
+    }
+
+    /// Greedily applies shrinking steps while the backends keep disagreeing.
+    /// Candidates keep the original's shape: subset-only, and well-typed if it was.
+    pub fn minimize(&mut self, program: &Node) -> Node {
+        let typed = type_of(program).is_some();
+        let mut current = program.clone();
+        loop {
+            let candidates: Vec<Node> = shrinks(&current)
+                .into_iter()
+                .filter(|c| matches!(c, Node::Block(_)) && fits_shared_subset(c) && (!typed || type_of(c).is_some()))
+                .collect();
+            if candidates.is_empty() {
+                return current;
+            }
+            let outcomes = self.outcomes(&candidates);
+            match candidates.into_iter().zip(outcomes).find(|(_, o)| Self::disagree(o)) {
+                Some((smaller, _)) => current = smaller,
+                None => return current,
+            }
+        }
+    }
+
+    /// Runs `programs` (labelled by `origins`) and minimizes every mismatch.
+    fn check_all(&mut self, programs: Vec<Node>, origins: Vec<String>) -> Report {
+        let outcomes = self.outcomes(&programs);
+        let mut report = Report::default();
+        for ((program, origin), outcomes) in programs.into_iter().zip(origins).zip(outcomes) {
+            if outcomes.iter().all(Option::is_some) {
+                report.checked += 1;
+            }
+            if !Self::disagree(&outcomes) {
+                continue;
+            }
+            let reduced = self.minimize(&program);
+            let reduced_outcomes = self.outcomes(std::slice::from_ref(&reduced)).remove(0);
+            let outcomes = self.backends.iter().map(|b| b.name()).zip(reduced_outcomes).collect();
+            report.mismatches.push(Mismatch { origin, program, reduced, outcomes });
+        }
+        report
+    }
+
+    /// Checks a single program; `None` means all backends agree.
+    pub fn check(&mut self, program: &Node) -> Option<Mismatch> {
+        self.check_all(vec![program.clone()], vec!["program".into()]).mismatches.pop()
+    }
+
+    /// Checks `count` random programs generated from `seed`.
+    pub fn fuzz(&mut self, seed: u64, count: usize) -> Report {
+        let mut generator = Generator::new(seed);
+        let programs = (0..count).map(|_| generator.program()).collect();
+        let origins = (0..count).map(|i| format!("seed {} program #{}", seed, i)).collect();
+        self.check_all(programs, origins)
+    }
+
+    /// Checks every script under `dir` (recursively) that fits the shared subset.
+    /// `.nod`/`.json` files are AST JSON; `.knoten` files go through the parser.
+    pub fn examples(&mut self, dir: &Path) -> Report {
+        let mut programs = Vec::new();
+        let mut origins = Vec::new();
+        let mut pending = vec![dir.to_path_buf()];
+        while let Some(path) = pending.pop() {
+            if path.is_dir() {
+                if let Ok(entries) = std::fs::read_dir(&path) {
+                    pending.extend(entries.flatten().map(|e| e.path()));
+                }
+                continue;
+            }
+            let Some(program) = load_script(&path) else { continue };
+            if !fits_shared_subset(&program) {
+                continue;
+            }
+            // Transpiled code needs a block root to become `fn main`
+            let program = match program {
+                Node::Block(_) => program,
+                other => Node::Block(vec![other]),
+            };
+            programs.push(program);
+            origins.push(path.display().to_string());
+        }
+        self.check_all(programs, origins)
+    }
+}
+
+fn load_script(path: &Path) -> Option<Node> {
+    let source = std::fs::read_to_string(path).ok()?;
+    match path.extension()?.to_str()? {
+        "nod" | "json" => serde_json::from_str(&source).ok(),
+        "knoten" => crate::parser::try_parse(&source).ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interpreter_and_vm_agree() {
+        let mut harness = Harness::new();
+        let report = harness.fuzz(42, 300);
+        assert_eq!(report.checked, 300);
+        assert!(report.mismatches.is_empty(), "{}", report.mismatches[0]);
+
+        let examples = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples");
+        let report = harness.examples(&examples);
+        assert!(report.checked > 0);
+        assert!(report.mismatches.is_empty(), "{}", report.mismatches[0]);
+    }
+
+    /// Stands in for a buggy backend: prints integer division results off by one.
+    struct OffByOne;
+
+    impl Backend for OffByOne {
+        fn name(&self) -> &'static str {
+            "off-by-one"
+        }
+
+        fn run(&mut self, program: &Node) -> Option<Outcome> {
+            let mut outcome = Interpreter.run(program)?;
+            if contains_int_div(program) {
+                outcome.output.push("extra".into());
+            }
+            Some(outcome)
+        }
+    }
+
+    fn contains_int_div(node: &Node) -> bool {
+        match node {
+            Node::Div(l, r) => type_of(node) == Some(Ty::Int) || contains_int_div(l) || contains_int_div(r),
+            Node::Add(l, r) | Node::Sub(l, r) | Node::Mul(l, r) | Node::Eq(l, r) | Node::Lt(l, r) | Node::Gt(l, r) => {
+                contains_int_div(l) || contains_int_div(r)
+            }
+            Node::Print(e) => contains_int_div(e),
+            Node::If(c, t, e) => contains_int_div(c) || contains_int_div(t) || e.as_deref().is_some_and(contains_int_div),
+            Node::Block(stmts) => stmts.iter().any(contains_int_div),
+            _ => false,
+        }
+    }
+
+    fn size(node: &Node) -> usize {
+        match node {
+            Node::Add(l, r) | Node::Sub(l, r) | Node::Mul(l, r) | Node::Div(l, r)
+            | Node::Eq(l, r) | Node::Lt(l, r) | Node::Gt(l, r) => 1 + size(l) + size(r),
+            Node::Print(e) => 1 + size(e),
+            Node::If(c, t, e) => 1 + size(c) + size(t) + e.as_deref().map_or(0, size),
+            Node::Block(stmts) => 1 + stmts.iter().map(size).sum::<usize>(),
+            _ => 1,
+        }
+    }
+
+    #[test]
+    fn test_minimizes_mismatch() {
+        let mut harness = Harness::new().with_backend(OffByOne);
+        let report = harness.fuzz(7, 100);
+        let mismatch = report.mismatches.iter().max_by_key(|m| size(&m.program)).expect("expected mismatches");
+        assert!(contains_int_div(&mismatch.reduced));
+        // Block holding the division (or a Print of it) over two literals
+        assert!(size(&mismatch.reduced) <= 5, "{}", mismatch);
+        assert!(mismatch.to_string().contains("off-by-one"));
+    }
+
+    #[test]
+    #[ignore = "builds a cargo project; run with --ignored"]
+    fn test_transpiler_agrees() {
+        let work_dir = std::env::temp_dir().join("knoten_difftest");
+        let mut harness = Harness::new().with_backend(Transpiler::new(&work_dir));
+        let report = harness.fuzz(1, 100);
+        assert_eq!(report.checked, 100);
+        assert!(report.mismatches.is_empty(), "{}", report.mismatches[0]);
+
+        let examples = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples");
+        let report = harness.examples(&examples);
+        assert!(report.checked > 0);
+        assert!(report.mismatches.is_empty(), "{}", report.mismatches[0]);
+    }
+}
@@ -79,6 +79,7 @@ pub fn emit_dsl(node: &Node, indent: usize) -> String {
                 emit_dsl(body, indent)
             )
         }
+        Node::Lambda(args, body) => format!("fn({}) {}", args.join(", "), emit_dsl(body, indent)),
         Node::Return(val) => format!("return {}", emit_dsl(val, indent)),
 
         // Arrays & Objects
@@ -173,6 +174,8 @@ fn extract_args(node: &Node) -> Vec<&Node> {
         Node::Concat(a, b) => vec![&**a, &**b],
         Node::FileRead(a) => vec![&**a],
         Node::FileWrite(a, b) => vec![&**a, &**b],
+        Node::FileReadBytes(a) => vec![&**a],
+        Node::FileWriteBytes(a, b) => vec![&**a, &**b],
         Node::FSRead(a) => vec![&**a],
         Node::FSWrite(a, b) => vec![&**a, &**b],
         Node::Fetch {
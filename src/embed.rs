@@ -0,0 +1,495 @@
+use crate::ast::{Node, Type};
+use crate::executor::{AgentPermissions, ExecResult, ExecutionEngine, RelType};
+use crate::natives::NativeModule;
+use crate::natives::convert::{ConvertError, FromRel, IntoRel, rel_type_name};
+use crate::natives::rel_serde;
+use crate::vm::storage::KvStore;
+
+type HostFn = dyn Fn(&str, &[RelType]) -> Result<RelType, String> + Send;
+
+/// A Rust closure callable from scripts by name. `params` and `ret` are
+/// derived from the closure's argument and return types.
+pub struct HostFunction {
+    pub params: Vec<Type>,
+    pub ret: Type,
+    func: Box<HostFn>,
+}
+
+/// Whether a runtime value satisfies a declared signature type.
+pub fn type_matches(t: &Type, v: &RelType) -> bool {
+    match (t, v) {
+        (Type::Any, _) => true,
+        (Type::Int, RelType::Int(_)) => true,
+        (Type::Float, RelType::Float(_)) => true,
+        (Type::Bool, RelType::Bool(_)) => true,
+        (Type::String, RelType::Str(_)) => true,
+        (Type::Array(elems), RelType::Array(items)) => match elems.as_slice() {
+            [] => true,
+            [elem] => items.iter().all(|i| type_matches(elem, i)),
+            _ => elems.len() == items.len() && elems.iter().zip(items).all(|(t, v)| type_matches(t, v)),
+        },
+        (Type::Map(elem), RelType::Object(map)) => map.values().all(|v| type_matches(elem, v)),
+        (Type::Object, RelType::Object(_)) => true,
+        (Type::Handle, RelType::Handle(_)) => true,
+        (Type::Void, RelType::Void) => true,
+        _ => false,
+    }
+}
+
+/// The signature type a host function parameter or result is declared as.
+pub trait HostType {
+    fn host_type() -> Type;
+}
+
+macro_rules! host_types {
+    ($($t:ty => $type:expr),* $(,)?) => {$(
+        impl HostType for $t {
+            fn host_type() -> Type {
+                $type
+            }
+        }
+    )*};
+}
+
+host_types!(
+    i8 => Type::Int, i16 => Type::Int, i32 => Type::Int, i64 => Type::Int,
+    u8 => Type::Int, u16 => Type::Int, u32 => Type::Int, u64 => Type::Int, usize => Type::Int,
+    f32 => Type::Float, f64 => Type::Float,
+    bool => Type::Bool,
+    String => Type::String,
+    RelType => Type::Any,
+    () => Type::Void,
+    glam::Vec2 => Type::Any, glam::Vec3 => Type::Any, glam::Vec4 => Type::Any, glam::Quat => Type::Any, glam::Mat4 => Type::Any,
+);
+
+impl<T: HostType> HostType for Vec<T> {
+    fn host_type() -> Type {
+        Type::Array(vec![T::host_type()])
+    }
+}
+
+impl<T: HostType> HostType for std::collections::HashMap<String, T> {
+    fn host_type() -> Type {
+        Type::Map(Box::new(T::host_type()))
+    }
+}
+
+/// `Void` or a `T`, which signatures cannot express.
+impl<T: HostType> HostType for Option<T> {
+    fn host_type() -> Type {
+        Type::Any
+    }
+}
+
+macro_rules! tuple_host_types {
+    ($($name:ident),+) => {
+        impl<$($name: HostType),+> HostType for ($($name,)+) {
+            fn host_type() -> Type {
+                Type::Array(vec![$($name::host_type()),+])
+            }
+        }
+    };
+}
+
+tuple_host_types!(A);
+tuple_host_types!(A, B);
+tuple_host_types!(A, B, C);
+tuple_host_types!(A, B, C, D);
+
+/// What a host closure may return: a value, or `Err(msg)` to fault.
+pub trait HostReturn {
+    fn host_type() -> Type;
+    fn into_result(self) -> Result<RelType, String>;
+}
+
+impl<T: IntoRel + HostType> HostReturn for T {
+    fn host_type() -> Type {
+        <T as HostType>::host_type()
+    }
+
+    fn into_result(self) -> Result<RelType, String> {
+        Ok(self.into_rel())
+    }
+}
+
+impl<T: IntoRel + HostType> HostReturn for Result<T, String> {
+    fn host_type() -> Type {
+        T::host_type()
+    }
+
+    fn into_result(self) -> Result<RelType, String> {
+        self.map(IntoRel::into_rel)
+    }
+}
+
+/// Converts argument `idx` of host function `name`, naming it on failure.
+fn host_arg<T: FromRel + HostType>(name: &str, idx: usize, v: &RelType) -> Result<T, String> {
+    T::from_rel(v).map_err(|e| {
+        let expected = T::host_type();
+        if e.path.is_empty() && !type_matches(&expected, v) {
+            format!("'{}' argument {} expects {:?}, got {}", name, idx + 1, expected, rel_type_name(v))
+        } else {
+            format!("'{}' argument {} {}", name, idx + 1, e)
+        }
+    })
+}
+
+/// Closures that can be registered as host functions: `Fn(A, B, ..) -> R`
+/// with up to six arguments, each `FromRel + HostType`, and `R: HostReturn`.
+pub trait IntoHostFunction<Args> {
+    fn into_host_function(self) -> HostFunction;
+}
+
+macro_rules! host_closures {
+    ($($arg:ident),*) => {
+        impl<F, R, $($arg),*> IntoHostFunction<($($arg,)*)> for F
+        where
+            F: Fn($($arg),*) -> R + Send + 'static,
+            R: HostReturn,
+            $($arg: FromRel + HostType,)*
+        {
+            fn into_host_function(self) -> HostFunction {
+                // Without arguments, `name` and `args` go unused.
+                #[allow(unused_mut, unused_variables)]
+                let func = move |name: &str, args: &[RelType]| {
+                    let mut args = args.iter().enumerate();
+                    // `HostFunction::call` has checked the arity.
+                    self($({
+                        let (idx, v) = args.next().expect("arity checked");
+                        host_arg::<$arg>(name, idx, v)?
+                    }),*)
+                    .into_result()
+                };
+                HostFunction { params: vec![$($arg::host_type()),*], ret: R::host_type(), func: Box::new(func) }
+            }
+        }
+    };
+}
+
+host_closures!();
+host_closures!(A);
+host_closures!(A, B);
+host_closures!(A, B, C);
+host_closures!(A, B, C, D);
+host_closures!(A, B, C, D, E);
+host_closures!(A, B, C, D, E, G);
+
+impl HostFunction {
+    pub fn new<Args>(func: impl IntoHostFunction<Args>) -> Self {
+        func.into_host_function()
+    }
+
+    /// Checks arity, then runs the closure, which converts each argument.
+    pub fn call(&self, name: &str, args: &[RelType]) -> ExecResult {
+        let node = format!("Host::{}", name);
+        if args.len() != self.params.len() {
+            return ExecResult::Fault { msg: format!("'{}' expects {} args, got {}", name, self.params.len(), args.len()), node };
+        }
+        match (self.func)(name, args) {
+            Ok(v) => ExecResult::Value(v),
+            Err(msg) => ExecResult::Fault { msg, node },
+        }
+    }
+}
+
+/// Configures which built-in modules, permissions and host functions an `Engine` starts with.
+pub struct EngineBuilder {
+    math: bool,
+    io: bool,
+    string: bool,
+    array: bool,
+    vector: bool,
+    json: bool,
+    bytes: bool,
+    csv: bool,
+    template: bool,
+    store: bool,
+    registry: bool,
+    permissions: AgentPermissions,
+    storage: KvStore,
+    modules: Vec<Box<dyn NativeModule>>,
+    host: Vec<(String, HostFunction)>,
+}
+
+impl Default for EngineBuilder {
+    fn default() -> Self {
+        Self {
+            math: true,
+            io: true,
+            string: true,
+            array: true,
+            vector: true,
+            json: true,
+            bytes: true,
+            csv: true,
+            template: true,
+            store: true,
+            registry: true,
+            permissions: AgentPermissions::default(),
+            storage: KvStore::default(),
+            modules: Vec::new(),
+            host: Vec::new(),
+        }
+    }
+}
+
+impl EngineBuilder {
+    pub fn math(mut self, enabled: bool) -> Self {
+        self.math = enabled;
+        self
+    }
+
+    pub fn io(mut self, enabled: bool) -> Self {
+        self.io = enabled;
+        self
+    }
+
+    pub fn string(mut self, enabled: bool) -> Self {
+        self.string = enabled;
+        self
+    }
+
+    pub fn array(mut self, enabled: bool) -> Self {
+        self.array = enabled;
+        self
+    }
+
+    pub fn vector(mut self, enabled: bool) -> Self {
+        self.vector = enabled;
+        self
+    }
+
+    pub fn json(mut self, enabled: bool) -> Self {
+        self.json = enabled;
+        self
+    }
+
+    pub fn bytes(mut self, enabled: bool) -> Self {
+        self.bytes = enabled;
+        self
+    }
+
+    pub fn csv(mut self, enabled: bool) -> Self {
+        self.csv = enabled;
+        self
+    }
+
+    pub fn template(mut self, enabled: bool) -> Self {
+        self.template = enabled;
+        self
+    }
+
+    pub fn store(mut self, enabled: bool) -> Self {
+        self.store = enabled;
+        self
+    }
+
+    pub fn registry(mut self, enabled: bool) -> Self {
+        self.registry = enabled;
+        self
+    }
+
+    pub fn permissions(mut self, permissions: AgentPermissions) -> Self {
+        self.permissions = permissions;
+        self
+    }
+
+    /// Where `Store`/`Load` keep their data. Without this, every engine shares
+    /// the `default` namespace, so unrelated embedded scripts see each other's keys.
+    pub fn storage(mut self, storage: KvStore) -> Self {
+        self.storage = storage;
+        self
+    }
+
+    /// Installs an additional native module after the built-in ones.
+    pub fn module(mut self, module: impl NativeModule + 'static) -> Self {
+        self.modules.push(Box::new(module));
+        self
+    }
+
+    /// Makes a Rust closure callable from scripts, e.g.
+    /// `register("scale", |a: i64, b: i64| a * b)`. Its signature comes from
+    /// the closure's types; return `Err(msg)` to fault.
+    pub fn register<Args>(mut self, name: &str, func: impl IntoHostFunction<Args>) -> Self {
+        self.host.push((name.to_string(), HostFunction::new(func)));
+        self
+    }
+
+    pub fn build(self) -> Engine {
+        let mut inner = ExecutionEngine::new();
+        inner.native_modules.clear();
+        if self.math {
+            inner.native_modules.push(std::sync::Arc::new(crate::natives::math::MathModule));
+        }
+        if self.io {
+            inner.native_modules.push(std::sync::Arc::new(crate::natives::io::IoModule));
+        }
+        if self.string {
+            inner.native_modules.push(std::sync::Arc::new(crate::natives::string::StringModule));
+        }
+        if self.array {
+            inner.native_modules.push(std::sync::Arc::new(crate::natives::array::ArrayModule));
+        }
+        if self.vector {
+            inner.native_modules.push(std::sync::Arc::new(crate::natives::vector::VectorModule));
+        }
+        if self.json {
+            inner.native_modules.push(std::sync::Arc::new(crate::natives::json::JsonModule));
+        }
+        if self.bytes {
+            inner.native_modules.push(std::sync::Arc::new(crate::natives::bytes::BytesModule));
+        }
+        if self.csv {
+            inner.native_modules.push(std::sync::Arc::new(crate::natives::csv::CsvModule));
+        }
+        if self.template {
+            inner.native_modules.push(std::sync::Arc::new(crate::natives::template::TemplateModule));
+        }
+        if self.store {
+            inner.native_modules.push(std::sync::Arc::new(crate::natives::store::StoreModule));
+        }
+        if self.registry {
+            inner.native_modules.push(std::sync::Arc::new(crate::natives::registry::RegistryModule));
+        }
+        inner.native_modules.extend(self.modules.into_iter().map(std::sync::Arc::from));
+        inner.permissions = self.permissions;
+        inner.store = self.storage;
+        inner.host_functions.extend(self.host);
+        Engine { inner }
+    }
+}
+
+/// High-level handle for embedding KnotenCore in a Rust application.
+pub struct Engine {
+    inner: ExecutionEngine,
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+impl Engine {
+    pub fn builder() -> EngineBuilder {
+        EngineBuilder::default()
+    }
+
+    /// Registers (or replaces) a host function on a running engine.
+    pub fn register<Args>(&mut self, name: &str, func: impl IntoHostFunction<Args>) {
+        self.inner.host_functions.insert(name.to_string(), HostFunction::new(func));
+    }
+
+    /// Parses and runs DSL source. Parse errors are reported as a `Fault`.
+    pub fn eval(&mut self, source: &str) -> ExecResult {
+        match crate::parser::try_parse(source) {
+            Ok(ast) => self.inner.execute(&ast),
+            Err(diag) => ExecResult::Fault { msg: diag, node: "Parser".into() },
+        }
+    }
+
+    pub fn run(&mut self, ast: &Node) -> ExecResult {
+        self.inner.execute(ast)
+    }
+
+    /// Calls a script-defined function (or host/native function) with the given arguments.
+    pub fn call(&mut self, name: &str, args: &[RelType]) -> ExecResult {
+        self.inner.call_function(name, args.to_vec())
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<&RelType> {
+        self.inner.memory.get(name)
+    }
+
+    pub fn set_global(&mut self, name: &str, value: RelType) {
+        self.inner.memory.insert(name.to_string(), value);
+    }
+
+    /// Deserializes a global into a host type; errors are prefixed with the global's name.
+    pub fn get_global_as<T: serde::de::DeserializeOwned>(&self, name: &str) -> Result<T, ConvertError> {
+        let value = self.get_global(name).ok_or_else(|| ConvertError::new(format!("global '{}' is not defined", name)))?;
+        rel_serde::from_rel(value).map_err(|e| e.within(name))
+    }
+
+    pub fn set_global_from<T: serde::Serialize + ?Sized>(&mut self, name: &str, value: &T) -> Result<(), ConvertError> {
+        let value = rel_serde::to_rel(value).map_err(|e| e.within(name))?;
+        self.set_global(name, value);
+        Ok(())
+    }
+
+    pub fn inner(&self) -> &ExecutionEngine {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut ExecutionEngine {
+        &mut self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host_functions_and_script_calls() {
+        let mut engine = Engine::builder()
+            .io(false)
+            .register("host_scale", |a: i64, b: i64| a * b)
+            .build();
+        engine.set_global("base", RelType::Int(4));
+        let res = engine.eval("fn area(w) { return host_scale(w, base); }");
+        assert!(!matches!(res, ExecResult::Fault { .. }));
+
+        assert!(matches!(engine.call("area", &[RelType::Int(5)]), ExecResult::Value(RelType::Int(20))));
+        assert!(matches!(engine.eval("host_scale(2, 3)"), ExecResult::Value(RelType::Int(6))));
+        assert_eq!(engine.get_global("base"), Some(&RelType::Int(4)));
+
+        match engine.eval("host_scale(2, \"x\")") {
+            ExecResult::Fault { msg, .. } => assert_eq!(msg, "'host_scale' argument 2 expects Int, got String"),
+            _ => panic!("expected a type fault"),
+        }
+        match engine.call("missing", &[]) {
+            ExecResult::Fault { msg, .. } => assert_eq!(msg, "Function 'missing' not found"),
+            _ => panic!("expected a fault"),
+        }
+
+        engine.register("mean", |xs: Vec<f64>| match xs.len() {
+            0 => Err("mean of an empty list".to_string()),
+            n => Ok(xs.iter().sum::<f64>() / n as f64),
+        });
+        let host = &engine.inner().host_functions["mean"];
+        assert_eq!((&host.params, &host.ret), (&vec![Type::Array(vec![Type::Float])], &Type::Float));
+        assert!(matches!(engine.eval("mean([1, 2.5, 3])"), ExecResult::Value(RelType::Float(f)) if (f - 6.5 / 3.0).abs() < 1e-9));
+        for (src, expected) in [
+            ("mean([])", "mean of an empty list"),
+            ("mean([1, \"x\"])", "'mean' argument 1 [1]: expected Number, found String"),
+        ] {
+            match engine.eval(src) {
+                ExecResult::Fault { msg, node } => assert_eq!((msg.as_str(), node.as_str()), (expected, "Host::mean")),
+                other => panic!("expected a fault from {}, got {}", src, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_storage_namespaces_are_separate() {
+        let root = std::path::Path::new("target/kv_test/embed");
+        let _ = std::fs::remove_dir_all(root);
+        let permissions = AgentPermissions { allow_fs_read: true, allow_fs_write: true, ..AgentPermissions::default() };
+        let engine = |ns: &str| {
+            Engine::builder().permissions(permissions.clone()).storage(KvStore::new(root, ns).unwrap()).build()
+        };
+        let (mut a, mut b) = (engine("a"), engine("b"));
+        assert!(matches!(a.eval("Store.Set(\"k\", 1)"), ExecResult::Value(_)));
+        assert!(matches!(a.eval("Store.Get(\"k\")"), ExecResult::Value(RelType::Int(1))));
+        assert!(matches!(b.eval("Store.Get(\"k\")"), ExecResult::Value(RelType::Void)));
+    }
+
+    #[test]
+    fn test_builder_selects_modules() {
+        let mut bare = Engine::builder().math(false).build();
+        assert!(matches!(bare.call("Math.Floor", &[RelType::Float(2.5)]), ExecResult::Fault { .. }));
+        let mut full = Engine::default();
+        assert!(matches!(full.call("Math.Floor", &[RelType::Float(2.5)]), ExecResult::Value(RelType::Float(2.0))));
+    }
+}
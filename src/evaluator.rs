@@ -13,7 +13,22 @@ impl ExecutionEngine {
         res
     }
 
+    #[inline]
     pub fn evaluate_inner(&mut self, node: &Node) -> ExecResult {
+        if self.profiler.is_some() {
+            return self.evaluate_profiled(node);
+        }
+        self.evaluate_node(node)
+    }
+
+    fn evaluate_profiled(&mut self, node: &Node) -> ExecResult {
+        if let Some(p) = self.profiler.as_mut() { p.enter_node(node.kind()); }
+        let res = self.evaluate_node(node);
+        if let Some(p) = self.profiler.as_mut() { p.exit_node(); }
+        res
+    }
+
+    fn evaluate_node(&mut self, node: &Node) -> ExecResult {
         match node {
             // Literals
             Node::IntLiteral(v) => ExecResult::Value(RelType::Int(*v)),
@@ -64,26 +79,18 @@ impl ExecutionEngine {
             Node::Eq(l, r) => self.do_compare(l, "==", r),
             Node::Lt(l, r) => self.do_compare(l, "<", r),
             Node::Gt(l, r) => self.do_compare(l, ">", r),
-            Node::Time | Node::GlobalTime => ExecResult::Value(RelType::Float(self.startup_time.elapsed().as_secs_f64())),
+            Node::Time | Node::GlobalTime => ExecResult::Value(RelType::Float(self.elapsed().as_secs_f64())),
             Node::Mat4Mul(l, r) => {
-                let lv = match self.evaluate_inner(l) { ExecResult::Value(RelType::Array(v)) => v, _ => return ExecResult::Fault { msg: "Mat4Mul expects array".into(), node: "Node::Mat4Mul".into() } };
-                let rv = match self.evaluate_inner(r) { ExecResult::Value(RelType::Array(v)) => v, _ => return ExecResult::Fault { msg: "Mat4Mul expects array".into(), node: "Node::Mat4Mul".into() } };
-                if lv.len() != 16 || rv.len() != 16 { return ExecResult::Fault { msg: "Mat4Mul expects 16-element arrays".into(), node: "Node::Mat4Mul".into() }; }
-                
-                let a: Vec<f32> = lv.iter().map(|v| match v { RelType::Float(f) => *f as f32, RelType::Int(i) => *i as f32, _ => 0.0 }).collect();
-                let b: Vec<f32> = rv.iter().map(|v| match v { RelType::Float(f) => *f as f32, RelType::Int(i) => *i as f32, _ => 0.0 }).collect();
-                let mut res = vec![0.0f32; 16];
-                
-                for i in 0..4 {
-                    for j in 0..4 {
-                        let mut sum = 0.0f32;
-                        for k in 0..4 {
-                            sum += a[i * 4 + k] * b[k * 4 + j];
-                        }
-                        res[i * 4 + j] = sum;
-                    }
-                }
-                ExecResult::Value(RelType::Array(res.into_iter().map(|f| RelType::Float(f as f64)).collect()))
+                let lv = match self.evaluate_inner(l) { ExecResult::Value(v) => v, err => return err };
+                let rv = match self.evaluate_inner(r) { ExecResult::Value(v) => v, err => return err };
+                // 16-element arrays are column-major, the layout of `Mat4(array)` and `Mat4.ToArray`.
+                if let (RelType::Mat4(a), RelType::Mat4(b)) = (&lv, &rv) { return ExecResult::Value(RelType::Mat4(*a * *b)); }
+                use crate::natives::convert::FromRel;
+                let (a, b) = match (Vec::<f32>::from_rel(&lv), Vec::<f32>::from_rel(&rv)) {
+                    (Ok(a), Ok(b)) if a.len() == 16 && b.len() == 16 => (glam::Mat4::from_cols_slice(&a), glam::Mat4::from_cols_slice(&b)),
+                    _ => return ExecResult::Fault { msg: "Mat4Mul expects two Mat4 values or two 16-element number arrays".into(), node: "Node::Mat4Mul".into() },
+                };
+                ExecResult::Value(RelType::Array((a * b).to_cols_array().into_iter().map(|f| RelType::Float(f as f64)).collect()))
             }
 
             // Data Structures: Arrays
@@ -98,10 +105,20 @@ impl ExecutionEngine {
                 ExecResult::Value(RelType::Array(vals))
             }
             Node::ArrayGet(arr, idx) => {
-                let a = match self.evaluate_inner(arr) { ExecResult::Value(RelType::Array(v)) => v, _ => return ExecResult::Fault { msg: "Target is not an array".into(), node: "Node::ArrayGet".into() } };
+                let target = match self.evaluate_inner(arr) {
+                    ExecResult::Value(v @ (RelType::Array(_) | RelType::Bytes(_))) => v,
+                    _ => return ExecResult::Fault { msg: "Target is not an array".into(), node: "Node::ArrayGet".into() },
+                };
                 let i = match self.evaluate_inner(idx) { ExecResult::Value(RelType::Int(v)) => v as usize, _ => return ExecResult::Fault { msg: "Index is not an integer".into(), node: "Node::ArrayGet".into() } };
-                if i < a.len() { ExecResult::Value(a[i].clone()) }
-                else { ExecResult::Fault { msg: format!("Index {} out of bounds", i), node: "Node::ArrayGet".into() } }
+                let item = match &target {
+                    RelType::Bytes(b) => b.get(i).map(|x| RelType::Int(*x as i64)),
+                    RelType::Array(a) => a.get(i).cloned(),
+                    _ => None,
+                };
+                match item {
+                    Some(v) => ExecResult::Value(v),
+                    None => ExecResult::Fault { msg: format!("Index {} out of bounds", i), node: "Node::ArrayGet".into() },
+                }
             }
             Node::ArraySet(arr_expr, idx_expr, val_expr) => {
                 let val = match self.evaluate_inner(val_expr) { ExecResult::Value(v) => v, err => return err };
@@ -126,8 +143,11 @@ impl ExecutionEngine {
                 } else { ExecResult::Fault { msg: "ArrayPush only supported on identifiers currently".into(), node: "Node::ArrayPush".into() } }
             }
             Node::ArrayLen(arr) => {
-                let a = match self.evaluate_inner(arr) { ExecResult::Value(RelType::Array(v)) => v, _ => return ExecResult::Fault { msg: "Target is not an array".into(), node: "Node::ArrayLen".into() } };
-                ExecResult::Value(RelType::Int(a.len() as i64))
+                match self.evaluate_inner(arr) {
+                    ExecResult::Value(RelType::Array(v)) => ExecResult::Value(RelType::Int(v.len() as i64)),
+                    ExecResult::Value(RelType::Bytes(b)) => ExecResult::Value(RelType::Int(b.len() as i64)),
+                    _ => ExecResult::Fault { msg: "Target is not an array".into(), node: "Node::ArrayLen".into() },
+                }
             }
 
             // Data Structures: Maps & Objects
@@ -167,7 +187,14 @@ impl ExecutionEngine {
                 ExecResult::Value(RelType::Object(res))
             }
             Node::PropertyGet(obj_expr, prop) => {
-                let o = match self.evaluate_inner(obj_expr) { ExecResult::Value(RelType::Object(v)) => v, _ => return ExecResult::Fault { msg: "Target is not an object".into(), node: "Node::PropertyGet".into() } };
+                let o = match self.evaluate_inner(obj_expr) {
+                    ExecResult::Value(RelType::Object(v)) => v,
+                    ExecResult::Value(v) => match crate::natives::vector::swizzle(&v, prop) {
+                        Some(res) => return res.map_or_else(|msg| ExecResult::Fault { msg, node: "Node::PropertyGet".into() }, ExecResult::Value),
+                        None => return ExecResult::Fault { msg: "Target is not an object".into(), node: "Node::PropertyGet".into() },
+                    },
+                    _ => return ExecResult::Fault { msg: "Target is not an object".into(), node: "Node::PropertyGet".into() },
+                };
                 ExecResult::Value(o.get(prop).cloned().unwrap_or(RelType::Void))
             }
             Node::PropertySet(obj_expr, prop, val_expr) => {
@@ -197,6 +224,10 @@ impl ExecutionEngine {
                         if let Some(ch) = s.chars().nth(idx as usize) { ExecResult::Value(RelType::Str(ch.to_string())) }
                         else { ExecResult::Fault { msg: "String index out of bounds".into(), node: "Node::Index".into() } }
                     }
+                    (RelType::Bytes(b), RelType::Int(idx)) => {
+                        if let Some(x) = b.get(idx as usize) { ExecResult::Value(RelType::Int(*x as i64)) }
+                        else { ExecResult::Fault { msg: "Bytes index out of bounds".into(), node: "Node::Index".into() } }
+                    }
                     _ => ExecResult::Fault { msg: "Invalid index operation".into(), node: "Node::Index".into() },
                 }
             }
@@ -206,7 +237,8 @@ impl ExecutionEngine {
                 match (lv, rv) {
                     (RelType::Str(a), RelType::Str(b)) => ExecResult::Value(RelType::Str(a + &b)),
                     (RelType::Array(mut a), RelType::Array(b)) => { a.extend(b); ExecResult::Value(RelType::Array(a)) }
-                    _ => ExecResult::Fault { msg: "Concat expects strings or arrays".into(), node: "Node::Concat".into() },
+                    (RelType::Bytes(mut a), RelType::Bytes(b)) => { a.extend(b); ExecResult::Value(RelType::Bytes(a)) }
+                    _ => ExecResult::Fault { msg: "Concat expects strings, arrays or bytes".into(), node: "Node::Concat".into() },
                 }
             }
 
@@ -238,16 +270,19 @@ impl ExecutionEngine {
                         if let Some(eb) = else_b { self.evaluate_inner(eb) }
                         else { ExecResult::Value(RelType::Void) }
                     }
+                    fault @ ExecResult::Fault { .. } => fault,
                     _ => ExecResult::Fault { msg: "If condition must be boolean".into(), node: "Node::If".into() },
                 }
             }
             Node::While(cond, body) => {
                 while let ExecResult::Value(RelType::Bool(true)) = self.evaluate_inner(cond) {
+                    if let Err(cancelled) = self.check_interrupt() { return cancelled; }
                     match self.evaluate_inner(body) {
                         ExecResult::Value(v) => self.release_handles(&v),
                         ExecResult::ReturnBlockInfo(v) => return ExecResult::ReturnBlockInfo(v),
                         ExecResult::Fault { msg, node } => return ExecResult::Fault { msg, node },
                     }
+                    if self.hot_reload.is_some() { self.apply_hot_reload(); }
                 }
                 ExecResult::Value(RelType::Void)
             }
@@ -276,29 +311,29 @@ impl ExecutionEngine {
                 self.set_var(name.clone(), RelType::FnDef(name.clone(), params.clone(), body.clone()));
                 ExecResult::Value(RelType::Void)
             }
+            Node::Lambda(params, body) => {
+                ExecResult::Value(RelType::FnDef("<lambda>".into(), params.clone(), body.clone()))
+            }
             Node::Call(name, args) => {
-                let func = if let Some(f) = self.get_var(name) { f } else { return ExecResult::Fault { msg: format!("Function '{}' not found", name), node: "Node::Call".into() } };
+                let func = self.get_var(name);
+                if let Some(RelType::FnDef(_, params, _)) = &func
+                    && params.len() != args.len()
+                {
+                    return ExecResult::Fault { msg: format!("'{}' expects {} args, got {}", name, params.len(), args.len()), node: "Node::Call".into() };
+                }
+                if func.as_ref().is_some_and(|f| !matches!(f, RelType::FnDef(..))) {
+                    return ExecResult::Fault { msg: format!("'{}' is not a function", name), node: "Node::Call".into() };
+                }
+                let mut vals = Vec::with_capacity(args.len());
+                for a in args {
+                    match self.evaluate_inner(a) { ExecResult::Value(v) => vals.push(v), err => return err }
+                }
                 match func {
-                    RelType::FnDef(_, params, body) => {
-                        if params.len() != args.len() { return ExecResult::Fault { msg: format!("'{}' expects {} args, got {}", name, params.len(), args.len()), node: "Node::Call".into() } }
-                        let mut locals = HashMap::with_capacity(params.len());
-                        for (p, a) in params.iter().zip(args.iter()) {
-                            match self.evaluate_inner(a) {
-                                ExecResult::Value(v) => { locals.insert(p.clone(), v); }
-                                err => return err,
-                            }
-                        }
-                        self.call_stack.push(StackFrame { locals });
-                        let res = self.evaluate_inner(&body);
-                        if let Some(frame) = self.call_stack.pop() {
-                            for (_, val) in frame.locals { self.release_handles(&val); }
-                        }
-                        match res {
-                            ExecResult::ReturnBlockInfo(v) => ExecResult::Value(v),
-                            other => other,
-                        }
-                    }
-                    _ => ExecResult::Fault { msg: format!("'{}' is not a function", name), node: "Node::Call".into() },
+                    Some(RelType::FnDef(_, params, body)) => self.invoke_fn_def(name, &params, &body, vals),
+                    _ => match self.call_native(name, &vals) {
+                        Some(res) => res,
+                        None => ExecResult::Fault { msg: format!("Function '{}' not found", name), node: "Node::Call".into() },
+                    },
                 }
             }
 
@@ -308,15 +343,20 @@ impl ExecutionEngine {
                 let ax = match self.evaluate_inner(a_max) { ExecResult::Value(v) => v, err => return err };
                 let bm = match self.evaluate_inner(b_min) { ExecResult::Value(v) => v, err => return err };
                 let bx = match self.evaluate_inner(b_max) { ExecResult::Value(v) => v, err => return err };
-                let v_am = if let Some(v) = self.to_vec3(am) { v } else { return ExecResult::Fault { msg: "a_min must be array".into(), node: "Node::CheckCollision".into() } };
-                let v_ax = if let Some(v) = self.to_vec3(ax) { v } else { return ExecResult::Fault { msg: "a_max must be array".into(), node: "Node::CheckCollision".into() } };
-                let v_bm = if let Some(v) = self.to_vec3(bm) { v } else { return ExecResult::Fault { msg: "b_min must be array".into(), node: "Node::CheckCollision".into() } };
-                let v_bx = if let Some(v) = self.to_vec3(bx) { v } else { return ExecResult::Fault { msg: "b_max must be array".into(), node: "Node::CheckCollision".into() } };
+                let v_am = if let Some(v) = self.to_vec3(am) { v } else { return ExecResult::Fault { msg: "a_min must be a Vec3 or an array of 3 numbers".into(), node: "Node::CheckCollision".into() } };
+                let v_ax = if let Some(v) = self.to_vec3(ax) { v } else { return ExecResult::Fault { msg: "a_max must be a Vec3 or an array of 3 numbers".into(), node: "Node::CheckCollision".into() } };
+                let v_bm = if let Some(v) = self.to_vec3(bm) { v } else { return ExecResult::Fault { msg: "b_min must be a Vec3 or an array of 3 numbers".into(), node: "Node::CheckCollision".into() } };
+                let v_bx = if let Some(v) = self.to_vec3(bx) { v } else { return ExecResult::Fault { msg: "b_max must be a Vec3 or an array of 3 numbers".into(), node: "Node::CheckCollision".into() } };
                 let aabb_a = crate::math::AABB::new(v_am, v_ax);
                 let aabb_b = crate::math::AABB::new(v_bm, v_bx);
                 ExecResult::Value(RelType::Int(if aabb_a.intersects(&aabb_b) { 1 } else { 0 }))
             }
 
+            Node::Await(expr) => match self.evaluate_inner(expr) {
+                ExecResult::Value(v) => self.await_value(v),
+                err => err,
+            },
+
             // Delegation
             Node::ToString(expr) => {
                 match self.evaluate_inner(expr) {
@@ -327,6 +367,7 @@ impl ExecutionEngine {
 
             // Exhaustive Delegation of Effectful / System nodes to executor
             Node::FileRead(_) | Node::FileWrite(_, _) | Node::FSRead(_) | Node::FSWrite(_, _) |
+            Node::FileReadBytes(_) | Node::FileWriteBytes(_, _) |
             Node::Print(_) | Node::Store { .. } | Node::Load { .. } |
             Node::DrawRect { .. } | Node::UIFixed { .. } | Node::UIFillParent |
             Node::RenderCanvas { .. } | Node::Transform2D { .. } | Node::Sprite2D { .. } |
@@ -348,65 +389,125 @@ impl ExecutionEngine {
         }
     }
 
+    /// Runs a script function with already-evaluated arguments in a fresh stack frame.
+    pub fn invoke_fn_def(&mut self, name: &str, params: &[String], body: &Node, args: Vec<RelType>) -> ExecResult {
+        if let Err(cancelled) = self.check_interrupt() { return cancelled; }
+        let locals: HashMap<String, RelType> = params.iter().cloned().zip(args).collect();
+        self.call_stack.push(StackFrame { locals });
+        if let Some(p) = self.profiler.as_mut() { p.enter_fn(name); }
+        let res = self.evaluate_inner(body);
+        if let Some(p) = self.profiler.as_mut() { p.exit_fn(); }
+        if let Some(frame) = self.call_stack.pop() {
+            for (_, val) in frame.locals { self.release_handles(&val); }
+        }
+        match res {
+            ExecResult::ReturnBlockInfo(v) => ExecResult::Value(v),
+            other => other,
+        }
+    }
+
+    /// Calls a script function by name from host code.
+    pub fn call_function(&mut self, name: &str, args: Vec<RelType>) -> ExecResult {
+        match self.get_var(name) {
+            Some(RelType::FnDef(_, params, body)) => {
+                if params.len() != args.len() {
+                    return ExecResult::Fault { msg: format!("'{}' expects {} args, got {}", name, params.len(), args.len()), node: "Node::Call".into() };
+                }
+                self.invoke_fn_def(name, &params, &body, args)
+            }
+            Some(_) => ExecResult::Fault { msg: format!("'{}' is not a function", name), node: "Node::Call".into() },
+            None => match self.call_native(name, &args) {
+                Some(res) => res,
+                None => ExecResult::Fault { msg: format!("Function '{}' not found", name), node: "Node::Call".into() },
+            },
+        }
+    }
+
+    /// Calls a function value (as produced by `fn` definitions) or a function named by a string.
+    pub fn call_value(&mut self, func: &RelType, args: Vec<RelType>) -> ExecResult {
+        match func {
+            RelType::FnDef(name, params, body) => {
+                if params.len() != args.len() {
+                    return ExecResult::Fault { msg: format!("'{}' expects {} args, got {}", name, params.len(), args.len()), node: "Node::Call".into() };
+                }
+                self.invoke_fn_def(name, params, body, args)
+            }
+            RelType::Str(name) => self.call_function(name, args),
+            other => ExecResult::Fault { msg: format!("{} is not callable", other), node: "Node::Call".into() },
+        }
+    }
+
+    /// Dispatches to registered host functions, then native modules.
+    /// Returns `None` if nobody handles `name`.
+    pub fn call_native(&mut self, name: &str, args: &[RelType]) -> Option<ExecResult> {
+        if let Some(host) = self.host_functions.get(name) {
+            return Some(host.call(name, args));
+        }
+        if let Some(res) = self.call_promise_native(name, args) {
+            return Some(res);
+        }
+        if let Some(res) = self.call_timer_native(name, args) {
+            return Some(res);
+        }
+        if let Some(res) = self.call_bus_native(name, args) {
+            return Some(res);
+        }
+        if let Some(res) = self.call_task_native(name, args) {
+            return Some(res);
+        }
+        // Modules are cloned out of the list so callbacks can re-enter the engine.
+        for i in 0..self.native_modules.len() {
+            let module = self.native_modules[i].clone();
+            if let Some(res) = module.handle_in(&mut crate::natives::NativeContext::new(self), name, args) { return Some(res); }
+        }
+        None
+    }
+
     pub fn do_math(&mut self, left: &Node, op: char, right: &Node) -> ExecResult {
+        use crate::numeric::{BinOp, NumericError};
         let lv = match self.evaluate_inner(left) { ExecResult::Value(v) => v, err => return err };
         let rv = match self.evaluate_inner(right) { ExecResult::Value(v) => v, err => return err };
-        let res = match op {
-            '+' => match (lv, rv) {
-                (RelType::Int(a), RelType::Int(b)) => RelType::Int(a + b),
-                (RelType::Float(a), RelType::Float(b)) => RelType::Float(a + b),
-                (RelType::Str(a), RelType::Str(b)) => RelType::Str(a + &b),
-                _ => return ExecResult::Fault { msg: "Invalid types for +".into(), node: "Node::Add".into() },
-            },
-            '-' => match (lv, rv) {
-                (RelType::Int(a), RelType::Int(b)) => RelType::Int(a - b),
-                (RelType::Float(a), RelType::Float(b)) => RelType::Float(a - b),
-                _ => return ExecResult::Fault { msg: "Invalid types for -".into(), node: "Node::Sub".into() },
-            },
-            '*' => match (lv, rv) {
-                (RelType::Int(a), RelType::Int(b)) => RelType::Int(a * b),
-                (RelType::Float(a), RelType::Float(b)) => RelType::Float(a * b),
-                _ => return ExecResult::Fault { msg: "Invalid types for *".into(), node: "Node::Mul".into() },
-            },
-            '/' => match (lv, rv) {
-                (RelType::Int(a), RelType::Int(b)) => { if b == 0 { return ExecResult::Fault { msg: "Div by zero".into(), node: "Node::MathDiv".into() } } RelType::Int(a / b) },
-                (RelType::Float(a), RelType::Float(b)) => RelType::Float(a / b),
-                _ => return ExecResult::Fault { msg: "Invalid types for /".into(), node: "Node::Div".into() },
-            },
-            _ => return ExecResult::Fault { msg: format!("Unknown operator: {}", op), node: "Unknown".into() },
+        let Some(bin) = BinOp::from_symbol(op) else {
+            return ExecResult::Fault { msg: format!("Unknown operator: {}", op), node: "Unknown".into() };
         };
-        ExecResult::Value(res)
+        match crate::numeric::arith(bin, &lv, &rv) {
+            Ok(v) => ExecResult::Value(v),
+            Err(e) => {
+                let node = match (&e, bin) {
+                    (NumericError::DivByZero, _) => "Node::MathDiv",
+                    (_, BinOp::Add) => "Node::Add",
+                    (_, BinOp::Sub) => "Node::Sub",
+                    (_, BinOp::Mul) => "Node::Mul",
+                    (_, BinOp::Div) => "Node::Div",
+                };
+                ExecResult::Fault { msg: e.to_string(), node: node.into() }
+            }
+        }
     }
 
     pub fn do_compare(&mut self, left: &Node, op: &str, right: &Node) -> ExecResult {
+        use crate::numeric::CmpOp;
         let lv = match self.evaluate_inner(left) { ExecResult::Value(v) => v, err => return err };
         let rv = match self.evaluate_inner(right) { ExecResult::Value(v) => v, err => return err };
-        let res = match op {
-            "==" => RelType::Bool(lv == rv),
-            "<" => match (lv, rv) {
-                (RelType::Int(a), RelType::Int(b)) => RelType::Bool(a < b),
-                (RelType::Float(a), RelType::Float(b)) => RelType::Bool(a < b),
-                _ => return ExecResult::Fault { msg: "Invalid types for <".into(), node: "Node::Lt".into() },
-            },
-            ">" => match (lv, rv) {
-                (RelType::Int(a), RelType::Int(b)) => RelType::Bool(a > b),
-                (RelType::Float(a), RelType::Float(b)) => RelType::Bool(a > b),
-                _ => return ExecResult::Fault { msg: "Invalid types for >".into(), node: "Node::Gt".into() },
-            },
+        let (cmp, node) = match op {
+            "==" => (CmpOp::Eq, "Node::Eq"),
+            "<" => (CmpOp::Lt, "Node::Lt"),
+            ">" => (CmpOp::Gt, "Node::Gt"),
             _ => return ExecResult::Fault { msg: format!("Unknown comparison: {}", op), node: "Unknown".into() },
         };
-        ExecResult::Value(res)
+        match crate::numeric::compare(cmp, &lv, &rv) {
+            Ok(b) => ExecResult::Value(RelType::Bool(b)),
+            Err(e) => ExecResult::Fault { msg: e.to_string(), node: node.into() },
+        }
     }
 
+    /// A `Vec3` or an array of exactly three numbers; anything else is `None`.
     pub(crate) fn to_vec3(&self, val: RelType) -> Option<[f32; 3]> {
-        if let RelType::Array(arr) = val {
-            if arr.len() >= 3 {
-                let x = match arr[0] { RelType::Float(f) => f as f32, RelType::Int(i) => i as f32, _ => 0.0 };
-                let y = match arr[1] { RelType::Float(f) => f as f32, RelType::Int(i) => i as f32, _ => 0.0 };
-                let z = match arr[2] { RelType::Float(f) => f as f32, RelType::Int(i) => i as f32, _ => 0.0 };
-                return Some([x, y, z]);
-            }
+        use crate::natives::convert::FromRel;
+        match val {
+            RelType::Vec3(v) => Some(v.to_array()),
+            RelType::Array(arr) if arr.len() == 3 => arr.iter().map(f32::from_rel).collect::<Result<Vec<_>, _>>().ok().map(|c| [c[0], c[1], c[2]]),
+            _ => None,
         }
-        None
     }
 }
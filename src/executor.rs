@@ -33,6 +33,12 @@ pub enum RelType {
     FnDef(String, Vec<String>, Box<Node>),
     Call(String, Vec<Node>),
     Void,
+    Vec2(glam::Vec2),
+    Vec3(glam::Vec3),
+    Vec4(glam::Vec4),
+    Quat(glam::Quat),
+    Mat4(glam::Mat4),
+    Bytes(Vec<u8>),
 }
 
 #[derive(Clone)]
@@ -53,7 +59,7 @@ impl std::fmt::Display for RelType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             RelType::Int(v) => write!(f, "{}", v),
-            RelType::Float(v) => if v.fract() == 0.0 && v.abs() < 1e15 { write!(f, "{:.1}", v) } else { write!(f, "{}", v) },
+            RelType::Float(v) => write!(f, "{}", crate::numeric::format_float(*v)),
             RelType::Bool(v) => write!(f, "{}", v),
             RelType::Str(v) => write!(f, "{}", v),
             RelType::Array(v) => { let s: Vec<String> = v.iter().map(|i| i.to_string()).collect(); write!(f, "[{}]", s.join(", ")) }
@@ -62,6 +68,16 @@ impl std::fmt::Display for RelType {
             RelType::FnDef(_, _, _) => write!(f, "<Function>"),
             RelType::Call(_, _) => write!(f, "<Function Call>"),
             RelType::Void => write!(f, ""),
+            RelType::Vec2(v) => write!(f, "Vec2({}, {})", v.x, v.y),
+            RelType::Vec3(v) => write!(f, "Vec3({}, {}, {})", v.x, v.y, v.z),
+            RelType::Vec4(v) => write!(f, "Vec4({}, {}, {}, {})", v.x, v.y, v.z, v.w),
+            RelType::Quat(q) => write!(f, "Quat({}, {}, {}, {})", q.x, q.y, q.z, q.w),
+            RelType::Mat4(m) => { let c: Vec<String> = m.to_cols_array().iter().map(|v| v.to_string()).collect(); write!(f, "Mat4({})", c.join(", ")) }
+            RelType::Bytes(b) => {
+                // Hex dump, cut after 32 bytes so file contents stay printable
+                let shown: Vec<String> = b.iter().take(32).map(|x| format!("{:02x}", x)).collect();
+                if b.len() > 32 { write!(f, "Bytes({} ... {} bytes)", shown.join(" "), b.len()) } else { write!(f, "Bytes({})", shown.join(" ")) }
+            }
         }
     }
 }
@@ -137,7 +153,7 @@ pub struct MeshUniforms {
 pub struct ExecutionEngine {
     pub memory: HashMap<String, RelType>,
     pub startup_time: std::time::Instant,
-    pub native_modules: Vec<Box<dyn NativeModule>>,
+    pub native_modules: Vec<Arc<dyn NativeModule>>,
     pub bridge: Box<dyn BridgeModule>,
     // ── Camera / FPS state (read by executor nodes) ───────────────────
     pub camera_active: bool,
@@ -175,6 +191,15 @@ pub struct ExecutionEngine {
     pub samples: HashMap<i64, std::sync::Arc<[u8]>>,
     // ── Async / Permissions / Actions ────────────────────────────────
     pub async_bridge: Option<crate::async_bridge::AsyncBridge>,
+    /// Finished legacy `Fetch(...) => { }` requests whose blocks have not run yet.
+    pub deferred_fetches: Vec<(Box<Node>, Result<String, String>)>,
+    pub clock: crate::timers::Clock,
+    pub timers: crate::timers::TimerQueue,
+    pub event_loop_stopped: bool,
+    pub interrupt: crate::interrupt::InterruptHandle,
+    pub bus: crate::bus::MessageBus,
+    /// Workers started by `Spawn`, keyed by their task (promise) handle id.
+    pub tasks: HashMap<i64, std::thread::JoinHandle<ExecResult>>,
     pub action_tx: Option<std::sync::mpsc::Sender<Action>>,
     pub action_rx: Option<std::sync::mpsc::Receiver<Action>>,
     pub permission_fault: Option<String>,
@@ -193,6 +218,16 @@ pub struct ExecutionEngine {
     // ── Physics AABBs ────────────────────────────────────────────────
     pub world_aabbs: Vec<crate::math::AABB>,
     pub camera_aabb_offset: crate::math::AABB,
+    // ── Tooling ──────────────────────────────────────────────────────
+    pub profiler: Option<Box<crate::profiler::Profiler>>,
+    pub hot_reload: Option<crate::hot_reload::HotReload>,
+    // ── Embedding ────────────────────────────────────────────────────
+    pub host_functions: HashMap<String, crate::embed::HostFunction>,
+    /// When set, `Print` appends here instead of writing to stdout.
+    pub output: Option<Vec<String>>,
+    /// Backs `Store`/`Load`; namespaced per script by the runner. Engines that
+    /// are not given one share the `default` namespace.
+    pub store: crate::vm::storage::KvStore,
 }
 
 // SAFETY: ExecutionEngine is moved to a background thread and stays there.
@@ -202,7 +237,11 @@ pub struct ExecutionEngine {
 // threads simultaneously, so Send alone is sufficient.
 unsafe impl Send for ExecutionEngine {}
 
-pub enum Action { UpdateData(String, RelType) }
+pub enum Action {
+    UpdateData(String, RelType),
+    /// Host-side `BusSender::publish`: queue a message for script handlers.
+    Publish(String, RelType),
+}
 
 pub enum ExecResult { Value(RelType), ReturnBlockInfo(RelType), Fault { msg: String, node: String } }
 
@@ -226,21 +265,33 @@ impl ExecutionEngine {
         self.evaluate(node)
     }
 
-    pub fn poll_async_bridge(&mut self) {
-        let mut payloads = Vec::new();
-        if let Some(bridge) = &self.async_bridge {
-            while let Some(payload) = bridge.try_recv() {
-                payloads.push(payload);
+    /// Settles the promises of finished `Fetch` requests without running any
+    /// script code. Replies for legacy callback blocks wait in
+    /// `deferred_fetches` until `poll_async_bridge` runs them.
+    pub(crate) fn settle_fetch_promises(&mut self) {
+        let Some(bridge) = &self.async_bridge else { return };
+        while let Some(payload) = bridge.try_recv() {
+            match payload.reply {
+                crate::async_bridge::FetchReply::Promise(settler) => {
+                    settler.settle(payload.payload.map(RelType::Str));
+                }
+                crate::async_bridge::FetchReply::Callback(callback_node) => {
+                    self.deferred_fetches.push((callback_node, payload.payload));
+                }
             }
         }
-        for payload in payloads {
-            let (data, is_err) = match payload.payload {
+    }
+
+    pub fn poll_async_bridge(&mut self) {
+        self.settle_fetch_promises();
+        for (callback_node, payload) in std::mem::take(&mut self.deferred_fetches) {
+            let (data, is_err) = match payload {
                 Ok(s) => (RelType::Str(s), RelType::Bool(false)),
                 Err(e) => (RelType::Str(e), RelType::Bool(true)),
             };
             self.memory.insert("fetch_result".into(), data);
             self.memory.insert("fetch_error".into(), is_err);
-            let _ = self.evaluate(&payload.callback_node);
+            let _ = self.evaluate(&callback_node);
         }
     }
 
@@ -368,6 +419,13 @@ impl ExecutionEngine {
             audio_stream_handle: None,
             samples: HashMap::new(),
             async_bridge: Some(crate::async_bridge::AsyncBridge::new()),
+            deferred_fetches: Vec::new(),
+            clock: crate::timers::Clock::Real,
+            timers: crate::timers::TimerQueue::default(),
+            event_loop_stopped: false,
+            interrupt: crate::interrupt::InterruptHandle::default(),
+            bus: crate::bus::MessageBus::default(),
+            tasks: HashMap::new(),
             action_tx: None,
             action_rx: None,
             permission_fault: None,
@@ -384,13 +442,26 @@ impl ExecutionEngine {
             weapon_sway: (0.0, 0.0),
             world_aabbs: Vec::new(),
             camera_aabb_offset: crate::math::AABB::new([-0.3, -1.6, -0.3], [0.3, 0.2, 0.3]),
+            profiler: None,
+            hot_reload: None,
+            host_functions: HashMap::new(),
+            output: None,
+            store: crate::vm::storage::KvStore::default(),
         };
         let (tx, rx) = std::sync::mpsc::channel();
         engine.action_tx = Some(tx);
         engine.action_rx = Some(rx);
-        engine.native_modules.push(Box::new(crate::natives::math::MathModule));
-        engine.native_modules.push(Box::new(crate::natives::io::IoModule));
-        engine.native_modules.push(Box::new(crate::natives::registry::RegistryModule));
+        engine.native_modules.push(Arc::new(crate::natives::math::MathModule));
+        engine.native_modules.push(Arc::new(crate::natives::io::IoModule));
+        engine.native_modules.push(Arc::new(crate::natives::string::StringModule));
+        engine.native_modules.push(Arc::new(crate::natives::array::ArrayModule));
+        engine.native_modules.push(Arc::new(crate::natives::vector::VectorModule));
+        engine.native_modules.push(Arc::new(crate::natives::json::JsonModule));
+        engine.native_modules.push(Arc::new(crate::natives::bytes::BytesModule));
+        engine.native_modules.push(Arc::new(crate::natives::csv::CsvModule));
+        engine.native_modules.push(Arc::new(crate::natives::template::TemplateModule));
+        engine.native_modules.push(Arc::new(crate::natives::store::StoreModule));
+        engine.native_modules.push(Arc::new(crate::natives::registry::RegistryModule));
         engine
     }
 
@@ -399,7 +470,10 @@ impl ExecutionEngine {
             Node::PollEvents(body) => { self.evaluate(body) }
             Node::Print(expr) => {
                 match self.evaluate(expr) {
-                    ExecResult::Value(v) => { println!("{}", v); ExecResult::Value(RelType::Void) }
+                    ExecResult::Value(v) => {
+                        match &mut self.output { Some(out) => out.push(v.to_string()), None => println!("{}", v) }
+                        ExecResult::Value(RelType::Void)
+                    }
                     err => err,
                 }
             }
@@ -436,13 +510,13 @@ impl ExecutionEngine {
                 ExecResult::Value(RelType::Void)
             }
             Node::AddWorldAABB { min, max } => {
-                let v_min = match self.evaluate(min) { ExecResult::Value(v) => self.to_vec3(v), _ => None };
-                let v_max = match self.evaluate(max) { ExecResult::Value(v) => self.to_vec3(v), _ => None };
+                let v_min = match self.evaluate(min) { ExecResult::Value(v) => self.to_vec3(v), fault => return fault };
+                let v_max = match self.evaluate(max) { ExecResult::Value(v) => self.to_vec3(v), fault => return fault };
                 if let (Some(mi), Some(ma)) = (v_min, v_max) {
                     self.world_aabbs.push(crate::math::AABB::new(mi, ma));
                     ExecResult::Value(RelType::Void)
                 } else {
-                    ExecResult::Fault { msg: "AddWorldAABB expects two arrays of 3 floats".into(), node: "Node::AddWorldAABB".into() }
+                    ExecResult::Fault { msg: "AddWorldAABB expects two Vec3 values or arrays of 3 numbers".into(), node: "Node::AddWorldAABB".into() }
                 }
             }
             Node::EnableInteraction(b) => {
@@ -454,7 +528,11 @@ impl ExecutionEngine {
                 ExecResult::Value(RelType::Void)
             }
             Node::FPSCamera { fov } => {
-                if let ExecResult::Value(RelType::Float(f)) = self.evaluate(fov) { self.camera_fov = f as f32; self.camera_active = true; }
+                match self.evaluate(fov) {
+                    ExecResult::Value(RelType::Float(f)) => { self.camera_fov = f as f32; self.camera_active = true; }
+                    ExecResult::Value(RelType::Int(i)) => { self.camera_fov = i as f32; self.camera_active = true; }
+                    _ => {}
+                }
                 ExecResult::Value(RelType::Void)
             }
             Node::WeaponViewModel { mesh, tex } => {
@@ -463,12 +541,19 @@ impl ExecutionEngine {
                 ExecResult::Value(RelType::Void)
             }
             Node::Store { key, value } => {
-                if let ExecResult::Value(v) = self.evaluate(value) { self.memory.insert(key.clone(), v); }
-                ExecResult::Value(RelType::Void)
+                if !self.permissions.allow_fs_write { return ExecResult::Fault { msg: "Permission Denied: allow_fs_write is false".into(), node: "Node::Store".into() }; }
+                let v = match self.evaluate(value) { ExecResult::Value(v) => v, fault => return fault };
+                match self.store.put(key, &v) {
+                    Ok(()) => ExecResult::Value(RelType::Void),
+                    Err(e) => ExecResult::Fault { msg: format!("Store: {}", e), node: "Node::Store".into() },
+                }
             }
             Node::Load { key } => {
-                if let Some(v) = self.memory.get(key) { ExecResult::Value(v.clone()) }
-                else { ExecResult::Value(RelType::Void) }
+                if !self.permissions.allow_fs_read { return ExecResult::Fault { msg: "Permission Denied: allow_fs_read is false".into(), node: "Node::Load".into() }; }
+                match self.store.get(key) {
+                    Ok(v) => ExecResult::Value(v.unwrap_or(RelType::Void)),
+                    Err(e) => ExecResult::Fault { msg: format!("Load: {}", e), node: "Node::Load".into() },
+                }
             }
             Node::FileRead(path) => {
                 if !self.permissions.allow_fs_read { return ExecResult::Fault { msg: "Permission Denied: allow_fs_read is false".into(), node: "Node::FileRead".into() }; }
@@ -496,6 +581,34 @@ impl ExecutionEngine {
                     }
                 } else { ExecResult::Fault { msg: "FileWrite expects string path and data".into(), node: "Node::FileWrite".into() } }
             }
+            Node::FileReadBytes(path) => {
+                if !self.permissions.allow_fs_read { return ExecResult::Fault { msg: "Permission Denied: allow_fs_read is false".into(), node: "Node::FileReadBytes".into() }; }
+                if let ExecResult::Value(RelType::Str(p)) = self.evaluate(path) {
+                    match Self::validate_fs_path(&p) {
+                        Err(e) => ExecResult::Fault { msg: format!("Security: {}", e), node: "Node::FileReadBytes".into() },
+                        Ok(safe_path) => match std::fs::read(&safe_path) {
+                            Ok(b) => ExecResult::Value(RelType::Bytes(b)),
+                            Err(e) => ExecResult::Fault { msg: format!("File read error: {}", e), node: "Node::FileReadBytes".into() },
+                        }
+                    }
+                } else { ExecResult::Fault { msg: "FileReadBytes expects string path".into(), node: "Node::FileReadBytes".into() } }
+            }
+            Node::FileWriteBytes(path, data) => {
+                if !self.permissions.allow_fs_write { return ExecResult::Fault { msg: "Permission Denied: allow_fs_write is false".into(), node: "Node::FileWriteBytes".into() }; }
+                let fault = |msg: String| ExecResult::Fault { msg, node: "Node::FileWriteBytes".into() };
+                let (p, d) = match (self.evaluate(path), self.evaluate(data)) {
+                    (ExecResult::Value(RelType::Str(p)), ExecResult::Value(d)) => (p, d),
+                    _ => return fault("FileWriteBytes expects a string path and bytes".into()),
+                };
+                let bytes = match crate::natives::bytes::to_bytes(&d) { Ok(b) => b, Err(e) => return fault(format!("FileWriteBytes: {}", e)) };
+                match Self::validate_fs_path_write(&p) {
+                    Err(e) => fault(format!("Security: {}", e)),
+                    Ok(safe_path) => match std::fs::write(&safe_path, &bytes) {
+                        Ok(()) => ExecResult::Value(RelType::Void),
+                        Err(e) => fault(format!("File write error: {}", e)),
+                    }
+                }
+            }
             Node::FSRead(path) => {
                 if !self.permissions.allow_fs_read { return ExecResult::Fault { msg: "Permission Denied: allow_fs_read is false".into(), node: "Node::FSRead".into() }; }
                 if let ExecResult::Value(RelType::Str(p)) = self.evaluate(path) {
@@ -525,9 +638,7 @@ impl ExecutionEngine {
             Node::NativeCall(name, args) => {
                 let mut v_args = Vec::with_capacity(args.len());
                 for a in args { match self.evaluate(a) { ExecResult::Value(v) => v_args.push(v), err => return err } }
-                for mod_ in &self.native_modules {
-                    if let Some(res) = mod_.handle(name, &v_args, &self.permissions) { return res; }
-                }
+                if let Some(res) = self.call_native(name, &v_args) { return res; }
                 ExecResult::Fault { msg: format!("Native function '{}' not found", name), node: "Node::NativeCall".into() }
             }
             Node::ExternCall { module, function, args } => {
@@ -582,15 +693,34 @@ impl ExecutionEngine {
                 if !self.permissions.allow_network {
                     return ExecResult::Fault { msg: "Permission Denied: allow_network is false. Use --allow-network flag.".into(), node: "Node::Fetch".into() };
                 }
+                let policy = crate::net_policy::NetPolicy::new(&self.permissions.allowed_domains);
+                if let Err(e) = policy.check_url(url) {
+                    return ExecResult::Fault { msg: format!("Fetch: {}", e), node: "Node::Fetch".into() };
+                }
                 if let Some(bridge) = &self.async_bridge {
-                    bridge.dispatch_fetch(method.clone(), url.clone(), callback.clone());
+                    bridge.dispatch_fetch(method.clone(), url.clone(), policy, callback.clone());
                     ExecResult::Value(RelType::Void)
                 } else { ExecResult::Fault { msg: "AsyncBridge not initialized".into(), node: "Node::Fetch".into() } }
             }
-            Node::Extract { .. } => ExecResult::Fault { msg: "Extract not implemented".into(), node: "Node::Extract".into() },
+            Node::Extract { source, path } => {
+                let fault = |msg: String| ExecResult::Fault { msg: format!("Extract: {}", msg), node: "Node::Extract".into() };
+                let source = match self.evaluate(source) {
+                    ExecResult::Value(v) => v,
+                    other => return other,
+                };
+                let path = match self.evaluate(path) {
+                    ExecResult::Value(RelType::Str(p)) => p,
+                    ExecResult::Value(_) => return fault("path must be a string".into()),
+                    other => return other,
+                };
+                crate::jsonpath::extract(&source, &path).map_or_else(fault, ExecResult::Value)
+            }
             Node::EvalJSONNative(json_expr) => {
                 if let ExecResult::Value(RelType::Str(json)) = self.evaluate(json_expr) {
-                    ExecResult::Value(crate::natives::fs::fs_parse_json(&json))
+                    match crate::natives::fs::fs_parse_json(&json) {
+                        Ok(v) => ExecResult::Value(v),
+                        Err(e) => ExecResult::Fault { msg: format!("EvalJSONNative: {}", e), node: "Node::EvalJSONNative".into() },
+                    }
                 } else { ExecResult::Fault { msg: "EvalJSONNative expects string".into(), node: "Node::EvalJSONNative".into() } }
             }
             Node::ToString(expr) => {
@@ -602,7 +732,26 @@ impl ExecutionEngine {
             Node::RenderCanvas { body } => self.evaluate(body),
             Node::Transform2D { body, .. } => self.evaluate(body),
             Node::Sprite2D { .. } => ExecResult::Value(RelType::Void),
-            Node::Camera3D { .. } => { self.camera_active = true; ExecResult::Value(RelType::Void) }
+            Node::Camera3D { pos_x, pos_y, pos_z, target_x, target_y, target_z, fov } => {
+                let pos = match self.camera_point([&**pos_x, &**pos_y, &**pos_z]) { Ok(p) => p, Err(e) => return e };
+                let target = match self.camera_point([&**target_x, &**target_y, &**target_z]) { Ok(p) => p, Err(e) => return e };
+                let fov = match self.evaluate(fov) {
+                    ExecResult::Value(RelType::Float(f)) => f as f32,
+                    ExecResult::Value(RelType::Int(i)) => i as f32,
+                    ExecResult::Value(_) => return ExecResult::Fault { msg: "Camera3D fov must be a number".into(), node: "Node::Camera3D".into() },
+                    err => return err,
+                };
+                // Express the look direction as the FPS camera's yaw/pitch (yaw -90 looks down -Z).
+                let dir = target - pos;
+                if let Some(dir) = dir.try_normalize() {
+                    self.camera_yaw = dir.z.atan2(dir.x).to_degrees();
+                    self.camera_pitch = dir.y.asin().to_degrees();
+                }
+                self.camera_pos = pos.to_array();
+                self.camera_fov = fov;
+                self.camera_active = true;
+                ExecResult::Value(RelType::Void)
+            }
             Node::Material3D { .. } => ExecResult::Value(RelType::Void),
             Node::MeshInstance3D { .. } => ExecResult::Value(RelType::Void),
             Node::RaycastSimple => ExecResult::Value(RelType::Void),
@@ -614,6 +763,35 @@ impl ExecutionEngine {
             _ => ExecResult::Fault { msg: format!("Unsupported node in executor: {:?}", node), node: "Executor".into() },
         }
     }
+
+    /// A Camera3D point: three numbers, or a `Vec3` (or 3-number array) in the
+    /// first slot with the other two left as Void or 0.
+    fn camera_point(&mut self, nodes: [&Node; 3]) -> Result<glam::Vec3, ExecResult> {
+        let invalid = || ExecResult::Fault {
+            msg: "Camera3D expects three numbers, or a Vec3 followed by two 0 placeholders".into(),
+            node: "Node::Camera3D".into(),
+        };
+        let mut values = Vec::with_capacity(3);
+        for node in nodes {
+            match self.evaluate(node) {
+                ExecResult::Value(v) => values.push(v),
+                err => return Err(err),
+            }
+        }
+        let number = |v: &RelType| match v {
+            RelType::Float(f) => Some(*f as f32),
+            RelType::Int(n) => Some(*n as f32),
+            _ => None,
+        };
+        let placeholder = |v: &RelType| matches!(v, RelType::Void) || number(v) == Some(0.0);
+        match (number(&values[0]), number(&values[1]), number(&values[2])) {
+            (Some(x), Some(y), Some(z)) => Ok(glam::Vec3::new(x, y, z)),
+            (None, _, _) if placeholder(&values[1]) && placeholder(&values[2]) => {
+                self.to_vec3(values.swap_remove(0)).map(glam::Vec3::from).ok_or_else(invalid)
+            }
+            _ => Err(invalid()),
+        }
+    }
 }
 
 impl ExecutionEngine {
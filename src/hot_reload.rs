@@ -0,0 +1,230 @@
+use crate::ast::Node;
+use crate::executor::{ExecResult, ExecutionEngine, RelType};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::mpsc::{Receiver, Sender, channel};
+use std::time::{Duration, SystemTime};
+
+/// What a watcher or a reload did, for the host to show however it likes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReloadEvent {
+    /// The file changed and the new version was queued.
+    Changed(PathBuf),
+    /// The new version failed to parse or type-check; the old one keeps running.
+    Rejected { path: PathBuf, diagnostic: String },
+    /// The engine swapped in the queued version.
+    Swapped { functions: usize },
+    /// A new global's initializer faulted, so the global was not created.
+    InitFailed { name: String, fault: String },
+}
+
+impl ReloadEvent {
+    pub fn is_error(&self) -> bool {
+        matches!(self, ReloadEvent::Rejected { .. } | ReloadEvent::InitFailed { .. })
+    }
+}
+
+impl fmt::Display for ReloadEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReloadEvent::Changed(path) => write!(f, "{} changed, reloading.", path.display()),
+            ReloadEvent::Rejected { path, diagnostic } => {
+                write!(f, "{} has errors, keeping previous version:\n{}", path.display(), diagnostic)
+            }
+            ReloadEvent::Swapped { functions } => write!(f, "Swapped {} function(s).", functions),
+            ReloadEvent::InitFailed { name, fault } => write!(f, "Could not initialize '{}': {}", name, fault),
+        }
+    }
+}
+
+/// Receives every `ReloadEvent`, from the watcher thread or the engine's.
+pub type Reporter = Arc<dyn Fn(&ReloadEvent) + Send + Sync>;
+
+/// Receiving end of a script watcher. Attach it to `ExecutionEngine::hot_reload`;
+/// the engine swaps in new code at the next `While` iteration or event-loop pass.
+pub struct HotReload {
+    rx: Receiver<Node>,
+    report: Reporter,
+}
+
+impl HotReload {
+    /// A reload channel that reports nothing.
+    pub fn channel() -> (Sender<Node>, HotReload) {
+        Self::channel_with(Arc::new(|_: &ReloadEvent| {}))
+    }
+
+    pub fn channel_with(report: Reporter) -> (Sender<Node>, HotReload) {
+        let (tx, rx) = channel();
+        (tx, HotReload { rx, report })
+    }
+
+    /// Latest pending program, skipping intermediate versions.
+    fn take_latest(&self) -> Option<Node> {
+        let mut latest = None;
+        while let Ok(ast) = self.rx.try_recv() {
+            latest = Some(ast);
+        }
+        latest
+    }
+}
+
+/// Parses, type-checks and (optionally) optimizes the script at `path`.
+/// Errors are returned as printable diagnostics.
+pub fn prepare(path: &Path, optimize: bool) -> Result<Node, String> {
+    let source = std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    let ast = if path.extension().is_some_and(|e| e == "knoten") {
+        crate::parser::try_parse(&source)?
+    } else {
+        serde_json::from_str(&source).map_err(|e| format!("Invalid AST JSON: {}", e))?
+    };
+    let mut typer = crate::optimizer::TypeChecker::new();
+    let _ = typer.check(&ast);
+    if !typer.errors.is_empty() {
+        let errs: Vec<String> = typer.errors.iter().map(|e| format!(" - {}", e)).collect();
+        return Err(format!("Static Type Inference Failed:\n{}", errs.join("\n")));
+    }
+    Ok(if optimize { crate::optimizer::optimize(ast) } else { ast })
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Spawns a thread that polls `path` and sends every version that parses and type-checks.
+/// Broken versions go to `report` as `Rejected` and the previous code keeps running.
+pub fn watch(path: impl Into<PathBuf>, optimize: bool, report: Reporter) -> HotReload {
+    let path = path.into();
+    let (tx, reload) = HotReload::channel_with(report.clone());
+    std::thread::Builder::new()
+        .name("knoten-watch".into())
+        .spawn(move || {
+            let mut last = modified(&path);
+            loop {
+                std::thread::sleep(Duration::from_millis(250));
+                let now = modified(&path);
+                if now.is_none() || now == last {
+                    continue;
+                }
+                last = now;
+                match prepare(&path, optimize) {
+                    Ok(ast) => {
+                        report(&ReloadEvent::Changed(path.clone()));
+                        if tx.send(ast).is_err() {
+                            break; // Engine is gone.
+                        }
+                    }
+                    Err(diagnostic) => report(&ReloadEvent::Rejected { path: path.clone(), diagnostic }),
+                }
+            }
+        })
+        .expect("Failed to spawn watcher thread");
+    reload
+}
+
+impl ExecutionEngine {
+    /// Applies the newest pending reload, if any. Top-level `FnDef`s replace the
+    /// current definitions, including timer callbacks and `On` handlers bound to
+    /// them, and top-level `let`s initialize globals that do not exist yet;
+    /// everything else in memory and the registry is left untouched.
+    pub fn apply_hot_reload(&mut self) {
+        let Some(reload) = &self.hot_reload else { return };
+        let Some(ast) = reload.take_latest() else { return };
+        let report = reload.report.clone();
+        let statements = match ast {
+            Node::Block(stmts) => stmts,
+            other => vec![other],
+        };
+        let mut defs = HashMap::new();
+        for stmt in statements {
+            match stmt {
+                Node::FnDef(name, params, body) => {
+                    let def = RelType::FnDef(name.clone(), params, body);
+                    self.memory.insert(name.clone(), def.clone());
+                    defs.insert(name, def);
+                }
+                Node::Assign(name, expr) if !self.memory.contains_key(&name) => {
+                    match self.evaluate(&expr) {
+                        ExecResult::Value(v) | ExecResult::ReturnBlockInfo(v) => {
+                            self.memory.insert(name, v);
+                        }
+                        fault => report(&ReloadEvent::InitFailed { name, fault: fault.to_string() }),
+                    }
+                }
+                _ => {}
+            }
+        }
+        // Registered callbacks hold the function value they were given.
+        let callbacks = self.timers.callbacks_mut().chain(self.bus.handlers_mut());
+        for callback in callbacks {
+            if let RelType::FnDef(name, ..) = callback
+                && let Some(def) = defs.get(name)
+            {
+                *callback = def.clone();
+            }
+        }
+        report(&ReloadEvent::Swapped { functions: defs.len() });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reload_swaps_functions_between_iterations() {
+        let mut engine = ExecutionEngine::new();
+        let (tx, reload) = HotReload::channel();
+        engine.hot_reload = Some(reload);
+
+        let v1 = crate::parser::try_parse(
+            "fn step() { return 1; }\nlet total = 0;\nlet i = 0;\nwhile (i < 3) { total = total + step(); i = i + 1; }\ntotal",
+        )
+        .unwrap();
+        let v2 = crate::parser::try_parse("fn step() { return 10; }\nlet total = 999;\nlet bonus = 5;").unwrap();
+        tx.send(v2).unwrap();
+
+        // The first iteration runs the old step(); the swap happens at the back-edge.
+        assert!(matches!(engine.execute(&v1), ExecResult::Value(RelType::Int(21))));
+        assert_eq!(engine.memory["bonus"], RelType::Int(5));
+    }
+
+    #[test]
+    fn test_event_loop_reloads_and_rebinds_callbacks() {
+        let mut engine = ExecutionEngine::new();
+        engine.use_virtual_clock();
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let (tx, reload) = HotReload::channel_with(Arc::new(move |e: &ReloadEvent| sink.lock().unwrap().push(e.clone())));
+        engine.hot_reload = Some(reload);
+
+        let v1 = crate::parser::try_parse(
+            "let log = [];\nfn tick() { ArrayPush(log, 1); if (ArrayLen(log) == 4) { ClearTimer(timer); } }\n\
+             fn hit(m) { ArrayPush(log, m); }\nlet timer = SetInterval(tick, 100);\nOn(\"hit\", hit);",
+        )
+        .unwrap();
+        assert!(!matches!(engine.execute(&v1), ExecResult::Fault { .. }));
+        assert!(engine.service_events().is_ok());
+
+        let v2 = crate::parser::try_parse("fn tick() { ArrayPush(log, 2); if (ArrayLen(log) == 4) { ClearTimer(timer); } }\nfn hit(m) { ArrayPush(log, m * 10); }\nlet fresh = Undefined();").unwrap();
+        tx.send(v2).unwrap();
+        engine.bus_sender().unwrap().publish("hit", 3i64);
+        assert!(matches!(engine.run_event_loop(), ExecResult::Value(RelType::Void)));
+
+        let log: Vec<RelType> = [30, 2, 2, 2].into_iter().map(RelType::Int).collect();
+        assert_eq!(engine.memory["log"], RelType::Array(log));
+        let events = events.lock().unwrap();
+        assert!(matches!(&events[0], ReloadEvent::InitFailed { name, .. } if name == "fresh"));
+        assert_eq!(events[1], ReloadEvent::Swapped { functions: 2 });
+    }
+
+    #[test]
+    fn test_prepare_reports_parse_errors() {
+        let path = Path::new("target/knoten_hot_reload_test.knoten");
+        std::fs::write(path, "fn broken( {").unwrap();
+        let err = prepare(path, true).unwrap_err();
+        let _ = std::fs::remove_file(path);
+        assert!(err.contains("diagnostic"), "{}", err);
+    }
+}
@@ -0,0 +1,80 @@
+use crate::executor::{ExecResult, ExecutionEngine};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Node and message of the fault a cancelled script returns.
+pub const CANCELLED_NODE: &str = "Interrupt";
+pub const CANCELLED_MSG: &str = "Cancelled: script interrupted by host";
+
+/// Host-side switch that stops a running script from any thread.
+///
+/// The evaluator checks it at loop back-edges, function calls and while
+/// waiting on events, then unwinds like any other fault, so call frames and
+/// their handles are released on the way out. The flag stays set until
+/// `reset`, so later runs on the same engine are cancelled too.
+#[derive(Clone, Default)]
+pub struct InterruptHandle(Arc<AtomicBool>);
+
+impl InterruptHandle {
+    pub fn interrupt(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_interrupted(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
+pub fn cancelled() -> ExecResult {
+    ExecResult::Fault { msg: CANCELLED_MSG.into(), node: CANCELLED_NODE.into() }
+}
+
+impl ExecResult {
+    /// Whether this is the fault produced by an `InterruptHandle`.
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, ExecResult::Fault { node, .. } if node == CANCELLED_NODE)
+    }
+}
+
+impl ExecutionEngine {
+    /// A handle sharing this engine's interrupt flag. Tasks started with
+    /// `Spawn` share it as well.
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        self.interrupt.clone()
+    }
+
+    pub(crate) fn check_interrupt(&self) -> Result<(), ExecResult> {
+        if self.interrupt.is_interrupted() { Err(cancelled()) } else { Ok(()) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::RelType;
+    use std::time::Duration;
+
+    #[test]
+    fn test_interrupt_stops_infinite_loop() {
+        let mut engine = ExecutionEngine::new();
+        let handle = engine.interrupt_handle();
+        let ast = crate::parser::try_parse("fn spin(n) { while (true) { n = n + 1; } }\nspin(0)").unwrap();
+
+        let stopper = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(30));
+            handle.interrupt();
+        });
+        let res = engine.execute(&ast);
+        stopper.join().unwrap();
+        assert!(res.is_cancelled(), "{}", res);
+        assert_eq!(engine.call_stack.len(), 1);
+
+        assert!(engine.execute(&crate::parser::try_parse("fn f() { return 1; }\nf()").unwrap()).is_cancelled());
+        engine.interrupt_handle().reset();
+        assert!(matches!(engine.execute(&crate::parser::try_parse("f()").unwrap()), ExecResult::Value(RelType::Int(1))));
+    }
+}
@@ -0,0 +1,337 @@
+//! Path queries over `RelType` values, evaluated by `Node::Extract`.
+//!
+//! Two dialects are accepted:
+//! - JSON Pointer (RFC 6901) when the path starts with `/`: `/data/items/0/name`,
+//!   with `~1` for `/` and `~0` for `~` inside a token. `""` is the whole value.
+//! - A JSONPath subset otherwise, with an optional leading `$`: dotted keys
+//!   (`data.items`), brackets (`['odd key']`, `[0]`, `[-1]` from the end),
+//!   wildcards (`items[*]`, `user.*`) and filters on the elements of an array
+//!   or the values of an object (`[?(@.price < 10)]`, `[?(@.tags)]`,
+//!   `[?(@ == 'x')]`). Filter operators are `== != < <= > >=`, the literals
+//!   JSON values or single-quoted strings; comparisons follow `numeric`.
+//!
+//! A path made of keys and indices names one value: [`extract`] returns it, or
+//! fails naming the first segment that did not resolve. Wildcards and filters
+//! collect every match into an Array instead, and yield `Void` when nothing
+//! matches. Object members are visited in key order.
+
+use crate::executor::RelType;
+use crate::numeric::{self, CmpOp};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(i64),
+    /// A JSON Pointer token: a key on objects, an index on arrays.
+    Token(String),
+    Wildcard,
+    Filter(Filter),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Filter {
+    /// Keys after `@`; empty means the element itself.
+    keys: Vec<String>,
+    /// `None` tests that the keys resolve to a non-Void value.
+    test: Option<(FilterOp, RelType)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FilterOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl fmt::Display for Segment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Segment::Key(k) if is_identifier(k) => write!(f, ".{}", k),
+            Segment::Key(k) => write!(f, "['{}']", k),
+            Segment::Index(i) => write!(f, "[{}]", i),
+            Segment::Token(t) => write!(f, "/{}", t.replace('~', "~0").replace('/', "~1")),
+            Segment::Wildcard => write!(f, "[*]"),
+            Segment::Filter(_) => write!(f, "[?(...)]"),
+        }
+    }
+}
+
+fn is_identifier(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Runs `path` against `value`. Errors are plain messages; the caller adds the
+/// node context.
+pub fn extract(value: &RelType, path: &str) -> Result<RelType, String> {
+    let segments = if path.is_empty() || path.starts_with('/') {
+        parse_pointer(path)
+    } else {
+        parse_jsonpath(path).map_err(|e| format!("invalid path '{}': {}", path, e))?
+    };
+
+    if segments.iter().all(|s| matches!(s, Segment::Key(_) | Segment::Index(_) | Segment::Token(_))) {
+        let mut current = value;
+        for segment in &segments {
+            current = step(current, segment)
+                .map_err(|reason| format!("no match for '{}' in '{}': {}", segment, path, reason))?;
+        }
+        return Ok(current.clone());
+    }
+
+    let mut matches = vec![value];
+    for segment in &segments {
+        matches = matches.into_iter().flat_map(|v| expand(v, segment)).collect();
+    }
+    if matches.is_empty() {
+        Ok(RelType::Void)
+    } else {
+        Ok(RelType::Array(matches.into_iter().cloned().collect()))
+    }
+}
+
+/// Resolves a key or index segment, explaining why it does not apply.
+fn step<'a>(value: &'a RelType, segment: &Segment) -> Result<&'a RelType, String> {
+    match (segment, value) {
+        (Segment::Key(k) | Segment::Token(k), RelType::Object(map)) => {
+            map.get(k).ok_or_else(|| format!("object has no key '{}'", k))
+        }
+        (Segment::Index(i), RelType::Array(items)) => {
+            let idx = if *i < 0 { items.len() as i64 + i } else { *i };
+            usize::try_from(idx)
+                .ok()
+                .and_then(|idx| items.get(idx))
+                .ok_or_else(|| format!("index {} out of range for {} items", i, items.len()))
+        }
+        (Segment::Token(t), RelType::Array(items)) => match t.parse::<usize>() {
+            Ok(idx) => items.get(idx).ok_or_else(|| format!("index {} out of range for {} items", idx, items.len())),
+            Err(_) => Err(format!("'{}' is not an array index", t)),
+        },
+        (Segment::Key(_) | Segment::Token(_), other) => Err(format!("expected an object, found {}", kind(other))),
+        (Segment::Index(_), other) => Err(format!("expected an array, found {}", kind(other))),
+        (Segment::Wildcard | Segment::Filter(_), _) => unreachable!("only definite segments are stepped"),
+    }
+}
+
+/// Every value a segment selects from `value`, for non-definite paths.
+fn expand<'a>(value: &'a RelType, segment: &Segment) -> Vec<&'a RelType> {
+    match segment {
+        Segment::Wildcard => children(value),
+        Segment::Filter(filter) => children(value).into_iter().filter(|v| filter.matches(v)).collect(),
+        _ => step(value, segment).into_iter().collect(),
+    }
+}
+
+fn children(value: &RelType) -> Vec<&RelType> {
+    match value {
+        RelType::Array(items) => items.iter().collect(),
+        RelType::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            keys.into_iter().map(|k| &map[k]).collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn kind(value: &RelType) -> &'static str {
+    match value {
+        RelType::Void => "void",
+        RelType::Array(_) => "an array",
+        RelType::Object(_) => "an object",
+        RelType::Str(_) => "a string",
+        RelType::Int(_) | RelType::Float(_) => "a number",
+        RelType::Bool(_) => "a bool",
+        _ => "a non-JSON value",
+    }
+}
+
+impl Filter {
+    fn matches(&self, element: &RelType) -> bool {
+        let mut current = element;
+        for key in &self.keys {
+            match current {
+                RelType::Object(map) => match map.get(key) {
+                    Some(v) => current = v,
+                    None => return false,
+                },
+                _ => return false,
+            }
+        }
+        let Some((op, literal)) = &self.test else {
+            return *current != RelType::Void;
+        };
+        let cmp = |op| numeric::compare(op, current, literal).unwrap_or(false);
+        match op {
+            FilterOp::Eq => cmp(CmpOp::Eq),
+            FilterOp::Ne => !cmp(CmpOp::Eq),
+            FilterOp::Lt => cmp(CmpOp::Lt),
+            FilterOp::Le => cmp(CmpOp::Lt) || cmp(CmpOp::Eq),
+            FilterOp::Gt => cmp(CmpOp::Gt),
+            FilterOp::Ge => cmp(CmpOp::Gt) || cmp(CmpOp::Eq),
+        }
+    }
+}
+
+fn parse_pointer(path: &str) -> Vec<Segment> {
+    if path.is_empty() {
+        return Vec::new();
+    }
+    path[1..]
+        .split('/')
+        .map(|token| Segment::Token(token.replace("~1", "/").replace("~0", "~")))
+        .collect()
+}
+
+fn parse_jsonpath(path: &str) -> Result<Vec<Segment>, String> {
+    let mut rest = path.strip_prefix('$').unwrap_or(path);
+    let mut segments = Vec::new();
+    // A leading bare key is allowed: `data.items` means `$.data.items`
+    let mut dotted = !rest.is_empty() && !rest.starts_with(['.', '[']);
+    while !rest.is_empty() || dotted {
+        if dotted {
+            if rest.starts_with('.') {
+                return Err("recursive descent '..' is not supported".into());
+            }
+            let end = rest.find(['.', '[']).unwrap_or(rest.len());
+            let key = &rest[..end];
+            segments.push(match key {
+                "" => return Err("empty key".into()),
+                "*" => Segment::Wildcard,
+                _ => Segment::Key(key.to_string()),
+            });
+            rest = &rest[end..];
+            dotted = false;
+        } else if let Some(after) = rest.strip_prefix('.') {
+            rest = after;
+            dotted = true;
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = closing_bracket(after).ok_or("unclosed '['")?;
+            segments.push(parse_bracket(after[..end].trim())?);
+            rest = &after[end + 1..];
+        } else {
+            return Err(format!("unexpected '{}'", rest));
+        }
+    }
+    Ok(segments)
+}
+
+/// Position of the `]` closing a bracket, skipping quoted strings.
+fn closing_bracket(s: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, ']') => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_bracket(inner: &str) -> Result<Segment, String> {
+    if inner == "*" {
+        return Ok(Segment::Wildcard);
+    }
+    if let Some(expr) = inner.strip_prefix("?(").and_then(|e| e.strip_suffix(')')) {
+        return parse_filter(expr.trim()).map(Segment::Filter);
+    }
+    if let Some(key) = unquote(inner) {
+        return Ok(Segment::Key(key));
+    }
+    inner.parse::<i64>().map(Segment::Index).map_err(|_| format!("bad selector '[{}]'", inner))
+}
+
+fn unquote(s: &str) -> Option<String> {
+    let q = s.chars().next().filter(|c| *c == '\'' || *c == '"')?;
+    s.strip_prefix(q)?.strip_suffix(q).map(str::to_string)
+}
+
+fn parse_filter(expr: &str) -> Result<Filter, String> {
+    let mut rest = expr.strip_prefix('@').ok_or("filters must start with '@'")?;
+    let mut keys = Vec::new();
+    while let Some(after) = rest.strip_prefix('.') {
+        let end = after.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(after.len());
+        if end == 0 {
+            return Err("empty key in filter".into());
+        }
+        keys.push(after[..end].to_string());
+        rest = &after[end..];
+    }
+    let rest = rest.trim();
+    if rest.is_empty() {
+        return Ok(Filter { keys, test: None });
+    }
+    const OPS: [(&str, FilterOp); 6] = [
+        ("==", FilterOp::Eq),
+        ("!=", FilterOp::Ne),
+        ("<=", FilterOp::Le),
+        (">=", FilterOp::Ge),
+        ("<", FilterOp::Lt),
+        (">", FilterOp::Gt),
+    ];
+    let (op, literal) = OPS
+        .iter()
+        .find_map(|(sym, op)| rest.strip_prefix(sym).map(|lit| (*op, lit.trim())))
+        .ok_or_else(|| format!("expected a comparison in filter, found '{}'", rest))?;
+    let literal = match literal.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')) {
+        Some(s) => RelType::Str(s.to_string()),
+        None => serde_json::from_str::<serde_json::Value>(literal)
+            .map(|v| crate::natives::fs::json_value_to_reltype(&v))
+            .map_err(|_| format!("bad literal '{}' in filter", literal))?,
+    };
+    Ok(Filter { keys, test: Some((op, literal)) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc() -> RelType {
+        crate::natives::fs::fs_parse_json(
+            r#"{"data": {"items": [
+                {"id": 1, "name": "bolt", "price": 2.5, "tags": ["metal"]},
+                {"id": 2, "name": "nut", "price": 0.5},
+                {"id": 3, "name": "gear", "price": 12}
+            ]}, "odd key": {"a/b": true}}"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_definite_paths() {
+        let d = doc();
+        let name = |p: &str| extract(&d, p).unwrap().to_string();
+        assert_eq!(name("data.items[0].name"), "bolt");
+        assert_eq!(name("$.data.items[-1].id"), "3");
+        assert_eq!(name("$['odd key']['a/b']"), "true");
+        assert_eq!(name("/data/items/1/name"), "nut");
+        assert_eq!(name("/odd key/a~1b"), "true");
+        assert_eq!(extract(&d, "").unwrap(), d);
+
+        let err = extract(&d, "data.items[5].name").unwrap_err();
+        assert_eq!(err, "no match for '[5]' in 'data.items[5].name': index 5 out of range for 3 items");
+        let err = extract(&d, "/data/items/0/color").unwrap_err();
+        assert!(err.contains("'/color'") && err.contains("no key 'color'"), "{}", err);
+        assert!(extract(&d, "data..id").unwrap_err().starts_with("invalid path"));
+    }
+
+    #[test]
+    fn test_wildcards_and_filters() {
+        let d = doc();
+        let q = |p: &str| extract(&d, p).unwrap().to_string();
+        assert_eq!(q("data.items[*].id"), "[1, 2, 3]");
+        assert_eq!(q("data.items[?(@.price < 3)].name"), "[bolt, nut]");
+        assert_eq!(q("data.items[?(@.price >= 12)].id"), "[3]");
+        assert_eq!(q("data.items[?(@.name == 'nut')].id"), "[2]");
+        assert_eq!(q("data.items[?(@.name != \"nut\")].id"), "[1, 3]");
+        assert_eq!(q("data.items[?(@.tags)].tags[0]"), "[metal]");
+        assert_eq!(q("data.items[0].*"), "[1, bolt, 2.5, [metal]]");
+        assert_eq!(extract(&d, "data.items[?(@.price > 100)]").unwrap(), RelType::Void);
+    }
+}
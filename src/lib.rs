@@ -1,14 +1,29 @@
 pub mod ast;
 pub mod async_bridge;
+pub mod bus;
 pub mod compiler;
+pub mod coverage;
+pub mod difftest;
 pub mod dsl_emitter;
+pub mod embed;
 pub mod evaluator;
 pub mod executor;
+pub mod hot_reload;
+pub mod interrupt;
+pub mod jsonpath;
 pub mod natives;
+pub mod net_policy;
 pub mod window;
 pub mod optimizer;
 pub mod parser;
+pub mod profiler;
+pub mod promise;
+pub mod repl;
+pub mod snapshot;
+pub mod tasks;
 pub mod test_lib;
+pub mod timers;
 pub mod validator;
 pub mod vm;
 pub mod math;
+pub mod numeric;
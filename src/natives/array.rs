@@ -0,0 +1,214 @@
+use crate::ast::Type;
+use crate::executor::{AgentPermissions, ExecResult, RelType};
+use crate::natives::convert::{arg, expect_args, native_fault, native_result, rel_type_name};
+use crate::natives::{NativeContext, NativeModule};
+use std::cmp::Ordering;
+
+/// Array functions, including higher-order ones taking a function value
+/// (`fn(x) { ... }` or a named function). A fault inside a callback aborts the
+/// call and is returned as-is.
+pub struct ArrayModule;
+
+/// Longest array `Array.Range` builds.
+const MAX_RANGE_LEN: u64 = 1 << 24;
+
+/// Parameter and return types, for the TypeChecker. Callbacks are `Any`.
+pub fn signature(name: &str) -> Option<(Vec<Type>, Type)> {
+    let arr = || Type::Array(vec![]);
+    let sig = match name {
+        "Array.Map" | "Array.Filter" | "Array.SortBy" => (vec![arr(), Type::Any], arr()),
+        "Array.Reduce" => (vec![arr(), Type::Any, Type::Any], Type::Any),
+        "Array.Find" => (vec![arr(), Type::Any], Type::Any),
+        "Array.Any" | "Array.All" => (vec![arr(), Type::Any], Type::Bool),
+        "Array.Zip" => (vec![arr(), arr()], arr()),
+        "Array.Slice" => (vec![arr(), Type::Int, Type::Int], arr()),
+        "Array.Range" => (vec![Type::Int, Type::Int], Type::Array(vec![Type::Int])),
+        _ => return None,
+    };
+    Some(sig)
+}
+
+fn items_and_callback(args: &[RelType], func: &str) -> Result<(Vec<RelType>, RelType), ExecResult> {
+    expect_args(args, &["items", "callback"], func)?;
+    Ok((arg::<Vec<RelType>>(args, 0, "items", func)?, args[1].clone()))
+}
+
+/// Runs a predicate callback, which must return a Bool.
+fn test(ctx: &mut NativeContext<'_>, func: &str, callback: &RelType, item: RelType) -> Result<bool, ExecResult> {
+    match ctx.call(callback, vec![item])? {
+        RelType::Bool(b) => Ok(b),
+        other => Err(native_fault(func, format!("callback returned {}, expected Bool", rel_type_name(&other)))),
+    }
+}
+
+/// Orders sort keys: numbers with numbers, strings with strings.
+fn compare_keys(a: &RelType, b: &RelType) -> Option<Ordering> {
+    match (a, b) {
+        (RelType::Int(x), RelType::Int(y)) => Some(x.cmp(y)),
+        (RelType::Str(x), RelType::Str(y)) => Some(x.cmp(y)),
+        (RelType::Int(x), RelType::Float(y)) => (*x as f64).partial_cmp(y),
+        (RelType::Float(x), RelType::Int(y)) => x.partial_cmp(&(*y as f64)),
+        (RelType::Float(x), RelType::Float(y)) => x.partial_cmp(y),
+        _ => None,
+    }
+}
+
+impl ArrayModule {
+    fn call(&self, f: &str, args: &[RelType]) -> Option<Result<RelType, ExecResult>> {
+        let res = match f {
+            "Array.Zip" => (|| {
+                expect_args(args, &["left", "right"], f)?;
+                let left = arg::<Vec<RelType>>(args, 0, "left", f)?;
+                let right = arg::<Vec<RelType>>(args, 1, "right", f)?;
+                Ok(RelType::Array(left.into_iter().zip(right).map(|(l, r)| RelType::Array(vec![l, r])).collect()))
+            })(),
+            "Array.Slice" => (|| {
+                expect_args(args, &["items", "start", "end"], f)?;
+                let items = arg::<Vec<RelType>>(args, 0, "items", f)?;
+                let start = arg::<usize>(args, 1, "start", f)?;
+                let end = arg::<usize>(args, 2, "end", f)?;
+                if start > end || end > items.len() {
+                    return Err(native_fault(f, format!("range {}..{} out of bounds for length {}", start, end, items.len())));
+                }
+                Ok(RelType::Array(items[start..end].to_vec()))
+            })(),
+            "Array.Range" => (|| {
+                expect_args(args, &["start", "end"], f)?;
+                let start = arg::<i64>(args, 0, "start", f)?;
+                let end = arg::<i64>(args, 1, "end", f)?;
+                let len = (end as i128 - start as i128).max(0);
+                if len > MAX_RANGE_LEN as i128 {
+                    return Err(native_fault(f, format!("range {}..{} has more than {} items", start, end, MAX_RANGE_LEN)));
+                }
+                Ok(RelType::Array((start..end).map(RelType::Int).collect()))
+            })(),
+            _ => return None,
+        };
+        Some(res)
+    }
+
+    fn call_with(&self, ctx: &mut NativeContext<'_>, f: &str, args: &[RelType]) -> Option<Result<RelType, ExecResult>> {
+        let res = match f {
+            "Array.Map" => items_and_callback(args, f).and_then(|(items, callback)| {
+                let mapped = items.into_iter().map(|item| ctx.call(&callback, vec![item])).collect::<Result<_, _>>()?;
+                Ok(RelType::Array(mapped))
+            }),
+            "Array.Filter" => items_and_callback(args, f).and_then(|(items, callback)| {
+                let mut kept = Vec::new();
+                for item in items {
+                    if test(ctx, f, &callback, item.clone())? {
+                        kept.push(item);
+                    }
+                }
+                Ok(RelType::Array(kept))
+            }),
+            "Array.Find" => items_and_callback(args, f).and_then(|(items, callback)| {
+                for item in items {
+                    if test(ctx, f, &callback, item.clone())? {
+                        return Ok(item);
+                    }
+                }
+                Ok(RelType::Void)
+            }),
+            "Array.Any" | "Array.All" => items_and_callback(args, f).and_then(|(items, callback)| {
+                // Stops at the first element that decides the answer.
+                let want = f == "Array.Any";
+                for item in items {
+                    if test(ctx, f, &callback, item)? == want {
+                        return Ok(RelType::Bool(want));
+                    }
+                }
+                Ok(RelType::Bool(!want))
+            }),
+            "Array.Reduce" => (|| {
+                expect_args(args, &["items", "callback", "initial"], f)?;
+                let items = arg::<Vec<RelType>>(args, 0, "items", f)?;
+                let mut acc = args[2].clone();
+                for item in items {
+                    acc = ctx.call(&args[1], vec![acc, item])?;
+                }
+                Ok(acc)
+            })(),
+            "Array.SortBy" => items_and_callback(args, f).and_then(|(items, callback)| {
+                // Each key is computed once; `sort_by` is stable.
+                let mut keyed = Vec::with_capacity(items.len());
+                for item in items {
+                    keyed.push((ctx.call(&callback, vec![item.clone()])?, item));
+                }
+                let mut bad = None;
+                keyed.sort_by(|(a, _), (b, _)| {
+                    compare_keys(a, b).unwrap_or_else(|| {
+                        bad.get_or_insert((rel_type_name(a), rel_type_name(b)));
+                        Ordering::Equal
+                    })
+                });
+                if let Some((a, b)) = bad {
+                    return Err(native_fault(f, format!("cannot compare sort keys of type {} and {}", a, b)));
+                }
+                Ok(RelType::Array(keyed.into_iter().map(|(_, item)| item).collect()))
+            }),
+            _ => return self.call(f, args),
+        };
+        Some(res)
+    }
+}
+
+impl NativeModule for ArrayModule {
+    fn handle(&self, func_name: &str, args: &[RelType], _permissions: &AgentPermissions) -> Option<ExecResult> {
+        self.call(func_name, args).map(native_result)
+    }
+
+    fn handle_in(&self, ctx: &mut NativeContext<'_>, func_name: &str, args: &[RelType]) -> Option<ExecResult> {
+        self.call_with(ctx, func_name, args).map(native_result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::executor::{ExecResult, ExecutionEngine, RelType};
+
+    fn run(src: &str) -> ExecResult {
+        ExecutionEngine::new().execute(&crate::parser::try_parse(src).unwrap())
+    }
+
+    fn ints(v: &[i64]) -> RelType {
+        RelType::Array(v.iter().copied().map(RelType::Int).collect())
+    }
+
+    #[test]
+    fn test_higher_order_pipeline() {
+        let src = "let xs = Array.Range(1, 7);\n\
+            let evens = Array.Filter(xs, fn(x) { return (x / 2) * 2 == x; });\n\
+            let squares = Array.Map(evens, fn(x) { return x * x; });\n\
+            let total = Array.Reduce(squares, fn(acc, x) { return acc + x; }, 0);\n\
+            [squares, total, Array.Find(xs, fn(x) { return x > 4; }), Array.Any(xs, fn(x) { return x > 5; }), Array.All(xs, fn(x) { return x > 1; })]";
+        let expected = RelType::Array(vec![ints(&[4, 16, 36]), RelType::Int(56), RelType::Int(5), RelType::Bool(true), RelType::Bool(false)]);
+        assert!(matches!(run(src), ExecResult::Value(v) if v == expected));
+
+        let src = "let pairs = Array.Zip([3, 1, 3, 2], [\"a\", \"b\", \"c\", \"d\"]);\n\
+            Array.Map(Array.SortBy(pairs, fn(p) { return p[0]; }), fn(p) { return p[1]; })";
+        let sorted = RelType::Array(["b", "d", "a", "c"].iter().map(|s| RelType::Str(s.to_string())).collect());
+        assert!(matches!(run(src), ExecResult::Value(v) if v == sorted));
+        assert!(matches!(run("Array.Slice([1, 2, 3, 4], 1, 3)"), ExecResult::Value(v) if v == ints(&[2, 3])));
+    }
+
+    #[test]
+    fn test_callback_faults_propagate() {
+        match run("fn check(x) { if (x == 2) { return 1 / 0; } return true; }\nArray.Filter([1, 2, 3], check)") {
+            ExecResult::Fault { node, .. } => assert_eq!(node, "Node::MathDiv"),
+            other => panic!("expected the callback's fault, got {}", other),
+        }
+        match run("Array.Filter([1], fn(x) { return x; })") {
+            ExecResult::Fault { msg, .. } => assert_eq!(msg, "Array.Filter: callback returned Int, expected Bool"),
+            other => panic!("expected a type fault, got {}", other),
+        }
+        match run("Array.SortBy([1, 2], fn(x) { if (x == 1) { return \"a\"; } return 2; })") {
+            ExecResult::Fault { msg, .. } => assert!(msg.starts_with("Array.SortBy: cannot compare sort keys"), "{}", msg),
+            other => panic!("expected a key fault, got {}", other),
+        }
+        match run("Array.Range(0, 9223372036854775807)") {
+            ExecResult::Fault { msg, .. } => assert_eq!(msg, "Array.Range: range 0..9223372036854775807 has more than 16777216 items"),
+            other => panic!("expected a size fault, got {}", other),
+        }
+    }
+}
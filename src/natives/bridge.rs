@@ -1,4 +1,21 @@
 use crate::executor::{ExecResult, RelType, AgentPermissions};
+use crate::natives::convert::{IntoRel, arg, expect_args};
+
+crate::rel_struct!(crate::test_lib::Vector3 { x: f64, y: f64, z: f64 });
+
+/// Checks arity, then runs a typed bridge call; faults use the `Native::Bridge::` node prefix.
+fn bridge_call(
+    args: &[RelType],
+    params: &[&str],
+    function: &str,
+    f: impl FnOnce(&[RelType]) -> Result<RelType, ExecResult>,
+) -> ExecResult {
+    match expect_args(args, params, function).and_then(|_| f(args)) {
+        Ok(v) => ExecResult::Value(v),
+        Err(ExecResult::Fault { msg, .. }) => ExecResult::Fault { msg, node: format!("Native::Bridge::{}", function) },
+        Err(other) => other,
+    }
+}
 
 pub trait BridgeModule: Send {
     fn handle(&self, module: &str, function: &str, args: &[RelType], permissions: &AgentPermissions) -> Option<ExecResult>;
@@ -10,121 +27,28 @@ impl BridgeModule for CoreBridge {
     fn handle(&self, module: &str, function: &str, args: &[RelType], permissions: &AgentPermissions) -> Option<ExecResult> {
         if module == "test_lib" {
             match function {
-                "calculate_hash" => {
-                    if args.len() == 1
-                        && let RelType::Str(data) = &args[0]
-                    {
-                        let result = crate::test_lib::calculate_hash(data.clone());
-                        return Some(ExecResult::Value(RelType::Int(result)));
-                    }
-                    Some(ExecResult::Fault {
-                        msg: "calculate_hash expects 1 String argument".to_string(),
-                        node: "Native::Bridge::calculate_hash".into()
-                    })
-                }
-                "greet_user" => {
-                    if args.len() == 1
-                        && let RelType::Str(name) = &args[0]
-                    {
-                        let result = crate::test_lib::greet_user(name.clone());
-                        return Some(ExecResult::Value(RelType::Str(result)));
-                    }
-                    Some(ExecResult::Fault {
-                        msg: "greet_user expects 1 String argument".to_string(),
-                        node: "Native::Bridge::greet_user".into()
-                    })
-                }
-                "normalize_vector" => {
-                    if args.len() == 1
-                        && let RelType::Object(map) = &args[0]
-                    {
-                        let x = if let Some(RelType::Float(v)) = map.get("x") {
-                            *v
-                        } else {
-                            return Some(ExecResult::Fault {
-                                msg: "[FFI Error] normalize_vector missing required float field 'x'"
-                                    .to_string(),
-                                node: "Native::Bridge::normalize_vector".into()
-                            });
-                        };
-                        let y = if let Some(RelType::Float(v)) = map.get("y") {
-                            *v
-                        } else {
-                            return Some(ExecResult::Fault {
-                                msg: "[FFI Error] normalize_vector missing required float field 'y'"
-                                    .to_string(),
-                                node: "Native::Bridge::normalize_vector".into()
-                            });
-                        };
-                        let z = if let Some(RelType::Float(v)) = map.get("z") {
-                            *v
-                        } else {
-                            return Some(ExecResult::Fault {
-                                msg: "[FFI Error] normalize_vector missing required float field 'z'"
-                                    .to_string(),
-                                node: "Native::Bridge::normalize_vector".into()
-                            });
-                        };
-
-                        let input_vec = crate::test_lib::Vector3 { x, y, z };
-                        let out_vec = crate::test_lib::normalize_vector(input_vec);
-
-                        let mut out_map = std::collections::HashMap::new();
-                        out_map.insert("x".to_string(), RelType::Float(out_vec.x));
-                        out_map.insert("y".to_string(), RelType::Float(out_vec.y));
-                        out_map.insert("z".to_string(), RelType::Float(out_vec.z));
-
-                        return Some(ExecResult::Value(RelType::Object(out_map)));
-                    }
-                    Some(ExecResult::Fault {
-                        msg: "normalize_vector expects 1 Vector3 Object argument".to_string(),
-                        node: "Native::Bridge::normalize_vector".into()
-                    })
-                }
+                "calculate_hash" => Some(bridge_call(args, &["data"], "calculate_hash", |args| {
+                    let data = arg::<String>(args, 0, "data", "calculate_hash")?;
+                    Ok(crate::test_lib::calculate_hash(data).into_rel())
+                })),
+                "greet_user" => Some(bridge_call(args, &["name"], "greet_user", |args| {
+                    let name = arg::<String>(args, 0, "name", "greet_user")?;
+                    Ok(crate::test_lib::greet_user(name).into_rel())
+                })),
+                "normalize_vector" => Some(bridge_call(args, &["v"], "normalize_vector", |args| {
+                    let v = arg::<crate::test_lib::Vector3>(args, 0, "v", "normalize_vector")?;
+                    Ok(crate::test_lib::normalize_vector(v).into_rel())
+                })),
                 _ => None,
             }
         } else if module == "ui" {
             match function {
-                "ui_init_window" => {
-                    if args.len() == 3 {
-                        let w = match &args[0] {
-                            RelType::Int(v) => *v,
-                            _ => {
-                                return Some(ExecResult::Fault {
-                                    msg: "[FFI] ui_init_window: arg 1 must be Int (width)".to_string(),
-                                    node: "Native::Bridge::ui_init_window".into()
-                                });
-                            }
-                        };
-                        let h = match &args[1] {
-                            RelType::Int(v) => *v,
-                            _ => {
-                                return Some(ExecResult::Fault {
-                                    msg: "[FFI] ui_init_window: arg 2 must be Int (height)".to_string(),
-                                    node: "Native::Bridge::ui_init_window".into()
-                                });
-                            }
-                        };
-                        let title = match &args[2] {
-                            RelType::Str(v) => v.clone(),
-                            _ => {
-                                return Some(ExecResult::Fault {
-                                    msg: "[FFI] ui_init_window: arg 3 must be String (title)"
-                                        .to_string(),
-                                    node: "Native::Bridge::ui_init_window".into()
-                                });
-                            }
-                        };
-                        let ok = crate::natives::ui::ui_init_window(w, h, title);
-                        Some(ExecResult::Value(RelType::Bool(ok)))
-                    } else {
-                        Some(ExecResult::Fault {
-                            msg: "[FFI] ui_init_window expects 3 args (width, height, title)"
-                                .to_string(),
-                            node: "Native::Bridge::ui_init_window".into()
-                        })
-                    }
-                }
+                "ui_init_window" => Some(bridge_call(args, &["width", "height", "title"], "ui_init_window", |args| {
+                    let w = arg::<i64>(args, 0, "width", "ui_init_window")?;
+                    let h = arg::<i64>(args, 1, "height", "ui_init_window")?;
+                    let title = arg::<String>(args, 2, "title", "ui_init_window")?;
+                    Ok(crate::natives::ui::ui_init_window(w, h, title).into_rel())
+                })),
                 "ui_clear" => {
                     if args.len() == 1 {
                         if let RelType::Int(c) = &args[0] {
@@ -285,8 +209,13 @@ impl BridgeModule for CoreBridge {
                 "fs_parse_json" => {
                     if args.len() == 1 {
                         if let RelType::Str(json_str) = &args[0] {
-                            let result = crate::natives::fs::fs_parse_json(json_str);
-                            return Some(ExecResult::Value(result));
+                            return Some(match crate::natives::fs::fs_parse_json(json_str) {
+                                Ok(result) => ExecResult::Value(result),
+                                Err(e) => ExecResult::Fault {
+                                    msg: format!("[FFI] fs_parse_json: {}", e),
+                                    node: "Native::Bridge::fs_parse_json".into()
+                                },
+                            });
                         }
                     }
                     Some(ExecResult::Fault {
@@ -754,6 +683,12 @@ impl BridgeModule for CoreBridge {
                     })
                 }
                 "registry_set_camera" => {
+                    if let [fov, RelType::Vec3(pos)] = args
+                        && let Ok(fov) = crate::natives::convert::FromRel::from_rel(fov)
+                    {
+                        crate::natives::registry::registry_set_camera(fov, pos.x, pos.y, pos.z);
+                        return Some(ExecResult::Value(RelType::Void));
+                    }
                     if args.len() == 4 {
                         let get_float = |arg: &RelType| -> Option<f32> {
                             match arg {
@@ -773,13 +708,19 @@ impl BridgeModule for CoreBridge {
                         }
                     }
                     Some(ExecResult::Fault {
-                        msg: "[FFI] registry_set_camera expects (Float fov, Float x, Float y, Float z)"
+                        msg: "[FFI] registry_set_camera expects (Float fov, Float x, Float y, Float z) or (Float fov, Vec3 pos)"
                             .to_string(),
                         node: "Native::Bridge::registry_set_camera".into()
                     })
                 }
-                // Sprint 86: window-specific camera — (Handle win, Float fov, Float x, Float y, Float z)
+                // Sprint 86: window-specific camera — (Handle win, Float fov, Float x, Float y, Float z) or a Vec3 position
                 "registry_set_camera_for_window" => {
+                    if let [RelType::Handle(crate::executor::NativeHandle(win_id)), fov, RelType::Vec3(pos)] = args
+                        && let Ok(fov) = crate::natives::convert::FromRel::from_rel(fov)
+                    {
+                        crate::natives::registry::registry_set_camera_for_window(*win_id, fov, pos.x, pos.y, pos.z);
+                        return Some(ExecResult::Value(RelType::Void));
+                    }
                     if args.len() == 5 {
                         let get_float = |arg: &RelType| -> Option<f32> {
                             match arg {
@@ -803,7 +744,7 @@ impl BridgeModule for CoreBridge {
                         }
                     }
                     Some(ExecResult::Fault {
-                        msg: "[FFI] registry_set_camera_for_window expects (Handle win, Float fov, Float x, Float y, Float z)"
+                        msg: "[FFI] registry_set_camera_for_window expects (Handle win, Float fov, Float x, Float y, Float z) or (Handle win, Float fov, Vec3 pos)"
                             .to_string(),
                         node: "Native::Bridge::registry_set_camera_for_window".into()
                     })
@@ -0,0 +1,249 @@
+use crate::ast::Type;
+use crate::executor::{AgentPermissions, ExecResult, RelType};
+use crate::natives::NativeModule;
+use crate::natives::convert::{arg, expect_args, native_fault, native_result, rel_type_name};
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+
+/// Functions over `Bytes` values: conversion, slicing, hex/base64 codecs and
+/// fixed-width integer packing.
+///
+/// Wherever a `Bytes` argument is expected, an array of Ints in `0..=255` is
+/// accepted too. Integer sizes are 1, 2, 4 or 8 bytes; byte order is `"le"`
+/// (little-endian) or `"be"` (big-endian).
+pub struct BytesModule;
+
+/// Parameter and return types, for the TypeChecker. `Bytes` has no static
+/// type of its own, so it appears as `Any`.
+pub fn signature(name: &str) -> Option<(Vec<Type>, Type)> {
+    use Type::{Any, Int, String as Str};
+    let sig = match name {
+        "Bytes.FromArray" => (vec![Type::Array(vec![Int])], Any),
+        "Bytes.ToArray" => (vec![Any], Type::Array(vec![Int])),
+        "Bytes.FromString" | "Bytes.FromHex" | "Bytes.FromBase64" => (vec![Str], Any),
+        "Bytes.ToString" | "Bytes.ToHex" | "Bytes.ToBase64" => (vec![Any], Str),
+        "Bytes.Length" => (vec![Any], Int),
+        "Bytes.Slice" => (vec![Any, Int, Int], Any),
+        "Bytes.Concat" => (vec![Any, Any], Any),
+        "Bytes.PackInt" => (vec![Int, Int, Str], Any),
+        "Bytes.UnpackInt" | "Bytes.UnpackSigned" => (vec![Any, Int, Int, Str], Int),
+        _ => return None,
+    };
+    Some(sig)
+}
+
+/// `Bytes`, or an array of byte-sized Ints, as raw bytes.
+pub fn to_bytes(v: &RelType) -> Result<Vec<u8>, String> {
+    match v {
+        RelType::Bytes(b) => Ok(b.clone()),
+        RelType::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| match item {
+                RelType::Int(x) => u8::try_from(*x).map_err(|_| format!("item {} ({}) is not a byte", i, x)),
+                other => Err(format!("item {} is {}, expected Int", i, rel_type_name(other))),
+            })
+            .collect(),
+        other => Err(format!("expected Bytes or an array of byte Ints, found {}", rel_type_name(other))),
+    }
+}
+
+fn bytes_arg(args: &[RelType], idx: usize, name: &str, func: &str) -> Result<Vec<u8>, ExecResult> {
+    to_bytes(&args[idx]).map_err(|e| native_fault(func, format!("{}: {}", name, e)))
+}
+
+fn hex_decode(s: &str) -> Result<Vec<u8>, String> {
+    let digits: Vec<u8> = s.bytes().filter(|c| !c.is_ascii_whitespace()).collect();
+    if !digits.len().is_multiple_of(2) {
+        return Err("odd number of hex digits".into());
+    }
+    digits
+        .chunks(2)
+        .map(|pair| {
+            let text = std::str::from_utf8(pair).map_err(|_| "invalid hex digit".to_string())?;
+            u8::from_str_radix(text, 16).map_err(|_| format!("invalid hex digits '{}'", text))
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Endian {
+    Little,
+    Big,
+}
+
+fn layout(size: i64, endian: &str) -> Result<(usize, Endian), String> {
+    let size = match size {
+        1 | 2 | 4 | 8 => size as usize,
+        _ => return Err(format!("size must be 1, 2, 4 or 8, got {}", size)),
+    };
+    let endian = match endian {
+        "le" | "little" => Endian::Little,
+        "be" | "big" => Endian::Big,
+        _ => return Err(format!("byte order must be \"le\" or \"be\", got \"{}\"", endian)),
+    };
+    Ok((size, endian))
+}
+
+/// Encodes `value` in `size` bytes. Any value that fits the signed or the
+/// unsigned range of that width is accepted (two's complement).
+fn pack_int(value: i64, size: usize, endian: Endian) -> Result<Vec<u8>, String> {
+    if size < 8 {
+        let bits = size as u32 * 8;
+        let (min, max) = (-(1i64 << (bits - 1)), (1i64 << bits) - 1);
+        if value < min || value > max {
+            return Err(format!("{} does not fit in {} byte(s)", value, size));
+        }
+    }
+    Ok(match endian {
+        Endian::Little => value.to_le_bytes()[..size].to_vec(),
+        Endian::Big => value.to_be_bytes()[8 - size..].to_vec(),
+    })
+}
+
+/// Reads `size` bytes at `offset`; 8-byte values are always signed.
+fn unpack_int(bytes: &[u8], offset: usize, size: usize, endian: Endian, signed: bool) -> Result<i64, String> {
+    let field = offset
+        .checked_add(size)
+        .and_then(|end| bytes.get(offset..end))
+        .ok_or_else(|| format!("{} byte(s) at offset {} exceed length {}", size, offset, bytes.len()))?;
+    let mut raw = 0u64;
+    for i in 0..size {
+        let b = if endian == Endian::Little { field[size - 1 - i] } else { field[i] };
+        raw = (raw << 8) | b as u64;
+    }
+    let shift = 64 - size as u32 * 8;
+    Ok(if signed { ((raw << shift) as i64) >> shift } else { raw as i64 })
+}
+
+impl BytesModule {
+    fn call(&self, func_name: &str, args: &[RelType]) -> Option<Result<RelType, ExecResult>> {
+        let f = func_name;
+        let res = match f {
+            "Bytes.FromArray" => expect_args(args, &["items"], f).and_then(|_| bytes_arg(args, 0, "items", f)).map(RelType::Bytes),
+            "Bytes.ToArray" => expect_args(args, &["bytes"], f)
+                .and_then(|_| bytes_arg(args, 0, "bytes", f))
+                .map(|b| RelType::Array(b.into_iter().map(|x| RelType::Int(x as i64)).collect())),
+            "Bytes.FromString" => expect_args(args, &["s"], f)
+                .and_then(|_| arg::<String>(args, 0, "s", f))
+                .map(|s| RelType::Bytes(s.into_bytes())),
+            "Bytes.ToString" => expect_args(args, &["bytes"], f).and_then(|_| bytes_arg(args, 0, "bytes", f)).and_then(|b| {
+                String::from_utf8(b).map(RelType::Str).map_err(|e| native_fault(f, format!("invalid UTF-8: {}", e.utf8_error())))
+            }),
+            "Bytes.Length" => expect_args(args, &["bytes"], f)
+                .and_then(|_| bytes_arg(args, 0, "bytes", f))
+                .map(|b| RelType::Int(b.len() as i64)),
+            "Bytes.Slice" => (|| {
+                expect_args(args, &["bytes", "start", "end"], f)?;
+                let b = bytes_arg(args, 0, "bytes", f)?;
+                let start = arg::<usize>(args, 1, "start", f)?;
+                let end = arg::<usize>(args, 2, "end", f)?;
+                if start > end || end > b.len() {
+                    return Err(native_fault(f, format!("range {}..{} out of bounds for length {}", start, end, b.len())));
+                }
+                Ok(RelType::Bytes(b[start..end].to_vec()))
+            })(),
+            "Bytes.Concat" => (|| {
+                expect_args(args, &["a", "b"], f)?;
+                let mut a = bytes_arg(args, 0, "a", f)?;
+                a.extend(bytes_arg(args, 1, "b", f)?);
+                Ok(RelType::Bytes(a))
+            })(),
+            "Bytes.ToHex" => expect_args(args, &["bytes"], f)
+                .and_then(|_| bytes_arg(args, 0, "bytes", f))
+                .map(|b| RelType::Str(b.iter().map(|x| format!("{:02x}", x)).collect())),
+            "Bytes.FromHex" => expect_args(args, &["hex"], f)
+                .and_then(|_| arg::<String>(args, 0, "hex", f))
+                .and_then(|s| hex_decode(&s).map(RelType::Bytes).map_err(|e| native_fault(f, e))),
+            "Bytes.ToBase64" => expect_args(args, &["bytes"], f)
+                .and_then(|_| bytes_arg(args, 0, "bytes", f))
+                .map(|b| RelType::Str(BASE64.encode(b))),
+            "Bytes.FromBase64" => expect_args(args, &["base64"], f)
+                .and_then(|_| arg::<String>(args, 0, "base64", f))
+                .and_then(|s| BASE64.decode(s.trim()).map(RelType::Bytes).map_err(|e| native_fault(f, e.to_string()))),
+            "Bytes.PackInt" => (|| {
+                expect_args(args, &["value", "size", "order"], f)?;
+                let value = arg::<i64>(args, 0, "value", f)?;
+                let (size, endian) = layout(arg::<i64>(args, 1, "size", f)?, &arg::<String>(args, 2, "order", f)?)
+                    .map_err(|e| native_fault(f, e))?;
+                pack_int(value, size, endian).map(RelType::Bytes).map_err(|e| native_fault(f, e))
+            })(),
+            "Bytes.UnpackInt" | "Bytes.UnpackSigned" => (|| {
+                expect_args(args, &["bytes", "offset", "size", "order"], f)?;
+                let b = bytes_arg(args, 0, "bytes", f)?;
+                let offset = arg::<usize>(args, 1, "offset", f)?;
+                let (size, endian) = layout(arg::<i64>(args, 2, "size", f)?, &arg::<String>(args, 3, "order", f)?)
+                    .map_err(|e| native_fault(f, e))?;
+                unpack_int(&b, offset, size, endian, f == "Bytes.UnpackSigned").map(RelType::Int).map_err(|e| native_fault(f, e))
+            })(),
+            _ => return None,
+        };
+        Some(res)
+    }
+}
+
+impl NativeModule for BytesModule {
+    fn handle(&self, func_name: &str, args: &[RelType], _permissions: &AgentPermissions) -> Option<ExecResult> {
+        self.call(func_name, args).map(native_result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(name: &str, args: Vec<RelType>) -> Result<RelType, ExecResult> {
+        BytesModule.call(name, &args).expect("unknown function")
+    }
+
+    fn s(v: &str) -> RelType {
+        RelType::Str(v.into())
+    }
+
+    fn bytes(v: &[u8]) -> RelType {
+        RelType::Bytes(v.to_vec())
+    }
+
+    #[test]
+    fn test_codecs_and_slicing() {
+        let png = bytes(&[0x89, b'P', b'N', b'G']);
+        assert_eq!(call("Bytes.ToHex", vec![png.clone()]).ok(), Some(s("89504e47")));
+        assert_eq!(call("Bytes.FromHex", vec![s("89 50 4E 47")]).ok(), Some(png.clone()));
+        assert_eq!(call("Bytes.ToBase64", vec![png.clone()]).ok(), Some(s("iVBORw==")));
+        assert_eq!(call("Bytes.FromBase64", vec![s("iVBORw==")]).ok(), Some(png.clone()));
+        assert_eq!(call("Bytes.Slice", vec![png.clone(), RelType::Int(1), RelType::Int(4)]).ok(), Some(bytes(b"PNG")));
+        assert_eq!(call("Bytes.ToString", vec![bytes(b"PNG")]).ok(), Some(s("PNG")));
+        assert_eq!(png.to_string(), "Bytes(89 50 4e 47)");
+        match call("Bytes.FromArray", vec![RelType::Array(vec![RelType::Int(1), RelType::Int(256)])]) {
+            Err(ExecResult::Fault { msg, .. }) => assert_eq!(msg, "Bytes.FromArray: items: item 1 (256) is not a byte"),
+            other => panic!("expected a fault, got {:?}", other.ok()),
+        }
+        match call("Bytes.ToString", vec![png]) {
+            Err(ExecResult::Fault { msg, .. }) => assert!(msg.starts_with("Bytes.ToString: invalid UTF-8"), "{}", msg),
+            other => panic!("expected a fault, got {:?}", other.ok()),
+        }
+    }
+
+    #[test]
+    fn test_pack_and_unpack() {
+        let pack = |v: i64, size: i64, order: &str| call("Bytes.PackInt", vec![RelType::Int(v), RelType::Int(size), s(order)]).ok();
+        assert_eq!(pack(0x0102, 2, "le"), Some(bytes(&[0x02, 0x01])));
+        assert_eq!(pack(0x0102, 4, "be"), Some(bytes(&[0, 0, 0x01, 0x02])));
+        assert_eq!(pack(-2, 2, "le"), Some(bytes(&[0xfe, 0xff])));
+        assert_eq!(pack(65535, 2, "be"), Some(bytes(&[0xff, 0xff])));
+        assert_eq!(pack(65536, 2, "be"), None);
+        assert_eq!(pack(1, 3, "be"), None);
+
+        // A WAV header field: 44100 as a little-endian u32 at offset 24
+        let mut header = vec![0u8; 24];
+        header.extend(44100u32.to_le_bytes());
+        let unpack = |name: &str, b: &[u8], offset: i64, size: i64, order: &str| {
+            call(name, vec![bytes(b), RelType::Int(offset), RelType::Int(size), s(order)]).ok()
+        };
+        assert_eq!(unpack("Bytes.UnpackInt", &header, 24, 4, "le"), Some(RelType::Int(44100)));
+        assert_eq!(unpack("Bytes.UnpackInt", &[0xfe, 0xff], 0, 2, "le"), Some(RelType::Int(65534)));
+        assert_eq!(unpack("Bytes.UnpackSigned", &[0xfe, 0xff], 0, 2, "le"), Some(RelType::Int(-2)));
+        assert_eq!(unpack("Bytes.UnpackSigned", &[0xff, 0xfe], 0, 2, "be"), Some(RelType::Int(-2)));
+        assert_eq!(unpack("Bytes.UnpackInt", &[1, 2, 3], 2, 2, "be"), None);
+    }
+}
@@ -0,0 +1,421 @@
+//! Typed conversions between Rust values and `RelType`.
+//!
+//! Natives use [`arg`] to pull typed parameters out of their argument slice; a
+//! failed conversion becomes a Fault that names the function and the parameter.
+
+use crate::executor::{ExecResult, RelType};
+use std::collections::HashMap;
+
+/// Why a `RelType` could not be converted. `path` locates the offending element
+/// inside nested arrays/objects (e.g. `[2].x`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConvertError {
+    pub path: String,
+    pub msg: String,
+}
+
+impl ConvertError {
+    pub fn new(msg: impl Into<String>) -> Self {
+        Self { path: String::new(), msg: msg.into() }
+    }
+
+    pub fn mismatch(expected: &str, found: &RelType) -> Self {
+        Self::new(format!("expected {}, found {}", expected, rel_type_name(found)))
+    }
+
+    /// Prefixes the path with an outer segment (`[i]` or `.key`).
+    pub fn within(mut self, segment: &str) -> Self {
+        self.path = format!("{}{}", segment, self.path);
+        self
+    }
+}
+
+impl std::fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let path = self.path.strip_prefix('.').unwrap_or(&self.path);
+        if path.is_empty() { write!(f, "{}", self.msg) } else { write!(f, "{}: {}", path, self.msg) }
+    }
+}
+
+pub fn rel_type_name(v: &RelType) -> &'static str {
+    match v {
+        RelType::Int(_) => "Int",
+        RelType::Float(_) => "Float",
+        RelType::Bool(_) => "Bool",
+        RelType::Str(_) => "String",
+        RelType::Array(_) => "Array",
+        RelType::Object(_) => "Object",
+        RelType::Handle(_) => "Handle",
+        RelType::FnDef(..) => "Function",
+        RelType::Call(..) => "Call",
+        RelType::Void => "Void",
+        RelType::Vec2(_) => "Vec2",
+        RelType::Vec3(_) => "Vec3",
+        RelType::Vec4(_) => "Vec4",
+        RelType::Quat(_) => "Quat",
+        RelType::Mat4(_) => "Mat4",
+        RelType::Bytes(_) => "Bytes",
+    }
+}
+
+pub trait FromRel: Sized {
+    fn from_rel(v: &RelType) -> Result<Self, ConvertError>;
+}
+
+pub trait IntoRel {
+    fn into_rel(self) -> RelType;
+}
+
+/// Converts `args[idx]` to `T`, or produces a Fault such as
+/// `Math.Perlin2D: argument 2 'y' expected Number, found String`.
+pub fn arg<T: FromRel>(args: &[RelType], idx: usize, param: &str, func: &str) -> Result<T, ExecResult> {
+    let Some(v) = args.get(idx) else {
+        return Err(native_fault(func, format!("missing argument {} '{}'", idx + 1, param)));
+    };
+    T::from_rel(v).map_err(|e| native_fault(func, format!("argument {} '{}' {}", idx + 1, param, e)))
+}
+
+/// Faults unless exactly `params.len()` arguments were passed.
+pub fn expect_args(args: &[RelType], params: &[&str], func: &str) -> Result<(), ExecResult> {
+    if args.len() == params.len() {
+        return Ok(());
+    }
+    Err(ExecResult::Fault {
+        msg: format!("{} expects {} argument(s) ({}), got {}", func, params.len(), params.join(", "), args.len()),
+        node: format!("Native::{}", func),
+    })
+}
+
+/// A Fault raised by the native `func`, reported as `"{func}: {msg}"`.
+pub fn native_fault(func: &str, msg: String) -> ExecResult {
+    ExecResult::Fault { msg: format!("{}: {}", func, msg), node: format!("Native::{}", func) }
+}
+
+/// A Fault for a native called without the capability it needs, e.g.
+/// `Permission Denied: IO.WriteFile requires FS_WRITE`.
+pub fn permission_denied(func: &str, capability: &str) -> ExecResult {
+    ExecResult::Fault {
+        msg: format!("Permission Denied: {} requires {}", func, capability),
+        node: format!("Native::{}", func),
+    }
+}
+
+/// Flattens a native's `Result` into the `ExecResult` the engine expects, so
+/// natives can be written with `?` and end in `.map(native_result)`.
+pub fn native_result(res: Result<RelType, ExecResult>) -> ExecResult {
+    res.map_or_else(|fault| fault, ExecResult::Value)
+}
+
+macro_rules! int_conversions {
+    ($($t:ty),*) => {$(
+        impl FromRel for $t {
+            fn from_rel(v: &RelType) -> Result<Self, ConvertError> {
+                match v {
+                    RelType::Int(i) => <$t>::try_from(*i)
+                        .map_err(|_| ConvertError::new(format!("integer {} out of range for {}", i, stringify!($t)))),
+                    other => Err(ConvertError::mismatch("Int", other)),
+                }
+            }
+        }
+        // Saturates at `i64::MAX`, which only `usize` can exceed.
+        impl IntoRel for $t {
+            fn into_rel(self) -> RelType {
+                RelType::Int(i64::try_from(self).unwrap_or(i64::MAX))
+            }
+        }
+    )*};
+}
+
+int_conversions!(i8, i16, i32, i64, u8, u16, u32, usize);
+
+impl FromRel for u64 {
+    fn from_rel(v: &RelType) -> Result<Self, ConvertError> {
+        match v {
+            RelType::Int(i) => u64::try_from(*i).map_err(|_| ConvertError::new(format!("integer {} out of range for u64", i))),
+            other => Err(ConvertError::mismatch("Int", other)),
+        }
+    }
+}
+
+// Floats accept integers as well; natives treat both as "Number".
+impl FromRel for f64 {
+    fn from_rel(v: &RelType) -> Result<Self, ConvertError> {
+        match v {
+            RelType::Float(f) => Ok(*f),
+            RelType::Int(i) => Ok(*i as f64),
+            other => Err(ConvertError::mismatch("Number", other)),
+        }
+    }
+}
+
+impl FromRel for f32 {
+    fn from_rel(v: &RelType) -> Result<Self, ConvertError> {
+        f64::from_rel(v).map(|f| f as f32)
+    }
+}
+
+impl IntoRel for f64 {
+    fn into_rel(self) -> RelType {
+        RelType::Float(self)
+    }
+}
+
+impl IntoRel for f32 {
+    fn into_rel(self) -> RelType {
+        RelType::Float(self as f64)
+    }
+}
+
+impl FromRel for bool {
+    fn from_rel(v: &RelType) -> Result<Self, ConvertError> {
+        match v {
+            RelType::Bool(b) => Ok(*b),
+            other => Err(ConvertError::mismatch("Bool", other)),
+        }
+    }
+}
+
+impl IntoRel for bool {
+    fn into_rel(self) -> RelType {
+        RelType::Bool(self)
+    }
+}
+
+impl FromRel for String {
+    fn from_rel(v: &RelType) -> Result<Self, ConvertError> {
+        match v {
+            RelType::Str(s) => Ok(s.clone()),
+            other => Err(ConvertError::mismatch("String", other)),
+        }
+    }
+}
+
+impl IntoRel for String {
+    fn into_rel(self) -> RelType {
+        RelType::Str(self)
+    }
+}
+
+impl IntoRel for &str {
+    fn into_rel(self) -> RelType {
+        RelType::Str(self.to_string())
+    }
+}
+
+impl FromRel for RelType {
+    fn from_rel(v: &RelType) -> Result<Self, ConvertError> {
+        Ok(v.clone())
+    }
+}
+
+impl IntoRel for RelType {
+    fn into_rel(self) -> RelType {
+        self
+    }
+}
+
+impl IntoRel for () {
+    fn into_rel(self) -> RelType {
+        RelType::Void
+    }
+}
+
+impl<T: FromRel> FromRel for Vec<T> {
+    fn from_rel(v: &RelType) -> Result<Self, ConvertError> {
+        match v {
+            RelType::Array(items) => items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| T::from_rel(item).map_err(|e| e.within(&format!("[{}]", i))))
+                .collect(),
+            other => Err(ConvertError::mismatch("Array", other)),
+        }
+    }
+}
+
+impl<T: IntoRel> IntoRel for Vec<T> {
+    fn into_rel(self) -> RelType {
+        RelType::Array(self.into_iter().map(IntoRel::into_rel).collect())
+    }
+}
+
+impl<T: FromRel> FromRel for HashMap<String, T> {
+    fn from_rel(v: &RelType) -> Result<Self, ConvertError> {
+        match v {
+            RelType::Object(map) => map
+                .iter()
+                .map(|(k, item)| T::from_rel(item).map(|t| (k.clone(), t)).map_err(|e| e.within(&format!(".{}", k))))
+                .collect(),
+            other => Err(ConvertError::mismatch("Object", other)),
+        }
+    }
+}
+
+impl<T: IntoRel> IntoRel for HashMap<String, T> {
+    fn into_rel(self) -> RelType {
+        RelType::Object(self.into_iter().map(|(k, v)| (k, v.into_rel())).collect())
+    }
+}
+
+/// `Void` maps to `None`.
+impl<T: FromRel> FromRel for Option<T> {
+    fn from_rel(v: &RelType) -> Result<Self, ConvertError> {
+        match v {
+            RelType::Void => Ok(None),
+            other => T::from_rel(other).map(Some),
+        }
+    }
+}
+
+impl<T: IntoRel> IntoRel for Option<T> {
+    fn into_rel(self) -> RelType {
+        self.map_or(RelType::Void, IntoRel::into_rel)
+    }
+}
+
+macro_rules! tuple_conversions {
+    ($len:expr; $($name:ident $idx:tt),+) => {
+        /// Tuples map to fixed-length arrays.
+        impl<$($name: FromRel),+> FromRel for ($($name,)+) {
+            fn from_rel(v: &RelType) -> Result<Self, ConvertError> {
+                match v {
+                    RelType::Array(items) if items.len() == $len => Ok(($(
+                        $name::from_rel(&items[$idx]).map_err(|e| e.within(concat!("[", $idx, "]")))?,
+                    )+)),
+                    RelType::Array(items) => Err(ConvertError::new(format!(
+                        "expected Array of length {}, found length {}", $len, items.len()
+                    ))),
+                    other => Err(ConvertError::mismatch("Array", other)),
+                }
+            }
+        }
+        impl<$($name: IntoRel),+> IntoRel for ($($name,)+) {
+            fn into_rel(self) -> RelType {
+                RelType::Array(vec![$(self.$idx.into_rel()),+])
+            }
+        }
+    };
+}
+
+tuple_conversions!(1; A 0);
+tuple_conversions!(2; A 0, B 1);
+tuple_conversions!(3; A 0, B 1, C 2);
+tuple_conversions!(4; A 0, B 1, C 2, D 3);
+
+macro_rules! glam_conversions {
+    ($($t:ident $len:literal $from:path),*) => {$(
+        /// Also accepts an array of numbers (column-major for `Mat4`).
+        impl FromRel for glam::$t {
+            fn from_rel(v: &RelType) -> Result<Self, ConvertError> {
+                match v {
+                    RelType::$t(x) => Ok(*x),
+                    RelType::Array(items) if items.len() == $len => Vec::<f32>::from_rel(v).map(|c| $from(&c)),
+                    other => Err(ConvertError::mismatch(concat!(stringify!($t), " or Array of ", $len, " Numbers"), other)),
+                }
+            }
+        }
+        impl IntoRel for glam::$t {
+            fn into_rel(self) -> RelType {
+                RelType::$t(self)
+            }
+        }
+    )*};
+}
+
+glam_conversions!(
+    Vec2 2 glam::Vec2::from_slice,
+    Vec3 3 glam::Vec3::from_slice,
+    Vec4 4 glam::Vec4::from_slice,
+    Quat 4 glam::Quat::from_slice,
+    Mat4 16 glam::Mat4::from_cols_slice
+);
+
+/// Maps a plain struct to and from `RelType::Object`, one key per field, and
+/// declares it as `Object` for host function signatures.
+///
+/// ```ignore
+/// rel_struct!(crate::test_lib::Vector3 { x: f64, y: f64, z: f64 });
+/// ```
+#[macro_export]
+macro_rules! rel_struct {
+    ($ty:path { $($field:ident : $fty:ty),* $(,)? }) => {
+        impl $crate::natives::convert::FromRel for $ty {
+            fn from_rel(
+                v: &$crate::executor::RelType,
+            ) -> Result<Self, $crate::natives::convert::ConvertError> {
+                use $crate::natives::convert::{ConvertError, FromRel};
+                match v {
+                    $crate::executor::RelType::Object(map) => Ok(Self {$(
+                        $field: match map.get(stringify!($field)) {
+                            Some(val) => <$fty as FromRel>::from_rel(val)
+                                .map_err(|e| e.within(concat!(".", stringify!($field))))?,
+                            None => return Err(ConvertError::new(concat!("missing field '", stringify!($field), "'"))),
+                        },
+                    )*}),
+                    other => Err(ConvertError::mismatch("Object", other)),
+                }
+            }
+        }
+        impl $crate::natives::convert::IntoRel for $ty {
+            fn into_rel(self) -> $crate::executor::RelType {
+                let mut map = std::collections::HashMap::new();
+                $(map.insert(
+                    stringify!($field).to_string(),
+                    $crate::natives::convert::IntoRel::into_rel(self.$field),
+                );)*
+                $crate::executor::RelType::Object(map)
+            }
+        }
+        impl $crate::embed::HostType for $ty {
+            fn host_type() -> $crate::ast::Type {
+                $crate::ast::Type::Object
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Item {
+        name: String,
+        count: u32,
+        tags: Vec<String>,
+    }
+    rel_struct!(Item { name: String, count: u32, tags: Vec<String> });
+
+    #[test]
+    fn test_roundtrip_and_nested_errors() {
+        let item = Item { name: "gem".into(), count: 3, tags: vec!["rare".into()] };
+        let rel = item.into_rel();
+        let back = Item::from_rel(&rel).unwrap();
+        assert_eq!((back.name.as_str(), back.count, back.tags.len()), ("gem", 3, 1));
+
+        let pairs: Vec<(i64, Option<f64>)> = vec![(1, Some(2.5)), (2, None)];
+        let rel = pairs.clone().into_rel();
+        assert_eq!(Vec::<(i64, Option<f64>)>::from_rel(&rel).unwrap(), pairs);
+        assert_eq!(usize::MAX.into_rel(), RelType::Int(i64::MAX));
+
+        let bad = RelType::Array(vec![RelType::Object(HashMap::from([
+            ("name".to_string(), RelType::Str("x".into())),
+            ("count".to_string(), RelType::Int(-1)),
+            ("tags".to_string(), RelType::Array(vec![])),
+        ]))]);
+        let err = Vec::<Item>::from_rel(&bad).err().unwrap();
+        assert_eq!(err.to_string(), "[0].count: integer -1 out of range for u32");
+    }
+
+    #[test]
+    fn test_arg_faults_name_the_parameter() {
+        let args = [RelType::Float(1.0), RelType::Str("no".into())];
+        assert_eq!(arg::<f64>(&args, 0, "x", "Math.Perlin2D").ok(), Some(1.0));
+        match arg::<f64>(&args, 1, "y", "Math.Perlin2D") {
+            Err(ExecResult::Fault { msg, node }) => {
+                assert_eq!(msg, "Math.Perlin2D: argument 2 'y' expected Number, found String");
+                assert_eq!(node, "Native::Math.Perlin2D");
+            }
+            _ => panic!("expected a fault"),
+        }
+    }
+}
@@ -0,0 +1,336 @@
+use crate::ast::Type;
+use crate::executor::{AgentPermissions, ExecResult, ExecutionEngine, RelType};
+use crate::natives::NativeModule;
+use crate::natives::convert::{arg, expect_args, native_fault, native_result, permission_denied, rel_type_name};
+use std::collections::{BTreeSet, HashMap};
+
+/// RFC 4180 CSV: parsing into header-keyed objects and writing them back.
+///
+/// Each column gets one type, inferred from its non-empty cells: Int if all
+/// are integers, Float if all are numbers, Bool if all are `true`/`false`,
+/// otherwise String. Empty cells in a typed column become Void. Numbers with a
+/// leading zero (`007`) are left as strings, since they are usually codes.
+///
+/// Writing takes the column order from the `columns` argument, or uses the
+/// sorted union of all row keys when it is empty, so output is deterministic.
+pub struct CsvModule;
+
+
+pub fn signature(name: &str) -> Option<(Vec<Type>, Type)> {
+    let rows = || Type::Array(vec![Type::Object]);
+    let columns = || Type::Array(vec![Type::String]);
+    let sig = match name {
+        "CSV.Parse" => (vec![Type::String], rows()),
+        "CSV.ReadFile" => (vec![Type::String], rows()),
+        "CSV.Stringify" => (vec![rows(), columns()], Type::String),
+        "CSV.WriteFile" => (vec![Type::String, rows(), columns()], Type::Int),
+        _ => return None,
+    };
+    Some(sig)
+}
+
+/// Splits CSV text into records of raw fields. Quoted fields may contain
+/// commas, newlines and doubled quotes; blank lines are skipped.
+fn records(text: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut out = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut chars = text.strip_prefix('\u{feff}').unwrap_or(text).chars().peekable();
+    let (mut line, mut quote_line) = (1, 1);
+    // Whether the current field was quoted, and whether we are inside the quotes.
+    let (mut quoted, mut in_quotes) = (false, false);
+
+    let end_record = |record: &mut Vec<String>, field: &mut String, out: &mut Vec<Vec<String>>, quoted: bool| {
+        record.push(std::mem::take(field));
+        let blank = record.len() == 1 && record[0].is_empty() && !quoted;
+        let record = std::mem::take(record);
+        if !blank {
+            out.push(record);
+        }
+    };
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                '\n' => {
+                    line += 1;
+                    field.push(c);
+                }
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() && !quoted => {
+                quoted = true;
+                in_quotes = true;
+                quote_line = line;
+            }
+            '"' => return Err(format!("line {}: unexpected quote in field", line)),
+            ',' => {
+                record.push(std::mem::take(&mut field));
+                quoted = false;
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                end_record(&mut record, &mut field, &mut out, quoted);
+                quoted = false;
+                line += 1;
+            }
+            _ if quoted => return Err(format!("line {}: text after closing quote", line)),
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(format!("line {}: unterminated quoted field", quote_line));
+    }
+    if !record.is_empty() || !field.is_empty() || quoted {
+        end_record(&mut record, &mut field, &mut out, quoted);
+    }
+    Ok(out)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Column {
+    Int,
+    Float,
+    Bool,
+    Str,
+}
+
+fn is_number(cell: &str, allow_fraction: bool) -> bool {
+    let digits = cell.strip_prefix(['-', '+']).unwrap_or(cell);
+    let leading_zero = digits.len() > 1 && digits.starts_with('0') && digits.as_bytes()[1].is_ascii_digit();
+    if leading_zero || !digits.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+        return false;
+    }
+    if allow_fraction {
+        digits.chars().all(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '+' | '-')) && cell.parse::<f64>().is_ok()
+    } else {
+        digits.chars().all(|c| c.is_ascii_digit()) && cell.parse::<i64>().is_ok()
+    }
+}
+
+fn cell_kind(cell: &str) -> Column {
+    if is_number(cell, false) {
+        Column::Int
+    } else if is_number(cell, true) {
+        Column::Float
+    } else if cell.eq_ignore_ascii_case("true") || cell.eq_ignore_ascii_case("false") {
+        Column::Bool
+    } else {
+        Column::Str
+    }
+}
+
+/// The narrowest type that holds every non-empty cell of a column.
+fn infer(cells: &[&str]) -> Column {
+    let mut kind: Option<Column> = None;
+    for cell in cells.iter().filter(|c| !c.is_empty()) {
+        let next = cell_kind(cell);
+        kind = Some(match kind {
+            None => next,
+            Some(k) if k == next => k,
+            Some(Column::Int | Column::Float) if matches!(next, Column::Int | Column::Float) => Column::Float,
+            Some(_) => Column::Str,
+        });
+        if kind == Some(Column::Str) {
+            break;
+        }
+    }
+    kind.unwrap_or(Column::Str)
+}
+
+fn typed(cell: String, kind: Column) -> RelType {
+    match kind {
+        Column::Str => RelType::Str(cell),
+        _ if cell.is_empty() => RelType::Void,
+        Column::Int => RelType::Int(cell.parse().unwrap_or_default()),
+        Column::Float => RelType::Float(cell.parse().unwrap_or_default()),
+        Column::Bool => RelType::Bool(cell.eq_ignore_ascii_case("true")),
+    }
+}
+
+/// Parses CSV text with a header row into an array of objects.
+pub fn parse(text: &str) -> Result<RelType, String> {
+    let mut rows = records(text)?.into_iter();
+    let Some(header) = rows.next() else { return Ok(RelType::Array(Vec::new())) };
+    let mut seen = BTreeSet::new();
+    for name in &header {
+        if !seen.insert(name) {
+            return Err(format!("duplicate column '{}'", name));
+        }
+    }
+    let rows: Vec<Vec<String>> = rows.collect();
+    for (i, row) in rows.iter().enumerate() {
+        if row.len() != header.len() {
+            return Err(format!("record {} has {} fields, header has {}", i + 1, row.len(), header.len()));
+        }
+    }
+    let kinds: Vec<Column> =
+        (0..header.len()).map(|col| infer(&rows.iter().map(|r| r[col].as_str()).collect::<Vec<_>>())).collect();
+    Ok(RelType::Array(
+        rows.into_iter()
+            .map(|row| {
+                let fields = header.iter().cloned().zip(row.into_iter().zip(&kinds).map(|(cell, k)| typed(cell, *k)));
+                RelType::Object(fields.collect())
+            })
+            .collect(),
+    ))
+}
+
+fn cell_text(value: &RelType) -> Result<String, String> {
+    Ok(match value {
+        RelType::Void => String::new(),
+        RelType::Str(s) => s.clone(),
+        RelType::Int(_) | RelType::Float(_) | RelType::Bool(_) => value.to_string(),
+        other => return Err(format!("{} cannot be written to a CSV cell", rel_type_name(other))),
+    })
+}
+
+fn quote(cell: &str) -> String {
+    let needs_quotes = cell.contains([',', '"', '\n', '\r']) || cell.starts_with(' ') || cell.ends_with(' ');
+    if needs_quotes { format!("\"{}\"", cell.replace('"', "\"\"")) } else { cell.to_string() }
+}
+
+/// Writes objects as CSV with a header row. Keys missing from a row are left
+/// empty; keys not in `columns` are an error rather than silently dropped.
+pub fn stringify(rows: &[RelType], columns: &[String]) -> Result<String, String> {
+    let objects = rows
+        .iter()
+        .enumerate()
+        .map(|(i, row)| match row {
+            RelType::Object(map) => Ok(map),
+            other => Err(format!("row {} is {}, expected Object", i, rel_type_name(other))),
+        })
+        .collect::<Result<Vec<&HashMap<String, RelType>>, String>>()?;
+    let columns: Vec<String> = if columns.is_empty() {
+        objects.iter().flat_map(|m| m.keys()).collect::<BTreeSet<_>>().into_iter().cloned().collect()
+    } else {
+        for (i, map) in objects.iter().enumerate() {
+            if let Some(key) = map.keys().find(|k| !columns.contains(k)) {
+                return Err(format!("row {} has column '{}' not in the column list", i, key));
+            }
+        }
+        columns.to_vec()
+    };
+
+    let mut out = columns.iter().map(|c| quote(c)).collect::<Vec<_>>().join(",");
+    out.push('\n');
+    for (i, map) in objects.iter().enumerate() {
+        let cells = columns
+            .iter()
+            .map(|c| map.get(c).map_or(Ok(String::new()), cell_text).map(|s| quote(&s)))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("row {}: {}", i, e))?;
+        out.push_str(&cells.join(","));
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+impl CsvModule {
+    fn call(&self, func_name: &str, args: &[RelType], permissions: &AgentPermissions) -> Option<Result<RelType, ExecResult>> {
+        let f = func_name;
+        let res = match f {
+            "CSV.Parse" => (|| {
+                expect_args(args, &["text"], f)?;
+                parse(&arg::<String>(args, 0, "text", f)?).map_err(|e| native_fault(f, e))
+            })(),
+            "CSV.ReadFile" => (|| {
+                if !permissions.allow_fs_read {
+                    return Err(permission_denied(f, "FS_READ"));
+                }
+                expect_args(args, &["path"], f)?;
+                let path = arg::<String>(args, 0, "path", f)?;
+                let safe_path = ExecutionEngine::validate_fs_path(&path).map_err(|e| native_fault(f, format!("Security: {}", e)))?;
+                let text = std::fs::read_to_string(&safe_path).map_err(|e| native_fault(f, format!("File read error: {}", e)))?;
+                parse(&text).map_err(|e| native_fault(f, format!("{}: {}", path, e)))
+            })(),
+            "CSV.Stringify" => (|| {
+                expect_args(args, &["rows", "columns"], f)?;
+                let rows = arg::<Vec<RelType>>(args, 0, "rows", f)?;
+                let columns = arg::<Vec<String>>(args, 1, "columns", f)?;
+                stringify(&rows, &columns).map(RelType::Str).map_err(|e| native_fault(f, e))
+            })(),
+            "CSV.WriteFile" => (|| {
+                if !permissions.allow_fs_write {
+                    return Err(permission_denied(f, "FS_WRITE"));
+                }
+                expect_args(args, &["path", "rows", "columns"], f)?;
+                let path = arg::<String>(args, 0, "path", f)?;
+                let rows = arg::<Vec<RelType>>(args, 1, "rows", f)?;
+                let columns = arg::<Vec<String>>(args, 2, "columns", f)?;
+                let text = stringify(&rows, &columns).map_err(|e| native_fault(f, e))?;
+                let safe_path =
+                    ExecutionEngine::validate_fs_path_write(&path).map_err(|e| native_fault(f, format!("Security: {}", e)))?;
+                std::fs::write(&safe_path, text).map_err(|e| native_fault(f, format!("File write error: {}", e)))?;
+                Ok(RelType::Int(rows.len() as i64))
+            })(),
+            _ => return None,
+        };
+        Some(res)
+    }
+}
+
+impl NativeModule for CsvModule {
+    fn handle(&self, func_name: &str, args: &[RelType], permissions: &AgentPermissions) -> Option<ExecResult> {
+        self.call(func_name, args, permissions).map(native_result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn s(v: &str) -> RelType {
+        RelType::Str(v.into())
+    }
+
+    #[test]
+    fn test_parse_infers_column_types() {
+        let text = "id,price,ok,zip,note\r\n1,2,true,007,\"a, \"\"quoted\"\"\nline\"\n2,2.5,FALSE,12345,\n\n3,,true,00501,plain\n";
+        let RelType::Array(rows) = parse(text).unwrap() else { panic!("expected an array") };
+        assert_eq!(rows.len(), 3);
+        let row = |i: usize, key: &str| match &rows[i] {
+            RelType::Object(m) => m[key].clone(),
+            other => panic!("expected an object, got {}", other),
+        };
+        assert_eq!(row(0, "id"), RelType::Int(1));
+        assert_eq!(row(0, "price"), RelType::Float(2.0));
+        assert_eq!(row(1, "price"), RelType::Float(2.5));
+        assert_eq!(row(2, "price"), RelType::Void);
+        assert_eq!(row(1, "ok"), RelType::Bool(false));
+        assert_eq!(row(0, "zip"), s("007"));
+        assert_eq!(row(0, "note"), s("a, \"quoted\"\nline"));
+        assert_eq!(row(1, "note"), s(""));
+
+        assert_eq!(parse("a,b\n1\n").err().unwrap(), "record 1 has 1 fields, header has 2");
+        assert_eq!(parse("a,a\n").err().unwrap(), "duplicate column 'a'");
+        assert_eq!(parse("a\n\"open\n").err().unwrap(), "line 2: unterminated quoted field");
+        assert_eq!(parse("a\nx\"y\n").err().unwrap(), "line 2: unexpected quote in field");
+    }
+
+    #[test]
+    fn test_stringify_round_trips() {
+        let row = |pairs: &[(&str, RelType)]| RelType::Object(pairs.iter().map(|(k, v)| (k.to_string(), v.clone())).collect());
+        let rows = vec![
+            row(&[("name", s("Doe, Jane")), ("age", RelType::Int(41)), ("score", RelType::Float(3.0))]),
+            row(&[("name", s("say \"hi\"")), ("age", RelType::Int(7))]),
+        ];
+        let text = stringify(&rows, &[]).unwrap();
+        assert_eq!(text, "age,name,score\n41,\"Doe, Jane\",3.0\n7,\"say \"\"hi\"\"\",\n");
+        let ordered = stringify(&rows, &["name".into(), "score".into(), "age".into()]).unwrap();
+        assert!(ordered.starts_with("name,score,age\n\"Doe, Jane\",3.0,41\n"), "{}", ordered);
+        assert_eq!(parse(&text).unwrap(), RelType::Array(vec![
+            row(&[("name", s("Doe, Jane")), ("age", RelType::Int(41)), ("score", RelType::Float(3.0))]),
+            row(&[("name", s("say \"hi\"")), ("age", RelType::Int(7)), ("score", RelType::Void)]),
+        ]));
+        assert_eq!(stringify(&rows, &["name".into(), "score".into()]).err().unwrap(), "row 0 has column 'age' not in the column list");
+    }
+}
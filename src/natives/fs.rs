@@ -22,17 +22,15 @@ pub fn fs_read_file(path: String) -> String {
 /// - JSON Number → RelType::Int or RelType::Float
 /// - JSON Bool → RelType::Bool
 /// - JSON Null → RelType::Void
-pub fn fs_parse_json(json_str: &str) -> RelType {
-    match serde_json::from_str::<serde_json::Value>(json_str) {
-        Ok(value) => json_value_to_reltype(&value),
-        Err(e) => {
-            eprintln!("[KnotenCore FS] JSON parse error: {}", e);
-            RelType::Void
-        }
-    }
+///
+/// Malformed input is an error carrying serde_json's message and position.
+pub fn fs_parse_json(json_str: &str) -> Result<RelType, String> {
+    serde_json::from_str::<serde_json::Value>(json_str)
+        .map(|value| json_value_to_reltype(&value))
+        .map_err(|e| e.to_string())
 }
 
-fn json_value_to_reltype(value: &serde_json::Value) -> RelType {
+pub(crate) fn json_value_to_reltype(value: &serde_json::Value) -> RelType {
     match value {
         serde_json::Value::Null => RelType::Void,
         serde_json::Value::Bool(b) => RelType::Bool(*b),
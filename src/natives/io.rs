@@ -1,120 +1,60 @@
 use crate::executor::{ExecResult, RelType, AgentPermissions};
 use crate::natives::NativeModule;
+use crate::natives::convert::{arg, expect_args, native_result, permission_denied};
 
 pub struct IoModule;
 
-impl NativeModule for IoModule {
-    fn handle(&self, func_name: &str, args: &[RelType], permissions: &AgentPermissions) -> Option<ExecResult> {
-        match func_name {
-            "IO.WriteFile" => {
+impl IoModule {
+    fn call(&self, func_name: &str, args: &[RelType], permissions: &AgentPermissions) -> Option<Result<RelType, ExecResult>> {
+        let res = match func_name {
+            "IO.WriteFile" => (|| {
                 if !permissions.allow_fs_write {
-                    return Some(ExecResult::Fault { 
-                        msg: "Permission Denied: IO.WriteFile requires FS_WRITE".to_string(), 
-                        node: "Native::IO.WriteFile".into() 
-                    });
-                }
-                if args.len() != 2 {
-                    return Some(ExecResult::Fault {
-                        msg: "IO.WriteFile expects 2 arguments (path, content)".to_string(),
-                        node: "Native::IO.WriteFile".into()
-                    });
-                }
-                if let (RelType::Str(path), RelType::Str(content)) = (&args[0], &args[1]) {
-                    match std::fs::write(path, content) {
-                        Ok(_) => Some(ExecResult::Value(RelType::Bool(true))),
-                        Err(_) => Some(ExecResult::Value(RelType::Bool(false))),
-                    }
-                } else {
-                    Some(ExecResult::Fault {
-                        msg: "IO.WriteFile expects (String, String)".to_string(),
-                        node: "Native::IO.WriteFile".into()
-                    })
-                }
-            }
-            "IO.ReadFile" => {
+                    return Err(permission_denied(func_name, "FS_WRITE"));
+                }
+                expect_args(args, &["path", "content"], func_name)?;
+                let path = arg::<String>(args, 0, "path", func_name)?;
+                let content = arg::<String>(args, 1, "content", func_name)?;
+                Ok(RelType::Bool(std::fs::write(path, content).is_ok()))
+            })(),
+            "IO.ReadFile" => (|| {
                 if !permissions.allow_fs_read {
-                    return Some(ExecResult::Fault { 
-                        msg: "Permission Denied: IO.ReadFile requires FS_READ".to_string(), 
-                        node: "Native::IO.ReadFile".into() 
-                    });
+                    return Err(permission_denied(func_name, "FS_READ"));
                 }
-                if args.len() != 1 {
-                    return Some(ExecResult::Fault {
-                        msg: "IO.ReadFile expects 1 argument (path)".to_string(),
-                        node: "Native::IO.ReadFile".into()
-                    });
-                }
-                if let RelType::Str(path) = &args[0] {
-                    match std::fs::read_to_string(path) {
-                        Ok(content) => Some(ExecResult::Value(RelType::Str(content))),
-                        Err(_) => Some(ExecResult::Value(RelType::Str("".to_string()))),
-                    }
-                } else {
-                    Some(ExecResult::Fault {
-                        msg: "IO.ReadFile expects a String".to_string(),
-                        node: "Native::IO.ReadFile".into()
-                    })
-                }
-            }
-            "IO.AppendFile" => {
+                expect_args(args, &["path"], func_name)?;
+                let path = arg::<String>(args, 0, "path", func_name)?;
+                Ok(RelType::Str(std::fs::read_to_string(path).unwrap_or_default()))
+            })(),
+            "IO.AppendFile" => (|| {
                 if !permissions.allow_fs_write {
-                    return Some(ExecResult::Fault { 
-                        msg: "Permission Denied: IO.AppendFile requires FS_WRITE".to_string(), 
-                        node: "Native::IO.AppendFile".into() 
-                    });
-                }
-                if args.len() != 2 {
-                    return Some(ExecResult::Fault {
-                        msg: "IO.AppendFile expects 2 arguments (path, content)".to_string(),
-                        node: "Native::IO.AppendFile".into()
-                    });
-                }
-                if let (RelType::Str(path), RelType::Str(content)) = (&args[0], &args[1]) {
-                    use std::io::Write;
-                    let mut file = match std::fs::OpenOptions::new()
-                        .append(true)
-                        .create(true)
-                        .open(path)
-                    {
-                        Ok(f) => f,
-                        Err(_) => return Some(ExecResult::Value(RelType::Bool(false))),
-                    };
-                    match write!(file, "{}", content) {
-                        Ok(_) => Some(ExecResult::Value(RelType::Bool(true))),
-                        Err(_) => Some(ExecResult::Value(RelType::Bool(false))),
-                    }
-                } else {
-                    Some(ExecResult::Fault {
-                        msg: "IO.AppendFile expects (String, String)".to_string(),
-                        node: "Native::IO.AppendFile".into()
-                    })
-                }
-            }
-            "IO.FileExists" => {
+                    return Err(permission_denied(func_name, "FS_WRITE"));
+                }
+                expect_args(args, &["path", "content"], func_name)?;
+                let path = arg::<String>(args, 0, "path", func_name)?;
+                let content = arg::<String>(args, 1, "content", func_name)?;
+                use std::io::Write;
+                let written = std::fs::OpenOptions::new()
+                    .append(true)
+                    .create(true)
+                    .open(path)
+                    .and_then(|mut file| write!(file, "{}", content));
+                Ok(RelType::Bool(written.is_ok()))
+            })(),
+            "IO.FileExists" => (|| {
                 if !permissions.allow_fs_read {
-                    return Some(ExecResult::Fault { 
-                        msg: "Permission Denied: IO.FileExists requires FS_READ".to_string(), 
-                        node: "Native::IO.FileExists".into() 
-                    });
-                }
-                if args.len() != 1 {
-                    return Some(ExecResult::Fault {
-                        msg: "IO.FileExists expects 1 argument (path)".to_string(),
-                        node: "Native::IO.FileExists".into()
-                    });
-                }
-                if let RelType::Str(path) = &args[0] {
-                    Some(ExecResult::Value(RelType::Bool(
-                        std::path::Path::new(path).exists(),
-                    )))
-                } else {
-                    Some(ExecResult::Fault {
-                        msg: "IO.FileExists expects a String".to_string(),
-                        node: "Native::IO.FileExists".into()
-                    })
-                }
-            }
-            _ => None,
-        }
+                    return Err(permission_denied(func_name, "FS_READ"));
+                }
+                expect_args(args, &["path"], func_name)?;
+                let path = arg::<String>(args, 0, "path", func_name)?;
+                Ok(RelType::Bool(std::path::Path::new(&path).exists()))
+            })(),
+            _ => return None,
+        };
+        Some(res)
+    }
+}
+
+impl NativeModule for IoModule {
+    fn handle(&self, func_name: &str, args: &[RelType], permissions: &AgentPermissions) -> Option<ExecResult> {
+        self.call(func_name, args, permissions).map(native_result)
     }
 }
@@ -0,0 +1,353 @@
+use crate::ast::Type;
+use crate::executor::{AgentPermissions, ExecResult, RelType};
+use crate::natives::NativeModule;
+use crate::natives::convert::{arg, expect_args, native_fault, native_result, rel_type_name};
+use serde_json::{Map, Value};
+
+/// JSON encoding, decoding and schema validation.
+///
+/// `JSON.Stringify` sorts object keys so equal values always produce the same
+/// text. `Void` encodes as `null`, vectors and matrices as number arrays;
+/// functions, handles and non-finite floats cannot be encoded.
+pub struct JsonModule;
+
+/// Parameter and return types, for the TypeChecker.
+pub fn signature(name: &str) -> Option<(Vec<Type>, Type)> {
+    let sig = match name {
+        "JSON.Stringify" => (vec![Type::Any, Type::Bool], Type::String),
+        "JSON.Parse" => (vec![Type::String], Type::Any),
+        "JSON.Validate" => (vec![Type::Any, Type::Any], Type::Array(vec![Type::String])),
+        _ => return None,
+    };
+    Some(sig)
+}
+
+/// Converts a script value to JSON, failing on values JSON cannot hold.
+pub fn to_json_value(value: &RelType) -> Result<Value, String> {
+    let floats = |xs: &[f32]| Value::Array(xs.iter().map(|x| Value::from(*x as f64)).collect());
+    Ok(match value {
+        RelType::Void => Value::Null,
+        RelType::Bool(b) => Value::Bool(*b),
+        RelType::Int(i) => Value::from(*i),
+        RelType::Float(f) => {
+            serde_json::Number::from_f64(*f).map(Value::Number).ok_or_else(|| format!("cannot encode {} as JSON", f))?
+        }
+        RelType::Str(s) => Value::String(s.clone()),
+        RelType::Array(items) => Value::Array(items.iter().map(to_json_value).collect::<Result<_, _>>()?),
+        RelType::Object(map) => {
+            let mut obj = Map::new();
+            for (k, v) in map {
+                obj.insert(k.clone(), to_json_value(v)?);
+            }
+            Value::Object(obj)
+        }
+        RelType::Vec2(v) => floats(&v.to_array()),
+        RelType::Vec3(v) => floats(&v.to_array()),
+        RelType::Vec4(v) => floats(&v.to_array()),
+        RelType::Quat(q) => floats(&q.to_array()),
+        RelType::Mat4(m) => floats(&m.to_cols_array()),
+        other => return Err(format!("cannot encode a {} as JSON", rel_type_name(other))),
+    })
+}
+
+/// Object keys in sorted order, independent of serde_json's map feature flags.
+fn sorted(value: Value) -> Value {
+    match value {
+        Value::Object(obj) => {
+            let mut entries: Vec<(String, Value)> = obj.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            Value::Object(entries.into_iter().map(|(k, v)| (k, sorted(v))).collect())
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(sorted).collect()),
+        other => other,
+    }
+}
+
+pub fn stringify(value: &RelType, pretty: bool) -> Result<String, String> {
+    let json = sorted(to_json_value(value)?);
+    let text = if pretty { serde_json::to_string_pretty(&json) } else { serde_json::to_string(&json) };
+    text.map_err(|e| e.to_string())
+}
+
+/// Checks `value` against a JSON Schema, returning one message per violation,
+/// each prefixed with the JSON Pointer of the offending value.
+///
+/// Supported keywords: `type` (a name or a list of names), `enum`, `const`,
+/// `properties`, `required`, `additionalProperties`, `items`, `minItems`,
+/// `maxItems`, `uniqueItems`, `minLength`, `maxLength`, `minimum`, `maximum`,
+/// `exclusiveMinimum`, `exclusiveMaximum`, `allOf`, `anyOf`, `oneOf` and `not`.
+/// Other keywords are ignored, as the specification asks of unknown ones.
+pub fn validate(value: &Value, schema: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    check(value, schema, "", &mut errors);
+    errors
+}
+
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match (name, value) {
+        ("number", Value::Number(_)) => true,
+        // 2.0 is an integer in JSON Schema
+        ("integer", Value::Number(n)) => n.as_f64().is_some_and(|f| f.fract() == 0.0),
+        _ => json_type(value) == name,
+    }
+}
+
+/// Numeric equality across integer and float representations.
+fn json_eq(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x.as_f64() == y.as_f64(),
+        (Value::Array(x), Value::Array(y)) => x.len() == y.len() && x.iter().zip(y).all(|(a, b)| json_eq(a, b)),
+        (Value::Object(x), Value::Object(y)) => {
+            x.len() == y.len() && x.iter().all(|(k, v)| y.get(k).is_some_and(|w| json_eq(v, w)))
+        }
+        _ => a == b,
+    }
+}
+
+fn check(value: &Value, schema: &Value, at: &str, errors: &mut Vec<String>) {
+    let rule = match schema {
+        Value::Bool(true) => return,
+        Value::Bool(false) => return errors.push(format!("{}: no value is allowed here", pointer(at))),
+        Value::Object(rule) => rule,
+        _ => return errors.push(format!("{}: schema must be an object or a bool", pointer(at))),
+    };
+    let mut fail = |msg: String| errors.push(format!("{}: {}", pointer(at), msg));
+
+    if let Some(t) = rule.get("type") {
+        let names: Vec<&str> = match t {
+            Value::String(s) => vec![s.as_str()],
+            Value::Array(list) => list.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !names.is_empty() && !names.iter().any(|n| has_type(value, n)) {
+            fail(format!("expected {}, found {}", names.join(" or "), json_type(value)));
+        }
+    }
+    if let Some(Value::Array(options)) = rule.get("enum")
+        && !options.iter().any(|o| json_eq(o, value))
+    {
+        fail(format!("{} is not one of {}", value, Value::Array(options.clone())));
+    }
+    if let Some(expected) = rule.get("const")
+        && !json_eq(expected, value)
+    {
+        fail(format!("expected {}, found {}", expected, value));
+    }
+
+    let limit = |key: &str| rule.get(key).and_then(Value::as_f64);
+    if let Some(n) = value.as_f64() {
+        if let Some(min) = limit("minimum").filter(|min| n < *min) {
+            fail(format!("{} is less than the minimum {}", value, min));
+        }
+        if let Some(max) = limit("maximum").filter(|max| n > *max) {
+            fail(format!("{} is greater than the maximum {}", value, max));
+        }
+        if let Some(min) = limit("exclusiveMinimum").filter(|min| n <= *min) {
+            fail(format!("{} must be greater than {}", value, min));
+        }
+        if let Some(max) = limit("exclusiveMaximum").filter(|max| n >= *max) {
+            fail(format!("{} must be less than {}", value, max));
+        }
+    }
+    if let Value::String(s) = value {
+        let len = s.chars().count() as f64;
+        if let Some(min) = limit("minLength").filter(|min| len < *min) {
+            fail(format!("string is shorter than {} characters", min));
+        }
+        if let Some(max) = limit("maxLength").filter(|max| len > *max) {
+            fail(format!("string is longer than {} characters", max));
+        }
+    }
+    if let Value::Array(items) = value {
+        let len = items.len() as f64;
+        if let Some(min) = limit("minItems").filter(|min| len < *min) {
+            fail(format!("array has fewer than {} items", min));
+        }
+        if let Some(max) = limit("maxItems").filter(|max| len > *max) {
+            fail(format!("array has more than {} items", max));
+        }
+        if rule.get("uniqueItems") == Some(&Value::Bool(true))
+            && items.iter().enumerate().any(|(i, a)| items[..i].iter().any(|b| json_eq(a, b)))
+        {
+            fail("array items are not unique".into());
+        }
+        if let Some(item_schema) = rule.get("items") {
+            for (i, item) in items.iter().enumerate() {
+                check(item, item_schema, &format!("{}/{}", at, i), errors);
+            }
+        }
+    }
+    if let Value::Object(obj) = value {
+        if let Some(Value::Array(required)) = rule.get("required") {
+            for key in required.iter().filter_map(Value::as_str) {
+                if !obj.contains_key(key) {
+                    errors.push(format!("{}: missing required property '{}'", pointer(at), key));
+                }
+            }
+        }
+        let properties = rule.get("properties").and_then(Value::as_object);
+        let mut keys: Vec<&String> = obj.keys().collect();
+        keys.sort();
+        for key in keys {
+            let child = format!("{}/{}", at, key.replace('~', "~0").replace('/', "~1"));
+            match (properties.and_then(|p| p.get(key)), rule.get("additionalProperties")) {
+                (Some(prop_schema), _) => check(&obj[key], prop_schema, &child, errors),
+                (None, Some(Value::Bool(false))) => {
+                    errors.push(format!("{}: unexpected property '{}'", pointer(at), key));
+                }
+                (None, Some(extra)) => check(&obj[key], extra, &child, errors),
+                (None, None) => {}
+            }
+        }
+    }
+
+    let passes = |s: &Value| validate_at(value, s, at).is_empty();
+    if let Some(Value::Array(all)) = rule.get("allOf") {
+        for s in all {
+            check(value, s, at, errors);
+        }
+    }
+    if let Some(Value::Array(any)) = rule.get("anyOf")
+        && !any.iter().any(passes)
+    {
+        errors.push(format!("{}: does not match any schema in anyOf", pointer(at)));
+    }
+    if let Some(Value::Array(one)) = rule.get("oneOf") {
+        let matched = one.iter().filter(|s| passes(s)).count();
+        if matched != 1 {
+            errors.push(format!("{}: matches {} schemas in oneOf, expected exactly 1", pointer(at), matched));
+        }
+    }
+    if let Some(not) = rule.get("not")
+        && passes(not)
+    {
+        errors.push(format!("{}: must not match the schema in not", pointer(at)));
+    }
+}
+
+fn validate_at(value: &Value, schema: &Value, at: &str) -> Vec<String> {
+    let mut errors = Vec::new();
+    check(value, schema, at, &mut errors);
+    errors
+}
+
+fn pointer(at: &str) -> &str {
+    if at.is_empty() { "/" } else { at }
+}
+
+impl JsonModule {
+    fn call(&self, func_name: &str, args: &[RelType]) -> Option<Result<RelType, ExecResult>> {
+        let f = func_name;
+        let res = match f {
+            "JSON.Stringify" => (|| {
+                expect_args(args, &["value", "pretty"], f)?;
+                let pretty = arg::<bool>(args, 1, "pretty", f)?;
+                stringify(&args[0], pretty).map(RelType::Str).map_err(|e| native_fault(f, e))
+            })(),
+            "JSON.Parse" => expect_args(args, &["text"], f)
+                .and_then(|_| arg::<String>(args, 0, "text", f))
+                .and_then(|text| crate::natives::fs::fs_parse_json(&text).map_err(|e| native_fault(f, e))),
+            "JSON.Validate" => (|| {
+                expect_args(args, &["value", "schema"], f)?;
+                let value = to_json_value(&args[0]).map_err(|e| native_fault(f, e))?;
+                // A schema may be given as a value or as JSON text
+                let schema = match &args[1] {
+                    RelType::Str(text) => serde_json::from_str(text).map_err(|e| native_fault(f, format!("invalid schema JSON: {}", e)))?,
+                    other => to_json_value(other).map_err(|e| native_fault(f, e))?,
+                };
+                Ok(RelType::Array(validate(&value, &schema).into_iter().map(RelType::Str).collect()))
+            })(),
+            _ => return None,
+        };
+        Some(res)
+    }
+}
+
+impl NativeModule for JsonModule {
+    fn handle(&self, func_name: &str, args: &[RelType], _permissions: &AgentPermissions) -> Option<ExecResult> {
+        self.call(func_name, args).map(native_result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(name: &str, args: Vec<RelType>) -> Result<RelType, ExecResult> {
+        JsonModule.call(name, &args).expect("unknown function")
+    }
+
+    fn s(v: &str) -> RelType {
+        RelType::Str(v.into())
+    }
+
+    #[test]
+    fn test_stringify_round_trip() {
+        let value = crate::natives::fs::fs_parse_json(r#"{"b": [1, 2.5, null], "a": "say \"hi\"\n", "c": {"z": true, "y": false}}"#).unwrap();
+        let text = call("JSON.Stringify", vec![value.clone(), RelType::Bool(false)]).ok().unwrap();
+        assert_eq!(text, s(r#"{"a":"say \"hi\"\n","b":[1,2.5,null],"c":{"y":false,"z":true}}"#));
+        assert_eq!(call("JSON.Parse", vec![text]).ok(), Some(value.clone()));
+
+        let pretty = call("JSON.Stringify", vec![RelType::Array(vec![RelType::Int(1)]), RelType::Bool(true)]).ok().unwrap();
+        assert_eq!(pretty, s("[\n  1\n]"));
+        match call("JSON.Stringify", vec![RelType::Float(f64::NAN), RelType::Bool(false)]) {
+            Err(ExecResult::Fault { msg, .. }) => assert_eq!(msg, "JSON.Stringify: cannot encode NaN as JSON"),
+            other => panic!("expected a fault, got {:?}", other.ok()),
+        }
+        match call("JSON.Parse", vec![s("{\"a\": ")]) {
+            Err(ExecResult::Fault { msg, node }) => {
+                assert!(msg.starts_with("JSON.Parse: EOF while parsing"), "{}", msg);
+                assert_eq!(node, "Native::JSON.Parse");
+            }
+            other => panic!("expected a parse fault, got {:?}", other.ok()),
+        }
+    }
+
+    #[test]
+    fn test_validate_schema() {
+        let schema = s(r#"{
+            "type": "object",
+            "required": ["id", "tags"],
+            "additionalProperties": false,
+            "properties": {
+                "id": {"type": "integer", "minimum": 1},
+                "name": {"type": "string", "minLength": 1},
+                "tags": {"type": "array", "items": {"enum": ["a", "b"]}, "uniqueItems": true},
+                "score": {"anyOf": [{"type": "null"}, {"type": "number", "maximum": 1}]}
+            }
+        }"#);
+        let ok = crate::natives::fs::fs_parse_json(r#"{"id": 3, "tags": ["a"], "score": 0.5}"#).unwrap();
+        assert_eq!(call("JSON.Validate", vec![ok, schema.clone()]).ok(), Some(RelType::Array(vec![])));
+
+        let bad = crate::natives::fs::fs_parse_json(r#"{"id": 0.5, "name": "", "tags": ["a", "c", "a"], "score": 2, "x": 1}"#).unwrap();
+        let errors = call("JSON.Validate", vec![bad, schema]).ok().unwrap();
+        assert_eq!(
+            errors,
+            RelType::Array(
+                [
+                    "/id: expected integer, found number",
+                    "/id: 0.5 is less than the minimum 1",
+                    "/name: string is shorter than 1 characters",
+                    "/score: does not match any schema in anyOf",
+                    "/tags: array items are not unique",
+                    "/tags/1: \"c\" is not one of [\"a\",\"b\"]",
+                    "/: unexpected property 'x'",
+                ]
+                .map(s)
+                .to_vec()
+            )
+        );
+    }
+}
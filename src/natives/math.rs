@@ -1,91 +1,46 @@
 use crate::executor::{ExecResult, RelType, AgentPermissions};
 use crate::natives::NativeModule;
+use crate::natives::convert::{arg, expect_args, native_result};
 use noise::{NoiseFn, Perlin};
 
 pub struct MathModule;
 
+/// Floor/Ceil keep integers as integers.
+fn round_with(args: &[RelType], func: &str, op: fn(f64) -> f64) -> Result<RelType, ExecResult> {
+    expect_args(args, &["x"], func)?;
+    match args[0] {
+        RelType::Int(i) => Ok(RelType::Int(i)),
+        _ => Ok(RelType::Float(op(arg::<f64>(args, 0, "x", func)?))),
+    }
+}
+
+impl MathModule {
+    fn call(&self, func_name: &str, args: &[RelType]) -> Option<Result<RelType, ExecResult>> {
+        let res = match func_name {
+            "Math.Random" => Ok(RelType::Float(rand::random::<f64>())),
+            "Math.Sin" => expect_args(args, &["x"], func_name)
+                .and_then(|_| arg::<f64>(args, 0, "x", func_name))
+                .map(|x| RelType::Float(x.sin())),
+            "Math.Cos" => expect_args(args, &["x"], func_name)
+                .and_then(|_| arg::<f64>(args, 0, "x", func_name))
+                .map(|x| RelType::Float(x.cos())),
+            "Math.Floor" => round_with(args, func_name, f64::floor),
+            "Math.Ceil" => round_with(args, func_name, f64::ceil),
+            "Math.Perlin2D" => (|| {
+                expect_args(args, &["x", "y"], func_name)?;
+                let x = arg::<f64>(args, 0, "x", func_name)?;
+                let y = arg::<f64>(args, 1, "y", func_name)?;
+                let perlin = Perlin::new(1); // Explicit seed for stability
+                Ok(RelType::Float(perlin.get([x, y])))
+            })(),
+            _ => return None,
+        };
+        Some(res)
+    }
+}
+
 impl NativeModule for MathModule {
     fn handle(&self, func_name: &str, args: &[RelType], _permissions: &AgentPermissions) -> Option<ExecResult> {
-        match func_name {
-            "Math.Random" => Some(ExecResult::Value(RelType::Float(rand::random::<f64>()))),
-            "Math.Sin" => {
-                if args.len() != 1 {
-                    return Some(ExecResult::Fault { msg: "Math.Sin expects 1 argument".to_string(), node: "Native::Math.Sin".into() });
-                }
-                match args[0] {
-                    RelType::Float(f) => Some(ExecResult::Value(RelType::Float(f.sin()))),
-                    RelType::Int(i) => Some(ExecResult::Value(RelType::Float((i as f64).sin()))),
-                    _ => Some(ExecResult::Fault { msg: "Math.Sin expects a Number".to_string(), node: "Native::Math.Sin".into() }),
-                }
-            }
-            "Math.Cos" => {
-                if args.len() != 1 {
-                    return Some(ExecResult::Fault { msg: "Math.Cos expects 1 argument".to_string(), node: "Native::Math.Cos".into() });
-                }
-                match args[0] {
-                    RelType::Float(f) => Some(ExecResult::Value(RelType::Float(f.cos()))),
-                    RelType::Int(i) => Some(ExecResult::Value(RelType::Float((i as f64).cos()))),
-                    _ => Some(ExecResult::Fault { msg: "Math.Cos expects a Number".to_string(), node: "Native::Math.Cos".into() }),
-                }
-            }
-            "Math.Floor" => {
-                if args.len() != 1 {
-                    return Some(ExecResult::Fault {
-                        msg: "Math.Floor expects 1 argument".to_string(),
-                        node: "Native::Math.Floor".into()
-                    });
-                }
-                match args[0] {
-                    RelType::Float(f) => Some(ExecResult::Value(RelType::Float(f.floor()))),
-                    RelType::Int(i) => Some(ExecResult::Value(RelType::Int(i))),
-                    _ => Some(ExecResult::Fault { msg: "Math.Floor expects a Number".to_string(), node: "Native::Math.Floor".into() }),
-                }
-            }
-            "Math.Ceil" => {
-                if args.len() != 1 {
-                    return Some(ExecResult::Fault {
-                        msg: "Math.Ceil expects 1 argument".to_string(),
-                        node: "Native::Math.Ceil".into()
-                    });
-                }
-                match args[0] {
-                    RelType::Float(f) => Some(ExecResult::Value(RelType::Float(f.ceil()))),
-                    RelType::Int(i) => Some(ExecResult::Value(RelType::Int(i))),
-                    _ => Some(ExecResult::Fault { msg: "Math.Ceil expects a Number".to_string(), node: "Native::Math.Ceil".into() }),
-                }
-            }
-            "Math.Perlin2D" => {
-                if args.len() != 2 {
-                    return Some(ExecResult::Fault {
-                        msg: "Math.Perlin2D expects 2 arguments (x, y)".to_string(),
-                        node: "Native::Math.Perlin2D".into()
-                    });
-                }
-                let x = match args[0] {
-                    RelType::Float(f) => f,
-                    RelType::Int(i) => i as f64,
-                    _ => {
-                        return Some(ExecResult::Fault {
-                            msg: "Math.Perlin2D arg 1 must be a Number".to_string(),
-                            node: "Native::Math.Perlin2D".into()
-                        });
-                    }
-                };
-                let y = match args[1] {
-                    RelType::Float(f) => f,
-                    RelType::Int(i) => i as f64,
-                    _ => {
-                        return Some(ExecResult::Fault {
-                            msg: "Math.Perlin2D arg 2 must be a Number".to_string(),
-                            node: "Native::Math.Perlin2D".into()
-                        });
-                    }
-                };
-                let perlin = Perlin::new(1); // Explicit seed for stability
-                let val = perlin.get([x, y]);
-                Some(ExecResult::Value(RelType::Float(val)))
-            }
-            _ => None,
-        }
+        self.call(func_name, args).map(native_result)
     }
 }
@@ -1,12 +1,72 @@
-use crate::executor::{ExecResult, RelType, AgentPermissions};
+use crate::executor::{ExecResult, ExecutionEngine, RelType, AgentPermissions};
 
+pub mod array;
 pub mod bridge;
+pub mod bytes;
+pub mod convert;
+pub mod csv;
 pub mod fs;
 pub mod io;
+pub mod json;
 pub mod math;
 pub mod registry;
+pub mod rel_serde;
+pub mod store;
+pub mod string;
+pub mod template;
 pub mod ui;
+pub mod vector;
 
-pub trait NativeModule: Send {
+/// Engines hold modules behind an `Arc`, and an engine can move to another
+/// thread, so a module must be both `Send` and `Sync`.
+pub trait NativeModule: Send + Sync {
     fn handle(&self, func_name: &str, args: &[RelType], permissions: &AgentPermissions) -> Option<ExecResult>;
+
+    /// Entry point used by the engine. Natives that call back into script
+    /// functions override this; the default forwards to `handle`.
+    fn handle_in(&self, ctx: &mut NativeContext<'_>, func_name: &str, args: &[RelType]) -> Option<ExecResult> {
+        self.handle(func_name, args, ctx.permissions())
+    }
+}
+
+/// Engine access for a native call in progress.
+pub struct NativeContext<'a> {
+    engine: &'a mut ExecutionEngine,
+}
+
+impl<'a> NativeContext<'a> {
+    pub fn new(engine: &'a mut ExecutionEngine) -> Self {
+        Self { engine }
+    }
+
+    pub fn permissions(&self) -> &AgentPermissions {
+        &self.engine.permissions
+    }
+
+    /// The persistent store behind `Store`/`Load`.
+    pub fn store(&self) -> &crate::vm::storage::KvStore {
+        &self.engine.store
+    }
+
+    /// Calls a function value (or a function name) with the given arguments.
+    /// A fault raised inside the callback comes back as `Err`, so natives can
+    /// propagate it unchanged with `?`.
+    pub fn call(&mut self, func: &RelType, args: Vec<RelType>) -> Result<RelType, ExecResult> {
+        match self.engine.call_value(func, args) {
+            ExecResult::Value(v) | ExecResult::ReturnBlockInfo(v) => Ok(v),
+            fault => Err(fault),
+        }
+    }
+}
+
+/// Declared parameter and return types of built-in natives, for the TypeChecker.
+pub fn signature(name: &str) -> Option<(Vec<crate::ast::Type>, crate::ast::Type)> {
+    string::signature(name)
+        .or_else(|| array::signature(name))
+        .or_else(|| vector::signature(name))
+        .or_else(|| json::signature(name))
+        .or_else(|| bytes::signature(name))
+        .or_else(|| csv::signature(name))
+        .or_else(|| template::signature(name))
+        .or_else(|| store::signature(name))
 }
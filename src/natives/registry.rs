@@ -1,9 +1,9 @@
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::fs::File;
 use std::io::Write;
 use std::sync::Arc;
-use std::sync::Mutex;
+use std::sync::{Condvar, Mutex};
 use wgpu::util::DeviceExt;
 use winit::window::Window as WinitWindow;
 
@@ -146,6 +146,33 @@ pub enum NativeHandle {
     GpuContext(GpuContext),
     VoxelWorld(SendVoxelWorld),
     Texture(TextureAsset),
+    Promise(PromiseEntry),
+    Channel(Arc<ChannelQueue>),
+}
+
+impl NativeHandle {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            NativeHandle::Counter(_) => "Counter",
+            NativeHandle::Window(_) => "Window",
+            NativeHandle::File(_) => "File",
+            NativeHandle::Timestamp(_) => "Timestamp",
+            NativeHandle::GpuContext(_) => "GpuContext",
+            NativeHandle::VoxelWorld(_) => "VoxelWorld",
+            NativeHandle::Texture(_) => "Texture",
+            NativeHandle::Promise(_) => "Promise",
+            NativeHandle::Channel(_) => "Channel",
+        }
+    }
+
+    /// Whether scripts on other threads may use this handle. Windows, files and
+    /// GPU resources belong to the engine (and render thread) that created them.
+    pub fn is_shareable(&self) -> bool {
+        matches!(
+            self,
+            NativeHandle::Counter(_) | NativeHandle::Timestamp(_) | NativeHandle::Promise(_) | NativeHandle::Channel(_)
+        )
+    }
 }
 
 pub struct RegistryEntry {
@@ -166,7 +193,8 @@ pub struct GpuContext {
     pub queue: Arc<wgpu::Queue>,
 }
 
-// SAFETY: wgpu GPU types are Send+Sync; our registry is single-threaded.
+// SAFETY: wgpu GPU types are Send+Sync. Script tasks on other threads cannot
+// reach them: `registry_check_shareable` rejects such handles at the boundary.
 unsafe impl Send for GpuContext {}
 unsafe impl Sync for GpuContext {}
 
@@ -322,7 +350,7 @@ pub fn registry_release(handle_id: i64) {
     }
     let id = handle_id as usize;
     let mut remove = false;
-    with_registry(|registry| {
+    let removed = with_registry(|registry| {
         if let Some(entry) = registry.get_mut(&id) {
             if entry.ref_count > 0 {
                 entry.ref_count -= 1;
@@ -332,9 +360,14 @@ pub fn registry_release(handle_id: i64) {
             }
         }
         if remove {
-            registry.remove(&id);
+            registry.remove(&id)
+        } else {
+            None
         }
     });
+    // Entries may own script values (promises), whose handles release through
+    // this function again, so they are dropped after the lock is gone.
+    drop(removed);
 }
 
 // FFI Implementations
@@ -413,21 +446,14 @@ pub fn registry_dump() -> i64 {
     with_registry(|registry| {
         println!("[KnotenCore Registry] --- MEMORY DUMP ---");
         for (id, entry) in registry.iter() {
-            let handle_type = match &entry.handle {
-                NativeHandle::Counter(_) => "Counter",
-                NativeHandle::Window(_) => "Window",
-                NativeHandle::File(_) => "File",
-                NativeHandle::Timestamp(_) => "Timestamp",
-                NativeHandle::GpuContext(_) => "GpuContext",
+            match &entry.handle {
                 NativeHandle::VoxelWorld(SendVoxelWorld(s)) => {
                     println!("      voxels={}, {}x{}", s.voxels.len(), s.width, s.height);
-                    "VoxelWorld"
-                }
-                NativeHandle::Texture(tex) => {
-                    println!("      {}x{}", tex.width, tex.height);
-                    "Texture"
                 }
-            };
+                NativeHandle::Texture(tex) => println!("      {}x{}", tex.width, tex.height),
+                _ => {}
+            }
+            let handle_type = entry.handle.kind();
             println!(
                 "   -> Handle {} [Type: {}, RefCount: {}]",
                 id, handle_type, entry.ref_count
@@ -439,6 +465,252 @@ pub fn registry_dump() -> i64 {
     count
 }
 
+// ── Snapshot Hooks ─────────────────────────────────────────────────
+// Used by `ExecutionEngine::snapshot`/`restore`. Only handles whose state lives
+// entirely in the registry can be persisted; OS and GPU resources cannot.
+
+/// Serializable description of a live handle, or an error naming its type.
+pub fn registry_snapshot_handle(handle_id: i64) -> Result<serde_json::Value, String> {
+    if handle_id < 0 {
+        return Err(format!("Handle {} is invalid", handle_id));
+    }
+    let id = handle_id as usize;
+    with_registry(|registry| {
+        let entry = registry
+            .get(&id)
+            .ok_or_else(|| format!("Handle {} not found in registry", handle_id))?;
+        match &entry.handle {
+            NativeHandle::Counter(counter) => Ok(serde_json::json!({ "type": "Counter", "count": counter.count })),
+            NativeHandle::Timestamp(t) => {
+                Ok(serde_json::json!({ "type": "Timestamp", "elapsed_ms": t.elapsed().as_millis() as u64 }))
+            }
+            other => Err(format!("Handle {} ({}) is not serializable", handle_id, other.kind())),
+        }
+    })
+}
+
+/// Recreates a handle from `registry_snapshot_handle` output with the given
+/// reference count and returns its new id.
+pub fn registry_restore_handle(desc: &serde_json::Value, ref_count: usize) -> Result<i64, String> {
+    let handle = match desc["type"].as_str() {
+        Some("Counter") => {
+            let count = desc["count"].as_i64().ok_or("Counter snapshot is missing 'count'")?;
+            NativeHandle::Counter(StatefulCounter { count })
+        }
+        Some("Timestamp") => {
+            let elapsed = desc["elapsed_ms"].as_u64().ok_or("Timestamp snapshot is missing 'elapsed_ms'")?;
+            let now = std::time::Instant::now();
+            let start = now.checked_sub(std::time::Duration::from_millis(elapsed)).unwrap_or(now);
+            NativeHandle::Timestamp(start)
+        }
+        Some(other) => return Err(format!("Unknown handle type '{}' in snapshot", other)),
+        None => return Err("Handle snapshot is missing 'type'".to_string()),
+    };
+
+    let mut id_guard = COUNTER_NEXT_ID.lock().unwrap_or_else(|e| e.into_inner());
+    let id = *id_guard;
+    *id_guard += 1;
+    with_registry(|registry| {
+        registry.insert(id, RegistryEntry { handle, ref_count });
+    });
+    Ok(id as i64)
+}
+
+// ── Promise Orchestration ──────────────────────────────────────────
+// Settled values are kept behind an `Arc` so reading them never clones a
+// `RelType` (and retains its handles) while the registry lock is held.
+
+pub enum PromiseEntry {
+    Leaf(Option<Result<Arc<crate::executor::RelType>, String>>),
+    All(Vec<crate::executor::NativeHandle>),
+    Race(Vec<crate::executor::NativeHandle>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PromiseState {
+    Pending,
+    Resolved(crate::executor::RelType),
+    Rejected(String),
+}
+
+fn insert_handle(handle: NativeHandle) -> i64 {
+    let mut id_guard = COUNTER_NEXT_ID.lock().unwrap_or_else(|e| e.into_inner());
+    let id = *id_guard;
+    *id_guard += 1;
+    with_registry(|registry| {
+        registry.insert(id, RegistryEntry { handle, ref_count: 1 });
+    });
+    id as i64
+}
+
+/// Creates a pending promise, to be settled with `registry_promise_settle`.
+pub fn registry_promise_new() -> i64 {
+    insert_handle(NativeHandle::Promise(PromiseEntry::Leaf(None)))
+}
+
+/// The right to settle one promise, held by whatever produces its value: a
+/// fetch, a timer or a task. Dropping it unsettled rejects the promise, so an
+/// `await` never waits on a producer that is gone.
+pub struct PromiseSettler(Option<crate::executor::NativeHandle>);
+
+impl PromiseSettler {
+    /// A pending promise and the right to settle it.
+    pub fn new() -> (crate::executor::NativeHandle, Self) {
+        let promise = crate::executor::NativeHandle(registry_promise_new());
+        let settler = PromiseSettler(Some(promise.clone()));
+        (promise, settler)
+    }
+
+    pub fn settle(mut self, result: Result<crate::executor::RelType, String>) -> bool {
+        let promise = self.0.take().expect("settler holds its promise until settled");
+        registry_promise_settle(promise.0, result)
+    }
+}
+
+impl Drop for PromiseSettler {
+    fn drop(&mut self) {
+        if let Some(promise) = self.0.take() {
+            registry_promise_settle(promise.0, Err("Promise abandoned: its producer stopped before settling it".into()));
+        }
+    }
+}
+
+/// Settles a pending promise. Returns false if the handle is not a pending
+/// (non-combinator) promise, e.g. because it was already settled or freed.
+pub fn registry_promise_settle(handle_id: i64, result: Result<crate::executor::RelType, String>) -> bool {
+    if handle_id < 0 {
+        return false;
+    }
+    let id = handle_id as usize;
+    let mut result = Some(result);
+    let settled = with_registry(|registry| match registry.get_mut(&id).map(|e| &mut e.handle) {
+        Some(NativeHandle::Promise(PromiseEntry::Leaf(state @ None))) => {
+            *state = result.take().map(|r| r.map(Arc::new));
+            true
+        }
+        _ => false,
+    });
+    // A result that was not stored may own handles, which release through
+    // the registry lock, so it is dropped after the lock is gone.
+    drop(result);
+    settled
+}
+
+/// Combines promises: with `race == false` it resolves to an array once all
+/// resolve; with `race == true` it settles like the first settled input.
+/// Either way the first rejection (in input order) rejects it.
+pub fn registry_promise_combine(inputs: Vec<crate::executor::NativeHandle>, race: bool) -> i64 {
+    let entry = if race { PromiseEntry::Race(inputs) } else { PromiseEntry::All(inputs) };
+    insert_handle(NativeHandle::Promise(entry))
+}
+
+/// Current state of a promise, or `None` if the handle is not a promise.
+pub fn registry_promise_state(handle_id: i64) -> Option<PromiseState> {
+    enum Found {
+        Leaf(Option<Result<Arc<crate::executor::RelType>, String>>),
+        Inputs(Vec<i64>, bool),
+    }
+    if handle_id < 0 {
+        return None;
+    }
+    let id = handle_id as usize;
+    let found = with_registry(|registry| match registry.get(&id).map(|e| &e.handle) {
+        Some(NativeHandle::Promise(PromiseEntry::Leaf(state))) => Some(Found::Leaf(state.clone())),
+        Some(NativeHandle::Promise(PromiseEntry::All(inputs))) => Some(Found::Inputs(inputs.iter().map(|h| h.0).collect(), false)),
+        Some(NativeHandle::Promise(PromiseEntry::Race(inputs))) => Some(Found::Inputs(inputs.iter().map(|h| h.0).collect(), true)),
+        _ => None,
+    })?;
+    Some(match found {
+        Found::Leaf(None) => PromiseState::Pending,
+        Found::Leaf(Some(Ok(v))) => PromiseState::Resolved((*v).clone()),
+        Found::Leaf(Some(Err(e))) => PromiseState::Rejected(e),
+        Found::Inputs(ids, race) => {
+            let mut values = Vec::with_capacity(ids.len());
+            let mut pending = false;
+            for input in ids {
+                match registry_promise_state(input)
+                    .unwrap_or_else(|| PromiseState::Rejected(format!("Handle {} is not a promise", input)))
+                {
+                    PromiseState::Rejected(e) => return Some(PromiseState::Rejected(e)),
+                    PromiseState::Resolved(v) if race => return Some(PromiseState::Resolved(v)),
+                    PromiseState::Resolved(v) => values.push(v),
+                    PromiseState::Pending => pending = true,
+                }
+            }
+            if pending || race { PromiseState::Pending } else { PromiseState::Resolved(crate::executor::RelType::Array(values)) }
+        }
+    })
+}
+
+// ── Channel Orchestration ──────────────────────────────────────────
+// The queue lives behind its own lock; the registry only hands out the `Arc`,
+// so a blocking receive never holds the registry lock.
+
+#[derive(Default)]
+pub struct ChannelQueue {
+    items: Mutex<VecDeque<crate::executor::RelType>>,
+    ready: Condvar,
+}
+
+impl ChannelQueue {
+    pub fn send(&self, value: crate::executor::RelType) {
+        self.items.lock().unwrap_or_else(|e| e.into_inner()).push_back(value);
+        self.ready.notify_one();
+    }
+
+    pub fn try_recv(&self) -> Option<crate::executor::RelType> {
+        self.items.lock().unwrap_or_else(|e| e.into_inner()).pop_front()
+    }
+
+    /// Waits for a value, forever if `timeout` is `None`.
+    pub fn recv(&self, timeout: Option<std::time::Duration>) -> Option<crate::executor::RelType> {
+        let deadline = timeout.map(|t| std::time::Instant::now() + t);
+        let mut items = self.items.lock().unwrap_or_else(|e| e.into_inner());
+        loop {
+            if let Some(v) = items.pop_front() {
+                return Some(v);
+            }
+            items = match deadline {
+                None => self.ready.wait(items).unwrap_or_else(|e| e.into_inner()),
+                Some(d) => {
+                    let left = d.saturating_duration_since(std::time::Instant::now());
+                    if left.is_zero() {
+                        return None;
+                    }
+                    self.ready.wait_timeout(items, left).unwrap_or_else(|e| e.into_inner()).0
+                }
+            };
+        }
+    }
+}
+
+pub fn registry_channel_new() -> i64 {
+    insert_handle(NativeHandle::Channel(Arc::default()))
+}
+
+/// The queue behind a channel handle, or `None` if the handle is not a channel.
+pub fn registry_channel(handle_id: i64) -> Option<Arc<ChannelQueue>> {
+    if handle_id < 0 {
+        return None;
+    }
+    with_registry(|registry| match registry.get(&(handle_id as usize)).map(|e| &e.handle) {
+        Some(NativeHandle::Channel(queue)) => Some(queue.clone()),
+        _ => None,
+    })
+}
+
+/// Errors (naming the handle type) unless the handle may cross to another thread.
+pub fn registry_check_shareable(handle_id: i64) -> Result<(), String> {
+    if handle_id < 0 {
+        return Err(format!("Handle {} is invalid", handle_id));
+    }
+    with_registry(|registry| match registry.get(&(handle_id as usize)) {
+        Some(entry) if entry.handle.is_shareable() => Ok(()),
+        Some(entry) => Err(format!("Handle {} ({}) cannot be shared with another task", handle_id, entry.handle.kind())),
+        None => Err(format!("Handle {} not found in registry", handle_id)),
+    })
+}
+
 // ── Timestamp Orchestration ────────────────────────────────────────
 
 pub fn registry_now() -> i64 {
@@ -0,0 +1,650 @@
+//! `serde` support targeting `RelType` directly, so host types can move in and
+//! out of scripts without a JSON round-trip.
+//!
+//! Structs and maps become `Object`s, sequences and tuples become `Array`s,
+//! `None`/unit become `Void`. Enums are externally tagged: unit variants are
+//! strings, other variants are single-key objects.
+
+use crate::executor::RelType;
+use crate::natives::convert::{ConvertError, rel_type_name};
+use serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::ser::{self, Serialize};
+use std::collections::HashMap;
+
+impl std::error::Error for ConvertError {}
+
+impl ser::Error for ConvertError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        ConvertError::new(msg.to_string())
+    }
+}
+
+impl de::Error for ConvertError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        ConvertError::new(msg.to_string())
+    }
+}
+
+/// Serializes any `T: Serialize` into a script value.
+pub fn to_rel<T: Serialize + ?Sized>(value: &T) -> Result<RelType, ConvertError> {
+    value.serialize(RelSerializer)
+}
+
+/// Deserializes a script value into a host type. Errors carry the path of the
+/// offending element, e.g. `inventory[3].count: expected integer, found String`.
+pub fn from_rel<'de, T: de::Deserialize<'de>>(value: &'de RelType) -> Result<T, ConvertError> {
+    T::deserialize(RelDeserializer(value))
+}
+
+// --- Serializer ---
+
+pub struct RelSerializer;
+
+pub struct SeqBuilder {
+    items: Vec<RelType>,
+}
+
+pub struct VariantSeqBuilder {
+    variant: &'static str,
+    items: Vec<RelType>,
+}
+
+pub struct MapBuilder {
+    map: HashMap<String, RelType>,
+    key: Option<String>,
+}
+
+pub struct VariantMapBuilder {
+    variant: &'static str,
+    map: HashMap<String, RelType>,
+}
+
+fn tagged(variant: &str, value: RelType) -> RelType {
+    RelType::Object(HashMap::from([(variant.to_string(), value)]))
+}
+
+impl ser::Serializer for RelSerializer {
+    type Ok = RelType;
+    type Error = ConvertError;
+    type SerializeSeq = SeqBuilder;
+    type SerializeTuple = SeqBuilder;
+    type SerializeTupleStruct = SeqBuilder;
+    type SerializeTupleVariant = VariantSeqBuilder;
+    type SerializeMap = MapBuilder;
+    type SerializeStruct = MapBuilder;
+    type SerializeStructVariant = VariantMapBuilder;
+
+    fn serialize_bool(self, v: bool) -> Result<RelType, ConvertError> {
+        Ok(RelType::Bool(v))
+    }
+    fn serialize_i8(self, v: i8) -> Result<RelType, ConvertError> {
+        Ok(RelType::Int(v as i64))
+    }
+    fn serialize_i16(self, v: i16) -> Result<RelType, ConvertError> {
+        Ok(RelType::Int(v as i64))
+    }
+    fn serialize_i32(self, v: i32) -> Result<RelType, ConvertError> {
+        Ok(RelType::Int(v as i64))
+    }
+    fn serialize_i64(self, v: i64) -> Result<RelType, ConvertError> {
+        Ok(RelType::Int(v))
+    }
+    fn serialize_u8(self, v: u8) -> Result<RelType, ConvertError> {
+        Ok(RelType::Int(v as i64))
+    }
+    fn serialize_u16(self, v: u16) -> Result<RelType, ConvertError> {
+        Ok(RelType::Int(v as i64))
+    }
+    fn serialize_u32(self, v: u32) -> Result<RelType, ConvertError> {
+        Ok(RelType::Int(v as i64))
+    }
+    fn serialize_u64(self, v: u64) -> Result<RelType, ConvertError> {
+        i64::try_from(v)
+            .map(RelType::Int)
+            .map_err(|_| ConvertError::new(format!("integer {} does not fit in Int", v)))
+    }
+    fn serialize_f32(self, v: f32) -> Result<RelType, ConvertError> {
+        Ok(RelType::Float(v as f64))
+    }
+    fn serialize_f64(self, v: f64) -> Result<RelType, ConvertError> {
+        Ok(RelType::Float(v))
+    }
+    fn serialize_char(self, v: char) -> Result<RelType, ConvertError> {
+        Ok(RelType::Str(v.to_string()))
+    }
+    fn serialize_str(self, v: &str) -> Result<RelType, ConvertError> {
+        Ok(RelType::Str(v.to_string()))
+    }
+    fn serialize_bytes(self, v: &[u8]) -> Result<RelType, ConvertError> {
+        Ok(RelType::Array(v.iter().map(|b| RelType::Int(*b as i64)).collect()))
+    }
+    fn serialize_none(self) -> Result<RelType, ConvertError> {
+        Ok(RelType::Void)
+    }
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<RelType, ConvertError> {
+        value.serialize(self)
+    }
+    fn serialize_unit(self) -> Result<RelType, ConvertError> {
+        Ok(RelType::Void)
+    }
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<RelType, ConvertError> {
+        Ok(RelType::Void)
+    }
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<RelType, ConvertError> {
+        Ok(RelType::Str(variant.to_string()))
+    }
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<RelType, ConvertError> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<RelType, ConvertError> {
+        Ok(tagged(variant, value.serialize(self)?))
+    }
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqBuilder, ConvertError> {
+        Ok(SeqBuilder { items: Vec::with_capacity(len.unwrap_or(0)) })
+    }
+    fn serialize_tuple(self, len: usize) -> Result<SeqBuilder, ConvertError> {
+        self.serialize_seq(Some(len))
+    }
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<SeqBuilder, ConvertError> {
+        self.serialize_seq(Some(len))
+    }
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<VariantSeqBuilder, ConvertError> {
+        Ok(VariantSeqBuilder { variant, items: Vec::with_capacity(len) })
+    }
+    fn serialize_map(self, _len: Option<usize>) -> Result<MapBuilder, ConvertError> {
+        Ok(MapBuilder { map: HashMap::new(), key: None })
+    }
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<MapBuilder, ConvertError> {
+        self.serialize_map(Some(len))
+    }
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<VariantMapBuilder, ConvertError> {
+        Ok(VariantMapBuilder { variant, map: HashMap::new() })
+    }
+}
+
+impl ser::SerializeSeq for SeqBuilder {
+    type Ok = RelType;
+    type Error = ConvertError;
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ConvertError> {
+        let index = self.items.len();
+        self.items.push(to_rel(value).map_err(|e| e.within(&format!("[{}]", index)))?);
+        Ok(())
+    }
+    fn end(self) -> Result<RelType, ConvertError> {
+        Ok(RelType::Array(self.items))
+    }
+}
+
+impl ser::SerializeTuple for SeqBuilder {
+    type Ok = RelType;
+    type Error = ConvertError;
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ConvertError> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+    fn end(self) -> Result<RelType, ConvertError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqBuilder {
+    type Ok = RelType;
+    type Error = ConvertError;
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ConvertError> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+    fn end(self) -> Result<RelType, ConvertError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleVariant for VariantSeqBuilder {
+    type Ok = RelType;
+    type Error = ConvertError;
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ConvertError> {
+        let index = self.items.len();
+        self.items.push(to_rel(value).map_err(|e| e.within(&format!(".{}[{}]", self.variant, index)))?);
+        Ok(())
+    }
+    fn end(self) -> Result<RelType, ConvertError> {
+        Ok(tagged(self.variant, RelType::Array(self.items)))
+    }
+}
+
+impl ser::SerializeMap for MapBuilder {
+    type Ok = RelType;
+    type Error = ConvertError;
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), ConvertError> {
+        self.key = Some(match to_rel(key)? {
+            RelType::Str(s) => s,
+            RelType::Int(i) => i.to_string(),
+            RelType::Bool(b) => b.to_string(),
+            other => return Err(ConvertError::new(format!("map keys must be strings or integers, found {}", rel_type_name(&other)))),
+        });
+        Ok(())
+    }
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ConvertError> {
+        let key = self.key.take().ok_or_else(|| ConvertError::new("map value without a key"))?;
+        let value = to_rel(value).map_err(|e| e.within(&format!(".{}", key)))?;
+        self.map.insert(key, value);
+        Ok(())
+    }
+    fn end(self) -> Result<RelType, ConvertError> {
+        Ok(RelType::Object(self.map))
+    }
+}
+
+impl ser::SerializeStruct for MapBuilder {
+    type Ok = RelType;
+    type Error = ConvertError;
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), ConvertError> {
+        self.map.insert(key.to_string(), to_rel(value).map_err(|e| e.within(&format!(".{}", key)))?);
+        Ok(())
+    }
+    fn end(self) -> Result<RelType, ConvertError> {
+        Ok(RelType::Object(self.map))
+    }
+}
+
+impl ser::SerializeStructVariant for VariantMapBuilder {
+    type Ok = RelType;
+    type Error = ConvertError;
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), ConvertError> {
+        let value = to_rel(value).map_err(|e| e.within(&format!(".{}.{}", self.variant, key)))?;
+        self.map.insert(key.to_string(), value);
+        Ok(())
+    }
+    fn end(self) -> Result<RelType, ConvertError> {
+        Ok(tagged(self.variant, RelType::Object(self.map)))
+    }
+}
+
+// --- Deserializer ---
+
+pub struct RelDeserializer<'de>(pub &'de RelType);
+
+fn mismatch(expected: &str, found: &RelType) -> ConvertError {
+    ConvertError::mismatch(expected, found)
+}
+
+struct SeqDe<'de> {
+    iter: std::slice::Iter<'de, RelType>,
+    index: usize,
+}
+
+impl<'de> de::SeqAccess<'de> for SeqDe<'de> {
+    type Error = ConvertError;
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, ConvertError> {
+        let Some(item) = self.iter.next() else { return Ok(None) };
+        let index = self.index;
+        self.index += 1;
+        seed.deserialize(RelDeserializer(item)).map(Some).map_err(|e| e.within(&format!("[{}]", index)))
+    }
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct MapDe<'de> {
+    iter: std::collections::hash_map::Iter<'de, String, RelType>,
+    value: Option<(&'de String, &'de RelType)>,
+}
+
+impl<'de> de::MapAccess<'de> for MapDe<'de> {
+    type Error = ConvertError;
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, ConvertError> {
+        let Some((key, value)) = self.iter.next() else { return Ok(None) };
+        self.value = Some((key, value));
+        seed.deserialize(KeyDe(key)).map(Some)
+    }
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, ConvertError> {
+        let (key, value) = self.value.take().ok_or_else(|| ConvertError::new("map value requested before key"))?;
+        seed.deserialize(RelDeserializer(value)).map_err(|e| e.within(&format!(".{}", key)))
+    }
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+/// Object keys are always strings; integer and bool keys are parsed back out of them.
+struct KeyDe<'de>(&'de str);
+
+macro_rules! parse_key {
+    ($($method:ident => $visit:ident: $t:ty),*) => {$(
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ConvertError> {
+            match self.0.parse::<$t>() {
+                Ok(v) => visitor.$visit(v),
+                Err(_) => Err(ConvertError::new(format!("map key '{}' is not a valid {}", self.0, stringify!($t)))),
+            }
+        }
+    )*};
+}
+
+impl<'de> de::Deserializer<'de> for KeyDe<'de> {
+    type Error = ConvertError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ConvertError> {
+        visitor.visit_borrowed_str(self.0)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, ConvertError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ConvertError> {
+        visitor.visit_enum(self.0.into_deserializer())
+    }
+
+    parse_key!(
+        deserialize_bool => visit_bool: bool,
+        deserialize_i8 => visit_i8: i8,
+        deserialize_i16 => visit_i16: i16,
+        deserialize_i32 => visit_i32: i32,
+        deserialize_i64 => visit_i64: i64,
+        deserialize_u8 => visit_u8: u8,
+        deserialize_u16 => visit_u16: u16,
+        deserialize_u32 => visit_u32: u32,
+        deserialize_u64 => visit_u64: u64
+    );
+
+    serde::forward_to_deserialize_any! {
+        i128 u128 f32 f64 char str string bytes byte_buf option unit unit_struct
+        seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+/// Externally tagged variant: a bare string or a single-key object.
+struct EnumDe<'de> {
+    variant: &'de str,
+    value: Option<&'de RelType>,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumDe<'de> {
+    type Error = ConvertError;
+    type Variant = Self;
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), ConvertError> {
+        let variant = seed.deserialize(KeyDe(self.variant))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for EnumDe<'de> {
+    type Error = ConvertError;
+
+    fn unit_variant(self) -> Result<(), ConvertError> {
+        match self.value {
+            None | Some(RelType::Void) => Ok(()),
+            Some(other) => Err(mismatch("unit variant", other).within(&format!(".{}", self.variant))),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, ConvertError> {
+        let value = self.value.unwrap_or(&RelType::Void);
+        seed.deserialize(RelDeserializer(value)).map_err(|e| e.within(&format!(".{}", self.variant)))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, ConvertError> {
+        let value = self.value.unwrap_or(&RelType::Void);
+        de::Deserializer::deserialize_seq(RelDeserializer(value), visitor).map_err(|e| e.within(&format!(".{}", self.variant)))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, ConvertError> {
+        let value = self.value.unwrap_or(&RelType::Void);
+        de::Deserializer::deserialize_map(RelDeserializer(value), visitor).map_err(|e| e.within(&format!(".{}", self.variant)))
+    }
+}
+
+impl<'de> RelDeserializer<'de> {
+    fn visit_array<V: Visitor<'de>>(items: &'de [RelType], visitor: V) -> Result<V::Value, ConvertError> {
+        let mut seq = SeqDe { iter: items.iter(), index: 0 };
+        let value = visitor.visit_seq(&mut seq)?;
+        if seq.iter.len() > 0 {
+            return Err(ConvertError::new(format!("expected {} elements, found {}", seq.index, items.len())));
+        }
+        Ok(value)
+    }
+
+    fn visit_object<V: Visitor<'de>>(map: &'de HashMap<String, RelType>, visitor: V) -> Result<V::Value, ConvertError> {
+        visitor.visit_map(MapDe { iter: map.iter(), value: None })
+    }
+}
+
+macro_rules! deserialize_int {
+    ($($method:ident),*) => {$(
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ConvertError> {
+            match self.0 {
+                RelType::Int(i) => visitor.visit_i64(*i),
+                other => Err(mismatch("integer", other)),
+            }
+        }
+    )*};
+}
+
+impl<'de> de::Deserializer<'de> for RelDeserializer<'de> {
+    type Error = ConvertError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ConvertError> {
+        match self.0 {
+            RelType::Int(i) => visitor.visit_i64(*i),
+            RelType::Float(f) => visitor.visit_f64(*f),
+            RelType::Bool(b) => visitor.visit_bool(*b),
+            RelType::Str(s) => visitor.visit_borrowed_str(s),
+            RelType::Array(items) => Self::visit_array(items, visitor),
+            RelType::Object(map) => Self::visit_object(map, visitor),
+            RelType::Void => visitor.visit_unit(),
+            other => Err(ConvertError::new(format!("cannot convert {} to a host value", rel_type_name(other)))),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ConvertError> {
+        match self.0 {
+            RelType::Bool(b) => visitor.visit_bool(*b),
+            other => Err(mismatch("boolean", other)),
+        }
+    }
+
+    deserialize_int!(
+        deserialize_i8, deserialize_i16, deserialize_i32, deserialize_i64,
+        deserialize_u8, deserialize_u16, deserialize_u32, deserialize_u64
+    );
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ConvertError> {
+        self.deserialize_f64(visitor)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ConvertError> {
+        match self.0 {
+            RelType::Float(f) => visitor.visit_f64(*f),
+            RelType::Int(i) => visitor.visit_f64(*i as f64),
+            other => Err(mismatch("number", other)),
+        }
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ConvertError> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ConvertError> {
+        match self.0 {
+            RelType::Str(s) => visitor.visit_borrowed_str(s),
+            other => Err(mismatch("string", other)),
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ConvertError> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ConvertError> {
+        match self.0 {
+            RelType::Str(s) => visitor.visit_borrowed_bytes(s.as_bytes()),
+            _ => self.deserialize_seq(visitor),
+        }
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ConvertError> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ConvertError> {
+        match self.0 {
+            RelType::Void => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ConvertError> {
+        match self.0 {
+            RelType::Void => visitor.visit_unit(),
+            other => Err(mismatch("Void", other)),
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, ConvertError> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, ConvertError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ConvertError> {
+        match self.0 {
+            RelType::Array(items) => Self::visit_array(items, visitor),
+            other => Err(mismatch("array", other)),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, ConvertError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(self, _name: &'static str, _len: usize, visitor: V) -> Result<V::Value, ConvertError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ConvertError> {
+        match self.0 {
+            RelType::Object(map) => Self::visit_object(map, visitor),
+            other => Err(mismatch("object", other)),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ConvertError> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ConvertError> {
+        match self.0 {
+            RelType::Str(s) => visitor.visit_enum(EnumDe { variant: s, value: None }),
+            RelType::Object(map) if map.len() == 1 => {
+                let (variant, value) = map.iter().next().expect("map has one entry");
+                visitor.visit_enum(EnumDe { variant, value: Some(value) })
+            }
+            other => Err(mismatch("enum variant (string or single-key object)", other)),
+        }
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ConvertError> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ConvertError> {
+        visitor.visit_unit()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Item {
+        name: String,
+        count: u32,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    enum Class {
+        Warrior,
+        Mage { mana: f64 },
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Player {
+        name: String,
+        level: i64,
+        class: Class,
+        guild: Option<String>,
+        inventory: Vec<Item>,
+        stats: HashMap<String, f32>,
+    }
+
+    #[test]
+    fn test_roundtrip_host_struct() {
+        let player = Player {
+            name: "Ada".into(),
+            level: 7,
+            class: Class::Mage { mana: 12.5 },
+            guild: None,
+            inventory: vec![Item { name: "potion".into(), count: 3 }],
+            stats: HashMap::from([("speed".to_string(), 1.5)]),
+        };
+        let rel = to_rel(&player).unwrap();
+        let RelType::Object(map) = &rel else { panic!("expected object") };
+        assert_eq!(map["guild"], RelType::Void);
+        assert_eq!(map["level"], RelType::Int(7));
+        assert_eq!(from_rel::<Player>(&rel).unwrap(), player);
+        assert_eq!(to_rel(&Class::Warrior).unwrap(), RelType::Str("Warrior".into()));
+    }
+
+    #[test]
+    fn test_errors_report_paths() {
+        let mut item = HashMap::from([("name".to_string(), RelType::Str("gem".into()))]);
+        item.insert("count".into(), RelType::Str("three".into()));
+        let inventory = RelType::Array(vec![RelType::Object(item)]);
+        let rel = RelType::Object(HashMap::from([("inventory".to_string(), inventory)]));
+
+        #[derive(Deserialize, Debug)]
+        #[allow(dead_code)]
+        struct Bag {
+            inventory: Vec<Item>,
+        }
+        let err = from_rel::<Bag>(&rel).unwrap_err();
+        assert_eq!(err.to_string(), "inventory[0].count: expected integer, found String");
+        assert_eq!(err.within("player").to_string(), "player.inventory[0].count: expected integer, found String");
+    }
+}
@@ -0,0 +1,113 @@
+use crate::ast::Type;
+use crate::executor::{AgentPermissions, ExecResult, RelType};
+use crate::natives::convert::{arg, expect_args, native_fault, native_result, permission_denied};
+use crate::natives::{NativeContext, NativeModule};
+
+/// Access to the script's persistent store beyond the static keys of
+/// `Store`/`Load`: computed keys, listing and deletion. Reads need FS_READ,
+/// writes FS_WRITE, like the nodes.
+pub struct StoreModule;
+
+
+pub fn signature(name: &str) -> Option<(Vec<Type>, Type)> {
+    let sig = match name {
+        "Store.Get" => (vec![Type::String], Type::Any),
+        "Store.Set" => (vec![Type::String, Type::Any], Type::Void),
+        "Store.Delete" => (vec![Type::String], Type::Bool),
+        "Store.Keys" => (vec![], Type::Array(vec![Type::String])),
+        _ => return None,
+    };
+    Some(sig)
+}
+
+impl StoreModule {
+    fn call_with(&self, ctx: &NativeContext<'_>, func_name: &str, args: &[RelType]) -> Option<Result<RelType, ExecResult>> {
+        let f = func_name;
+        let permissions = ctx.permissions();
+        let store = ctx.store();
+        let res = match f {
+            "Store.Get" => (|| {
+                if !permissions.allow_fs_read {
+                    return Err(permission_denied(f, "FS_READ"));
+                }
+                expect_args(args, &["key"], f)?;
+                let key = arg::<String>(args, 0, "key", f)?;
+                store.get(&key).map(|v| v.unwrap_or(RelType::Void)).map_err(|e| native_fault(f, e))
+            })(),
+            "Store.Set" => (|| {
+                if !permissions.allow_fs_write {
+                    return Err(permission_denied(f, "FS_WRITE"));
+                }
+                expect_args(args, &["key", "value"], f)?;
+                let key = arg::<String>(args, 0, "key", f)?;
+                store.put(&key, &args[1]).map(|_| RelType::Void).map_err(|e| native_fault(f, e))
+            })(),
+            "Store.Delete" => (|| {
+                if !permissions.allow_fs_write {
+                    return Err(permission_denied(f, "FS_WRITE"));
+                }
+                expect_args(args, &["key"], f)?;
+                let key = arg::<String>(args, 0, "key", f)?;
+                store.delete(&key).map(RelType::Bool).map_err(|e| native_fault(f, e))
+            })(),
+            "Store.Keys" => (|| {
+                if !permissions.allow_fs_read {
+                    return Err(permission_denied(f, "FS_READ"));
+                }
+                expect_args(args, &[], f)?;
+                let keys = store.keys().map_err(|e| native_fault(f, e))?;
+                Ok(RelType::Array(keys.into_iter().map(RelType::Str).collect()))
+            })(),
+            _ => return None,
+        };
+        Some(res)
+    }
+}
+
+impl NativeModule for StoreModule {
+    /// The store belongs to the engine, so these natives only run through
+    /// `handle_in`.
+    fn handle(&self, _func_name: &str, _args: &[RelType], _permissions: &AgentPermissions) -> Option<ExecResult> {
+        None
+    }
+
+    fn handle_in(&self, ctx: &mut NativeContext<'_>, func_name: &str, args: &[RelType]) -> Option<ExecResult> {
+        self.call_with(ctx, func_name, args).map(native_result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::executor::{ExecResult, ExecutionEngine, RelType};
+    use crate::vm::storage::KvStore;
+
+    #[test]
+    fn test_store_natives_share_the_node_store() {
+        let root = std::path::Path::new("target/kv_test/natives");
+        let _ = std::fs::remove_dir_all(root);
+        let mut engine = ExecutionEngine::new();
+        engine.store = KvStore::new(root, "script").unwrap();
+        let program = crate::parser::try_parse(
+            r#"{ Store.Set("level-2", [1, 2.5]); Store.Delete("missing"); Store.Keys() }"#,
+        )
+        .unwrap();
+        match engine.execute(&program) {
+            ExecResult::Fault { msg, .. } => assert_eq!(msg, "Permission Denied: Store.Set requires FS_WRITE"),
+            _ => panic!("expected a permission fault"),
+        }
+
+        engine.permissions.allow_fs_read = true;
+        engine.permissions.allow_fs_write = true;
+        let keys = match engine.execute(&program) {
+            ExecResult::Value(v) => v,
+            _ => panic!("expected a value"),
+        };
+        assert_eq!(keys, RelType::Array(vec![RelType::Str("level-2".into())]));
+        let stored = engine.store.get("level-2").unwrap();
+        assert_eq!(stored, Some(RelType::Array(vec![RelType::Int(1), RelType::Float(2.5)])));
+        match engine.execute(&crate::parser::try_parse(r#"Store.Get("../x")"#).unwrap()) {
+            ExecResult::Fault { msg, .. } => assert!(msg.starts_with("Store.Get: invalid key '../x'"), "{}", msg),
+            _ => panic!("expected an invalid key fault"),
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use crate::ast::Type;
+use crate::executor::{AgentPermissions, ExecResult, RelType};
+use crate::natives::NativeModule;
+use crate::natives::convert::{arg, expect_args, native_fault, native_result};
+
+/// String functions. Indices and lengths count characters, not bytes.
+pub struct StringModule;
+
+/// Largest string `String.Repeat` builds, in bytes.
+const MAX_REPEAT_BYTES: usize = 64 << 20;
+
+/// Parameter and return types, for the TypeChecker.
+pub fn signature(name: &str) -> Option<(Vec<Type>, Type)> {
+    use Type::{Bool, Int, String as Str};
+    let sig = match name {
+        "String.Length" => (vec![Str], Int),
+        "String.Split" => (vec![Str, Str], Type::Array(vec![Str])),
+        "String.Join" => (vec![Type::Array(vec![]), Str], Str),
+        "String.Substring" => (vec![Str, Int, Int], Str),
+        "String.Find" => (vec![Str, Str], Int),
+        "String.Replace" => (vec![Str, Str, Str], Str),
+        "String.Trim" | "String.Upper" | "String.Lower" => (vec![Str], Str),
+        "String.StartsWith" | "String.EndsWith" | "String.Contains" => (vec![Str, Str], Bool),
+        "String.Repeat" => (vec![Str, Int], Str),
+        "String.CharCode" => (vec![Str, Int], Int),
+        "String.FromCharCode" => (vec![Int], Str),
+        "ParseInt" => (vec![Str], Int),
+        "ParseFloat" => (vec![Str], Type::Float),
+        _ => return None,
+    };
+    Some(sig)
+}
+
+/// Char index to byte offset; `idx` may equal the length.
+fn byte_offset(s: &str, idx: usize) -> usize {
+    s.char_indices().nth(idx).map_or(s.len(), |(b, _)| b)
+}
+
+impl StringModule {
+    fn call(&self, func_name: &str, args: &[RelType]) -> Option<Result<RelType, ExecResult>> {
+        let f = func_name;
+        let res = match f {
+            "String.Length" => expect_args(args, &["s"], f)
+                .and_then(|_| arg::<String>(args, 0, "s", f))
+                .map(|s| RelType::Int(s.chars().count() as i64)),
+            "String.Split" => (|| {
+                expect_args(args, &["s", "separator"], f)?;
+                let s = arg::<String>(args, 0, "s", f)?;
+                let sep = arg::<String>(args, 1, "separator", f)?;
+                let parts: Vec<RelType> = if sep.is_empty() {
+                    s.chars().map(|c| RelType::Str(c.to_string())).collect()
+                } else {
+                    s.split(sep.as_str()).map(|p| RelType::Str(p.to_string())).collect()
+                };
+                Ok(RelType::Array(parts))
+            })(),
+            "String.Join" => (|| {
+                expect_args(args, &["items", "separator"], f)?;
+                let items = arg::<Vec<RelType>>(args, 0, "items", f)?;
+                let sep = arg::<String>(args, 1, "separator", f)?;
+                let parts: Vec<String> = items.iter().map(|v| v.to_string()).collect();
+                Ok(RelType::Str(parts.join(&sep)))
+            })(),
+            "String.Substring" => (|| {
+                expect_args(args, &["s", "start", "end"], f)?;
+                let s = arg::<String>(args, 0, "s", f)?;
+                let start = arg::<usize>(args, 1, "start", f)?;
+                let end = arg::<usize>(args, 2, "end", f)?;
+                let len = s.chars().count();
+                if start > end || end > len {
+                    return Err(native_fault(f, format!("range {}..{} out of bounds for length {}", start, end, len)));
+                }
+                Ok(RelType::Str(s[byte_offset(&s, start)..byte_offset(&s, end)].to_string()))
+            })(),
+            "String.Find" => (|| {
+                expect_args(args, &["s", "needle"], f)?;
+                let s = arg::<String>(args, 0, "s", f)?;
+                let needle = arg::<String>(args, 1, "needle", f)?;
+                Ok(RelType::Int(s.find(&needle).map_or(-1, |b| s[..b].chars().count() as i64)))
+            })(),
+            "String.Replace" => (|| {
+                expect_args(args, &["s", "from", "to"], f)?;
+                let s = arg::<String>(args, 0, "s", f)?;
+                let from = arg::<String>(args, 1, "from", f)?;
+                let to = arg::<String>(args, 2, "to", f)?;
+                if from.is_empty() {
+                    return Err(native_fault(f, "argument 2 'from' must not be empty".into()));
+                }
+                Ok(RelType::Str(s.replace(&from, &to)))
+            })(),
+            "String.Trim" | "String.Upper" | "String.Lower" => expect_args(args, &["s"], f)
+                .and_then(|_| arg::<String>(args, 0, "s", f))
+                .map(|s| {
+                    RelType::Str(match f {
+                        "String.Trim" => s.trim().to_string(),
+                        "String.Upper" => s.to_uppercase(),
+                        _ => s.to_lowercase(),
+                    })
+                }),
+            "String.StartsWith" | "String.EndsWith" | "String.Contains" => (|| {
+                expect_args(args, &["s", "part"], f)?;
+                let s = arg::<String>(args, 0, "s", f)?;
+                let part = arg::<String>(args, 1, "part", f)?;
+                Ok(RelType::Bool(match f {
+                    "String.StartsWith" => s.starts_with(&part),
+                    "String.EndsWith" => s.ends_with(&part),
+                    _ => s.contains(&part),
+                }))
+            })(),
+            "String.Repeat" => (|| {
+                expect_args(args, &["s", "count"], f)?;
+                let s = arg::<String>(args, 0, "s", f)?;
+                let count = arg::<usize>(args, 1, "count", f)?;
+                match s.len().checked_mul(count) {
+                    Some(len) if len <= MAX_REPEAT_BYTES => Ok(RelType::Str(s.repeat(count))),
+                    _ => Err(native_fault(f, format!("result would exceed {} bytes", MAX_REPEAT_BYTES))),
+                }
+            })(),
+            "String.CharCode" => (|| {
+                expect_args(args, &["s", "index"], f)?;
+                let s = arg::<String>(args, 0, "s", f)?;
+                let idx = arg::<usize>(args, 1, "index", f)?;
+                match s.chars().nth(idx) {
+                    Some(c) => Ok(RelType::Int(c as i64)),
+                    None => Err(native_fault(f, format!("index {} out of bounds for length {}", idx, s.chars().count()))),
+                }
+            })(),
+            "String.FromCharCode" => (|| {
+                expect_args(args, &["code"], f)?;
+                let code = arg::<u32>(args, 0, "code", f)?;
+                match char::from_u32(code) {
+                    Some(c) => Ok(RelType::Str(c.to_string())),
+                    None => Err(native_fault(f, format!("{} is not a valid character code", code))),
+                }
+            })(),
+            "ParseInt" => expect_args(args, &["s"], f).and_then(|_| arg::<String>(args, 0, "s", f)).and_then(|s| {
+                s.trim().parse::<i64>().map(RelType::Int).map_err(|_| native_fault(f, format!("cannot parse '{}' as Int", s)))
+            }),
+            "ParseFloat" => expect_args(args, &["s"], f).and_then(|_| arg::<String>(args, 0, "s", f)).and_then(|s| {
+                s.trim().parse::<f64>().map(RelType::Float).map_err(|_| native_fault(f, format!("cannot parse '{}' as Float", s)))
+            }),
+            _ => return None,
+        };
+        Some(res)
+    }
+}
+
+impl NativeModule for StringModule {
+    fn handle(&self, func_name: &str, args: &[RelType], _permissions: &AgentPermissions) -> Option<ExecResult> {
+        self.call(func_name, args).map(native_result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(name: &str, args: Vec<RelType>) -> Result<RelType, ExecResult> {
+        StringModule.call(name, &args).expect("unknown function")
+    }
+
+    fn s(v: &str) -> RelType {
+        RelType::Str(v.into())
+    }
+
+    #[test]
+    fn test_char_indexed_operations() {
+        assert_eq!(call("String.Length", vec![s("héllo")]).ok(), Some(RelType::Int(5)));
+        assert_eq!(call("String.Substring", vec![s("héllo"), RelType::Int(1), RelType::Int(3)]).ok(), Some(s("él")));
+        assert_eq!(call("String.Find", vec![s("héllo"), s("llo")]).ok(), Some(RelType::Int(2)));
+        assert_eq!(call("String.Find", vec![s("héllo"), s("x")]).ok(), Some(RelType::Int(-1)));
+        assert_eq!(call("String.CharCode", vec![s("é"), RelType::Int(0)]).ok(), Some(RelType::Int(233)));
+        assert_eq!(
+            call("String.Split", vec![s("a,b,,c"), s(",")]).ok(),
+            Some(RelType::Array(vec![s("a"), s("b"), s(""), s("c")]))
+        );
+        assert_eq!(call("String.Join", vec![RelType::Array(vec![s("a"), RelType::Int(1)]), s("-")]).ok(), Some(s("a-1")));
+        match call("String.Substring", vec![s("abc"), RelType::Int(2), RelType::Int(5)]) {
+            Err(ExecResult::Fault { msg, .. }) => assert_eq!(msg, "String.Substring: range 2..5 out of bounds for length 3"),
+            other => panic!("expected a range fault, got {:?}", other.ok()),
+        }
+        assert_eq!(call("String.Repeat", vec![s("ab"), RelType::Int(3)]).ok(), Some(s("ababab")));
+        match call("String.Repeat", vec![s("ab"), RelType::Int(i64::MAX)]) {
+            Err(ExecResult::Fault { msg, .. }) => assert_eq!(msg, "String.Repeat: result would exceed 67108864 bytes"),
+            other => panic!("expected a size fault, got {:?}", other.ok()),
+        }
+    }
+
+    #[test]
+    fn test_parse_numbers() {
+        assert_eq!(call("ParseInt", vec![s(" 42 ")]).ok(), Some(RelType::Int(42)));
+        assert_eq!(call("ParseFloat", vec![s("2.5")]).ok(), Some(RelType::Float(2.5)));
+        match call("ParseInt", vec![s("4x")]) {
+            Err(ExecResult::Fault { msg, node }) => {
+                assert_eq!(msg, "ParseInt: cannot parse '4x' as Int");
+                assert_eq!(node, "Native::ParseInt");
+            }
+            other => panic!("expected a parse fault, got {:?}", other.ok()),
+        }
+    }
+
+    #[test]
+    fn test_dotted_calls_are_typed() {
+        let ast = crate::parser::try_parse("let n = String.Length(\"abc\");\nlet bad = String.Repeat(n, \"x\");").unwrap();
+        let mut checker = crate::optimizer::TypeChecker::new();
+        checker.check(&ast).unwrap();
+        assert_eq!(
+            checker.errors,
+            vec![
+                "TypeError: 'String.Repeat' argument 1 expects String, found Int".to_string(),
+                "TypeError: 'String.Repeat' argument 2 expects Int, found String".to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,416 @@
+use crate::ast::Type;
+use crate::executor::{AgentPermissions, ExecResult, ExecutionEngine, RelType};
+use crate::natives::NativeModule;
+use crate::natives::convert::{arg, expect_args, native_fault, native_result, permission_denied, rel_type_name};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Mustache-style text templates rendered against script values.
+///
+/// Supported tags: `{{name}}` and dotted `{{a.b}}` lookups, `{{.}}` for the
+/// current item, `{{{name}}}` / `{{& name}}` for unescaped output, sections
+/// `{{#name}}…{{/name}}` (repeated for each array item, entered once for any
+/// other truthy value), inverted sections `{{^name}}…{{/name}}`, comments
+/// `{{! …}}` and partials `{{> name}}`. Void, `false`, `""` and `[]` are
+/// falsy. A tag alone on its line takes the whole line with it, so sections
+/// don't leave blank lines in generated files.
+///
+/// Missing names render as empty text; every other error names the line of
+/// the template (and partial) it comes from.
+pub struct TemplateModule;
+
+
+pub fn signature(name: &str) -> Option<(Vec<Type>, Type)> {
+    use Type::{Any, String as Str};
+    let sig = match name {
+        "Template.Render" => (vec![Str, Any], Str),
+        "Template.RenderWith" => (vec![Str, Any, Any, Str], Str),
+        "Template.RenderFile" => (vec![Str, Any, Str], Str),
+        _ => return None,
+    };
+    Some(sig)
+}
+
+/// How `{{name}}` output is escaped; `{{{name}}}` is never escaped.
+#[derive(Clone, Copy)]
+pub enum Escape {
+    Html,
+    /// Escaped for use inside a JSON string literal.
+    Json,
+    None,
+}
+
+impl Escape {
+    fn parse(mode: &str) -> Result<Self, String> {
+        match mode {
+            "html" => Ok(Escape::Html),
+            "json" => Ok(Escape::Json),
+            "none" => Ok(Escape::None),
+            _ => Err(format!("escape mode must be \"html\", \"json\" or \"none\", got \"{}\"", mode)),
+        }
+    }
+
+    fn apply(self, text: &str, out: &mut String) {
+        match self {
+            Escape::None => out.push_str(text),
+            Escape::Json => {
+                let quoted = serde_json::Value::String(text.to_string()).to_string();
+                out.push_str(&quoted[1..quoted.len() - 1]);
+            }
+            Escape::Html => {
+                for c in text.chars() {
+                    match c {
+                        '&' => out.push_str("&amp;"),
+                        '<' => out.push_str("&lt;"),
+                        '>' => out.push_str("&gt;"),
+                        '"' => out.push_str("&quot;"),
+                        '\'' => out.push_str("&#39;"),
+                        _ => out.push(c),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Where `{{> name}}` finds its template text.
+pub enum Partials {
+    Map(HashMap<String, String>),
+    /// Files next to the template; names without an extension get `ext`.
+    Dir { dir: PathBuf, ext: Option<String> },
+}
+
+impl Partials {
+    fn load(&self, name: &str) -> Result<String, String> {
+        match self {
+            Partials::Map(map) => map.get(name).cloned().ok_or_else(|| format!("unknown partial '{}'", name)),
+            Partials::Dir { dir, ext } => {
+                let mut path = dir.join(name);
+                if let (None, Some(ext)) = (path.extension(), ext) {
+                    path.set_extension(ext);
+                }
+                let safe_path = ExecutionEngine::validate_fs_path(&path.to_string_lossy())
+                    .map_err(|e| format!("partial '{}': Security: {}", name, e))?;
+                std::fs::read_to_string(safe_path).map_err(|e| format!("partial '{}': {}", name, e))
+            }
+        }
+    }
+}
+
+enum Part {
+    Text(String),
+    Var { name: String, raw: bool, line: usize },
+    Section { name: String, inverted: bool, body: Vec<Part> },
+    Partial { name: String, indent: String, line: usize },
+}
+
+enum Tag {
+    Var { raw: bool },
+    Open { inverted: bool },
+    Close,
+    Comment,
+    Partial,
+}
+
+/// Parses template text into a tree of parts, checking that sections nest.
+fn parse(src: &str) -> Result<Vec<Part>, String> {
+    // Each open section: name, line, inverted, and the parts before it.
+    let mut stack: Vec<(String, usize, bool, Vec<Part>)> = Vec::new();
+    let mut parts = Vec::new();
+    let (mut pos, mut line, mut counted) = (0, 1, 0);
+
+    while let Some(offset) = src[pos..].find("{{") {
+        let start = pos + offset;
+        line += src[counted..start].matches('\n').count();
+        counted = start;
+
+        let (tag, name, end) = if src[start..].starts_with("{{{") {
+            let close = src[start + 3..].find("}}}").ok_or_else(|| format!("line {}: unclosed tag", line))?;
+            (Tag::Var { raw: true }, src[start + 3..start + 3 + close].trim(), start + 3 + close + 3)
+        } else {
+            let close = src[start + 2..].find("}}").ok_or_else(|| format!("line {}: unclosed tag", line))?;
+            let body = src[start + 2..start + 2 + close].trim();
+            let (tag, name) = match body.chars().next() {
+                Some('#') => (Tag::Open { inverted: false }, &body[1..]),
+                Some('^') => (Tag::Open { inverted: true }, &body[1..]),
+                Some('/') => (Tag::Close, &body[1..]),
+                Some('!') => (Tag::Comment, ""),
+                Some('>') => (Tag::Partial, &body[1..]),
+                Some('&') => (Tag::Var { raw: true }, &body[1..]),
+                Some('=') => return Err(format!("line {}: changing delimiters is not supported", line)),
+                _ => (Tag::Var { raw: false }, body),
+            };
+            (tag, name.trim(), start + 2 + close + 2)
+        };
+        if name.is_empty() && !matches!(tag, Tag::Comment) {
+            return Err(format!("line {}: empty tag", line));
+        }
+
+        // A non-output tag alone on its line swallows the line.
+        let line_start = src[..start].rfind('\n').map_or(0, |i| i + 1);
+        let rest = &src[end..];
+        let line_end = rest.find('\n').map_or(src.len(), |i| end + i + 1);
+        let standalone = !matches!(tag, Tag::Var { .. })
+            && line_start >= pos
+            && src[line_start..start].chars().all(|c| c == ' ' || c == '\t')
+            && src[end..line_end].trim().is_empty();
+        let (text_end, indent) = if standalone { (line_start, &src[line_start..start]) } else { (start, "") };
+        if text_end > pos {
+            parts.push(Part::Text(src[pos..text_end].to_string()));
+        }
+        pos = if standalone { line_end } else { end };
+
+        match tag {
+            Tag::Var { raw } => parts.push(Part::Var { name: name.to_string(), raw, line }),
+            Tag::Comment => {}
+            Tag::Partial => parts.push(Part::Partial { name: name.to_string(), indent: indent.to_string(), line }),
+            Tag::Open { inverted } => stack.push((name.to_string(), line, inverted, std::mem::take(&mut parts))),
+            Tag::Close => {
+                let Some((open, open_line, inverted, outer)) = stack.pop() else {
+                    return Err(format!("line {}: '{{{{/{}}}}}' has no open section", line, name));
+                };
+                if open != name {
+                    return Err(format!(
+                        "line {}: '{{{{/{}}}}}' closes section '{}' opened on line {}",
+                        line, name, open, open_line
+                    ));
+                }
+                let body = std::mem::replace(&mut parts, outer);
+                parts.push(Part::Section { name: open, inverted, body });
+            }
+        }
+    }
+    if let Some((name, open_line, ..)) = stack.pop() {
+        return Err(format!("line {}: section '{}' is never closed", open_line, name));
+    }
+    if pos < src.len() {
+        parts.push(Part::Text(src[pos..].to_string()));
+    }
+    Ok(parts)
+}
+
+fn truthy(value: &RelType) -> bool {
+    match value {
+        RelType::Void | RelType::Bool(false) => false,
+        RelType::Str(s) => !s.is_empty(),
+        RelType::Array(items) => !items.is_empty(),
+        _ => true,
+    }
+}
+
+/// Resolves a (dotted) name: the first segment is looked up from the
+/// innermost context outwards, the rest inside whatever it found.
+fn lookup(stack: &[&RelType], name: &str) -> RelType {
+    if name == "." {
+        return stack.last().map_or(RelType::Void, |v| (*v).clone());
+    }
+    let mut segments = name.split('.');
+    let first = segments.next().unwrap_or_default();
+    let found = stack.iter().rev().find_map(|ctx| match ctx {
+        RelType::Object(map) => map.get(first),
+        _ => None,
+    });
+    let mut value = match found {
+        Some(v) => v,
+        None => return RelType::Void,
+    };
+    for segment in segments {
+        value = match value {
+            RelType::Object(map) => match map.get(segment) {
+                Some(v) => v,
+                None => return RelType::Void,
+            },
+            _ => return RelType::Void,
+        };
+    }
+    value.clone()
+}
+
+const MAX_PARTIAL_DEPTH: usize = 32;
+
+struct Renderer<'a> {
+    escape: Escape,
+    partials: Option<&'a Partials>,
+}
+
+impl Renderer<'_> {
+    fn render(&self, parts: &[Part], stack: &mut Vec<RelType>, depth: usize, out: &mut String) -> Result<(), String> {
+        for part in parts {
+            match part {
+                Part::Text(text) => out.push_str(text),
+                Part::Var { name, raw, line } => {
+                    let value = lookup(&stack.iter().collect::<Vec<_>>(), name);
+                    let text = match &value {
+                        RelType::Void => continue,
+                        RelType::Str(s) => s.clone(),
+                        RelType::Int(_) | RelType::Float(_) | RelType::Bool(_) => value.to_string(),
+                        other => {
+                            return Err(format!(
+                                "line {}: '{}' is {} and cannot be printed; use a section",
+                                line,
+                                name,
+                                rel_type_name(other)
+                            ));
+                        }
+                    };
+                    if *raw { out.push_str(&text) } else { self.escape.apply(&text, out) }
+                }
+                Part::Section { name, inverted, body } => {
+                    let value = lookup(&stack.iter().collect::<Vec<_>>(), name);
+                    match (*inverted, value) {
+                        (true, v) if !truthy(&v) => self.render(body, stack, depth, out)?,
+                        (false, RelType::Array(items)) => {
+                            for item in items {
+                                stack.push(item);
+                                let res = self.render(body, stack, depth, out);
+                                stack.pop();
+                                res?;
+                            }
+                        }
+                        (false, v) if truthy(&v) => {
+                            stack.push(v);
+                            let res = self.render(body, stack, depth, out);
+                            stack.pop();
+                            res?;
+                        }
+                        _ => {}
+                    }
+                }
+                Part::Partial { name, indent, line } => {
+                    let partials = self.partials.ok_or_else(|| format!("line {}: unknown partial '{}'", line, name))?;
+                    if depth >= MAX_PARTIAL_DEPTH {
+                        return Err(format!("line {}: partials nested deeper than {}", line, MAX_PARTIAL_DEPTH));
+                    }
+                    let src = partials.load(name).map_err(|e| format!("line {}: {}", line, e))?;
+                    let src = if indent.is_empty() {
+                        src
+                    } else {
+                        src.split_inclusive('\n').map(|l| format!("{}{}", indent, l)).collect()
+                    };
+                    let parts = parse(&src).map_err(|e| format!("partial '{}': {}", name, e))?;
+                    self.render(&parts, stack, depth + 1, out).map_err(|e| {
+                        if e.starts_with("partial '") { e } else { format!("partial '{}': {}", name, e) }
+                    })?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Renders `template` against `context`.
+pub fn render(template: &str, context: &RelType, partials: Option<&Partials>, escape: Escape) -> Result<String, String> {
+    let parts = parse(template)?;
+    let mut out = String::new();
+    Renderer { escape, partials }.render(&parts, &mut vec![context.clone()], 0, &mut out)?;
+    Ok(out)
+}
+
+impl TemplateModule {
+    fn call(&self, func_name: &str, args: &[RelType], permissions: &AgentPermissions) -> Option<Result<RelType, ExecResult>> {
+        let f = func_name;
+        let res = match f {
+            "Template.Render" => (|| {
+                expect_args(args, &["template", "context"], f)?;
+                let template = arg::<String>(args, 0, "template", f)?;
+                render(&template, &args[1], None, Escape::Html).map(RelType::Str).map_err(|e| native_fault(f, e))
+            })(),
+            "Template.RenderWith" => (|| {
+                expect_args(args, &["template", "context", "partials", "escape"], f)?;
+                let template = arg::<String>(args, 0, "template", f)?;
+                let partials = Partials::Map(arg::<HashMap<String, String>>(args, 2, "partials", f)?);
+                let escape = Escape::parse(&arg::<String>(args, 3, "escape", f)?).map_err(|e| native_fault(f, e))?;
+                render(&template, &args[1], Some(&partials), escape).map(RelType::Str).map_err(|e| native_fault(f, e))
+            })(),
+            "Template.RenderFile" => (|| {
+                if !permissions.allow_fs_read {
+                    return Err(permission_denied(f, "FS_READ"));
+                }
+                expect_args(args, &["path", "context", "escape"], f)?;
+                let path = arg::<String>(args, 0, "path", f)?;
+                let escape = Escape::parse(&arg::<String>(args, 2, "escape", f)?).map_err(|e| native_fault(f, e))?;
+                let safe_path = ExecutionEngine::validate_fs_path(&path).map_err(|e| native_fault(f, format!("Security: {}", e)))?;
+                let template = std::fs::read_to_string(&safe_path).map_err(|e| native_fault(f, format!("File read error: {}", e)))?;
+                let partials = Partials::Dir {
+                    dir: safe_path.parent().map_or_else(|| Path::new(".").to_path_buf(), Path::to_path_buf),
+                    ext: safe_path.extension().map(|e| e.to_string_lossy().into_owned()),
+                };
+                render(&template, &args[1], Some(&partials), escape)
+                    .map(RelType::Str)
+                    .map_err(|e| native_fault(f, format!("{}: {}", path, e)))
+            })(),
+            _ => return None,
+        };
+        Some(res)
+    }
+}
+
+impl NativeModule for TemplateModule {
+    fn handle(&self, func_name: &str, args: &[RelType], permissions: &AgentPermissions) -> Option<ExecResult> {
+        self.call(func_name, args, permissions).map(native_result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx(json: &str) -> RelType {
+        crate::natives::fs::fs_parse_json(json).unwrap()
+    }
+
+    #[test]
+    fn test_sections_partials_and_escaping() {
+        let context = ctx(r#"{"title": "Q3 <draft>", "owner": {"name": "Ada"}, "items": [{"sku": "A1", "qty": 2}, {"sku": "B7", "qty": 0, "discontinued": true}], "empty": []}"#);
+        let template = "# {{title}} by {{owner.name}}\n{{#items}}\n- {{sku}}: {{qty}}{{#discontinued}} (discontinued){{/discontinued}}\n{{/items}}\n{{^empty}}\nnothing else\n{{/empty}}\n{{! footer }}\n{{{title}}}\n";
+        assert_eq!(
+            render(template, &context, None, Escape::Html).unwrap(),
+            "# Q3 &lt;draft&gt; by Ada\n- A1: 2\n- B7: 0 (discontinued)\nnothing else\nQ3 <draft>\n"
+        );
+
+        let partials = Partials::Map(HashMap::from([("row".to_string(), "{{sku}} = \"{{qty}}\"\n".to_string())]));
+        let template = "[stock]\n{{#items}}\n  {{> row}}\n{{/items}}\n";
+        assert_eq!(
+            render(template, &context, Some(&partials), Escape::None).unwrap(),
+            "[stock]\n  A1 = \"2\"\n  B7 = \"0\"\n"
+        );
+        assert_eq!(render("\"{{title}}\"", &ctx(r#"{"title": "a\"b\nc"}"#), None, Escape::Json).unwrap(), r#""a\"b\nc""#);
+    }
+
+    #[test]
+    fn test_errors_report_template_lines() {
+        let context = ctx(r#"{"items": [1, 2]}"#);
+        let err = |template: &str| render(template, &context, None, Escape::Html).err().unwrap();
+        assert_eq!(err("a\n{{#items}}\nb\n"), "line 2: section 'items' is never closed");
+        assert_eq!(err("{{#a}}\n{{/b}}\n"), "line 2: '{{/b}}' closes section 'a' opened on line 1");
+        assert_eq!(err("ok\n\n{{name"), "line 3: unclosed tag");
+        assert_eq!(err("x\n{{items}}"), "line 2: 'items' is Array and cannot be printed; use a section");
+        assert_eq!(err("{{> header}}"), "line 1: unknown partial 'header'");
+
+        let partials = Partials::Map(HashMap::from([("loop".to_string(), "{{> loop}}".to_string())]));
+        let res = render("{{> loop}}", &context, Some(&partials), Escape::Html);
+        assert!(res.err().unwrap().ends_with("partials nested deeper than 32"));
+    }
+
+    #[test]
+    fn test_render_file_loads_sibling_partials() {
+        let dir = std::path::Path::new("target/template_test");
+        std::fs::create_dir_all(dir).unwrap();
+        std::fs::write(dir.join("page.tpl"), "{{> head}}\nbody {{n}}\n").unwrap();
+        std::fs::write(dir.join("head.tpl"), "head {{n}}\n{{#n}}\n").unwrap();
+        let args = [RelType::Str("target/template_test/page.tpl".into()), ctx(r#"{"n": 1}"#), RelType::Str("none".into())];
+        let denied = TemplateModule.call("Template.RenderFile", &args, &AgentPermissions::default());
+        let perms = AgentPermissions { allow_fs_read: true, ..AgentPermissions::default() };
+        let res = TemplateModule.call("Template.RenderFile", &args, &perms);
+        std::fs::remove_dir_all(dir).unwrap();
+
+        assert!(matches!(denied, Some(Err(ExecResult::Fault { msg, .. })) if msg.starts_with("Permission Denied")));
+        match res {
+            Some(Err(ExecResult::Fault { msg, .. })) => assert_eq!(
+                msg,
+                "Template.RenderFile: target/template_test/page.tpl: partial 'head': line 2: section 'n' is never closed"
+            ),
+            _ => panic!("expected a fault"),
+        }
+    }
+}
@@ -0,0 +1,334 @@
+use crate::ast::Type;
+use crate::executor::{AgentPermissions, ExecResult, RelType};
+use crate::natives::NativeModule;
+use crate::natives::convert::{arg, expect_args, native_fault, native_result, rel_type_name};
+use crate::numeric::BinOp;
+use glam::{Mat4, Quat, Vec2, Vec3, Vec4};
+use std::ops::{Add, Div, Mul, Sub};
+
+/// Constructors and functions for the `Vec2`/`Vec3`/`Vec4`/`Quat`/`Mat4`
+/// value types. Angles are in degrees. Matrices convert to and from arrays
+/// in column-major order, the layout the renderer uploads.
+pub struct VectorModule;
+
+/// Parameter and return types, for the TypeChecker. Vector arguments are `Any`.
+pub fn signature(name: &str) -> Option<(Vec<Type>, Type)> {
+    use Type::{Any, Float};
+    let sig = match name {
+        "Vec.Dot" => (vec![Any, Any], Float),
+        "Vec.Cross" => (vec![Any, Any], Any),
+        "Vec.Length" => (vec![Any], Float),
+        "Vec.Normalize" => (vec![Any], Any),
+        "Vec.ToArray" | "Mat4.ToArray" => (vec![Any], Type::Array(vec![Float])),
+        "Mat4.Identity" | "Quat.Identity" => (vec![], Any),
+        "Mat4.LookAt" => (vec![Any, Any, Any], Any),
+        "Mat4.Perspective" => (vec![Float, Float, Float, Float], Any),
+        "Mat4.Translate" | "Mat4.Scale" | "Mat4.Inverse" | "Mat4.Transpose" => (vec![Any], Any),
+        "Quat.FromAxisAngle" => (vec![Any, Float], Any),
+        _ => return None,
+    };
+    Some(sig)
+}
+
+fn scalar(v: &RelType) -> Option<f32> {
+    match v {
+        RelType::Int(i) => Some(*i as f32),
+        RelType::Float(f) => Some(*f as f32),
+        _ => None,
+    }
+}
+
+fn arith<T>(op: BinOp, a: T, b: T) -> Option<T>
+where
+    T: Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>,
+{
+    Some(match op {
+        BinOp::Add => a + b,
+        BinOp::Sub => a - b,
+        BinOp::Mul => a * b,
+        BinOp::Div => a / b,
+    })
+}
+
+/// Arithmetic on vector values, used by `numeric::arith`. Vectors combine
+/// component-wise with vectors of the same size and with numbers;
+/// `Mat4 * Mat4`, `Mat4 * Vec4`, `Mat4 * Vec3` (as a point), `Quat * Quat`
+/// and `Quat * Vec3` follow glam. `None` when the operands don't apply.
+pub(crate) fn binary_op(op: BinOp, l: &RelType, r: &RelType) -> Option<RelType> {
+    use RelType as R;
+    let res = match (l, r) {
+        (R::Vec2(a), R::Vec2(b)) => R::Vec2(arith(op, *a, *b)?),
+        (R::Vec3(a), R::Vec3(b)) => R::Vec3(arith(op, *a, *b)?),
+        (R::Vec4(a), R::Vec4(b)) => R::Vec4(arith(op, *a, *b)?),
+        (R::Vec2(a), s) if scalar(s).is_some() => R::Vec2(arith(op, *a, Vec2::splat(scalar(s)?))?),
+        (R::Vec3(a), s) if scalar(s).is_some() => R::Vec3(arith(op, *a, Vec3::splat(scalar(s)?))?),
+        (R::Vec4(a), s) if scalar(s).is_some() => R::Vec4(arith(op, *a, Vec4::splat(scalar(s)?))?),
+        (s, R::Vec2(b)) if scalar(s).is_some() => R::Vec2(arith(op, Vec2::splat(scalar(s)?), *b)?),
+        (s, R::Vec3(b)) if scalar(s).is_some() => R::Vec3(arith(op, Vec3::splat(scalar(s)?), *b)?),
+        (s, R::Vec4(b)) if scalar(s).is_some() => R::Vec4(arith(op, Vec4::splat(scalar(s)?), *b)?),
+        (R::Mat4(a), R::Mat4(b)) => match op {
+            BinOp::Add => R::Mat4(*a + *b),
+            BinOp::Sub => R::Mat4(*a - *b),
+            BinOp::Mul => R::Mat4(*a * *b),
+            BinOp::Div => return None,
+        },
+        (R::Mat4(a), R::Vec4(b)) if op == BinOp::Mul => R::Vec4(*a * *b),
+        (R::Mat4(a), R::Vec3(b)) if op == BinOp::Mul => R::Vec3(a.transform_point3(*b)),
+        (R::Mat4(a), s) if op == BinOp::Mul => R::Mat4(*a * scalar(s)?),
+        (s, R::Mat4(b)) if op == BinOp::Mul => R::Mat4(*b * scalar(s)?),
+        (R::Quat(a), R::Quat(b)) if op == BinOp::Mul => R::Quat(*a * *b),
+        (R::Quat(a), R::Vec3(b)) if op == BinOp::Mul => R::Vec3(*a * *b),
+        _ => return None,
+    };
+    Some(res)
+}
+
+fn components(v: &RelType) -> Option<Vec<f32>> {
+    match v {
+        RelType::Vec2(v) => Some(v.to_array().to_vec()),
+        RelType::Vec3(v) => Some(v.to_array().to_vec()),
+        RelType::Vec4(v) => Some(v.to_array().to_vec()),
+        RelType::Quat(q) => Some(q.to_array().to_vec()),
+        RelType::Mat4(m) => Some(m.to_cols_array().to_vec()),
+        _ => None,
+    }
+}
+
+/// Reads a swizzle such as `v.x`, `v.zy` or `v.xyzw`: one component gives a
+/// Float, two to four give a vector of that size.
+pub(crate) fn swizzle(v: &RelType, prop: &str) -> Option<Result<RelType, String>> {
+    let comps = match v {
+        RelType::Vec2(_) | RelType::Vec3(_) | RelType::Vec4(_) | RelType::Quat(_) => components(v)?,
+        RelType::Mat4(_) => return Some(Err(format!("Mat4 has no component '{}'", prop))),
+        _ => return None,
+    };
+    let picked: Option<Vec<f32>> = prop
+        .chars()
+        .map(|c| "xyzw".find(c).and_then(|i| comps.get(i).copied()))
+        .collect();
+    let res = match picked.as_deref() {
+        Some([x]) => Ok(RelType::Float(*x as f64)),
+        Some([x, y]) => Ok(RelType::Vec2(Vec2::new(*x, *y))),
+        Some([x, y, z]) => Ok(RelType::Vec3(Vec3::new(*x, *y, *z))),
+        Some([x, y, z, w]) => Ok(RelType::Vec4(Vec4::new(*x, *y, *z, *w))),
+        _ => Err(format!("{} has no component '{}'", rel_type_name(v), prop)),
+    };
+    Some(res)
+}
+
+/// Gathers constructor arguments: numbers, vectors and number arrays are
+/// flattened, so `Vec4(v3, 1)` and `Vec3([1, 2, 3])` both work.
+fn flatten(args: &[RelType], want: usize, func: &str) -> Result<Vec<f32>, ExecResult> {
+    let mut out = Vec::with_capacity(want);
+    for (i, a) in args.iter().enumerate() {
+        match a {
+            RelType::Int(_) | RelType::Float(_) => out.push(arg::<f32>(args, i, "component", func)?),
+            RelType::Array(_) => out.extend(arg::<Vec<f32>>(args, i, "components", func)?),
+            RelType::Vec2(_) | RelType::Vec3(_) | RelType::Vec4(_) => out.extend(components(a).unwrap_or_default()),
+            other => {
+                return Err(native_fault(func, format!("argument {} expected Number, vector or Array, found {}", i + 1, rel_type_name(other))));
+            }
+        }
+    }
+    if out.len() != want {
+        return Err(native_fault(func, format!("expected {} components, got {}", want, out.len())));
+    }
+    Ok(out)
+}
+
+impl VectorModule {
+    fn call(&self, f: &str, args: &[RelType]) -> Option<Result<RelType, ExecResult>> {
+        let res = match f {
+            "Vec2" => flatten(args, 2, f).map(|c| RelType::Vec2(Vec2::from_slice(&c))),
+            "Vec3" => flatten(args, 3, f).map(|c| RelType::Vec3(Vec3::from_slice(&c))),
+            "Vec4" => flatten(args, 4, f).map(|c| RelType::Vec4(Vec4::from_slice(&c))),
+            "Quat" => flatten(args, 4, f).map(|c| RelType::Quat(Quat::from_slice(&c))),
+            "Mat4" => expect_args(args, &["columns"], f).and_then(|_| arg::<Mat4>(args, 0, "columns", f)).map(RelType::Mat4),
+            "Vec.ToArray" | "Mat4.ToArray" => expect_args(args, &["v"], f).and_then(|_| {
+                components(&args[0])
+                    .map(|c| RelType::Array(c.into_iter().map(|x| RelType::Float(x as f64)).collect()))
+                    .ok_or_else(|| native_fault(f, format!("argument 1 'v' expected a vector, found {}", rel_type_name(&args[0]))))
+            }),
+            "Vec.Dot" => expect_args(args, &["a", "b"], f).and_then(|_| match (&args[0], &args[1]) {
+                (RelType::Vec2(a), RelType::Vec2(b)) => Ok(a.dot(*b)),
+                (RelType::Vec3(a), RelType::Vec3(b)) => Ok(a.dot(*b)),
+                (RelType::Vec4(a), RelType::Vec4(b)) => Ok(a.dot(*b)),
+                (RelType::Quat(a), RelType::Quat(b)) => Ok(a.dot(*b)),
+                (a, b) => Err(native_fault(f, format!("cannot take the dot product of {} and {}", rel_type_name(a), rel_type_name(b)))),
+            })
+            .map(|d| RelType::Float(d as f64)),
+            "Vec.Cross" => (|| {
+                expect_args(args, &["a", "b"], f)?;
+                let a = arg::<Vec3>(args, 0, "a", f)?;
+                let b = arg::<Vec3>(args, 1, "b", f)?;
+                Ok(RelType::Vec3(a.cross(b)))
+            })(),
+            "Vec.Length" => expect_args(args, &["v"], f).and_then(|_| match &args[0] {
+                RelType::Vec2(v) => Ok(RelType::Float(v.length() as f64)),
+                RelType::Vec3(v) => Ok(RelType::Float(v.length() as f64)),
+                RelType::Vec4(v) => Ok(RelType::Float(v.length() as f64)),
+                RelType::Quat(q) => Ok(RelType::Float(q.length() as f64)),
+                other => Err(native_fault(f, format!("argument 1 'v' expected a vector, found {}", rel_type_name(other)))),
+            }),
+            "Vec.Normalize" => expect_args(args, &["v"], f).and_then(|_| {
+                let normalized = match &args[0] {
+                    RelType::Vec2(v) => v.try_normalize().map(RelType::Vec2),
+                    RelType::Vec3(v) => v.try_normalize().map(RelType::Vec3),
+                    RelType::Vec4(v) => v.try_normalize().map(RelType::Vec4),
+                    RelType::Quat(q) if q.length_squared() > 0.0 => Some(RelType::Quat(q.normalize())),
+                    RelType::Quat(_) => None,
+                    other => return Err(native_fault(f, format!("argument 1 'v' expected a vector, found {}", rel_type_name(other)))),
+                };
+                normalized.ok_or_else(|| native_fault(f, "cannot normalize a zero-length vector".into()))
+            }),
+            "Mat4.Identity" => expect_args(args, &[], f).map(|_| RelType::Mat4(Mat4::IDENTITY)),
+            "Quat.Identity" => expect_args(args, &[], f).map(|_| RelType::Quat(Quat::IDENTITY)),
+            "Mat4.LookAt" => (|| {
+                expect_args(args, &["eye", "target", "up"], f)?;
+                let eye = arg::<Vec3>(args, 0, "eye", f)?;
+                let target = arg::<Vec3>(args, 1, "target", f)?;
+                let up = arg::<Vec3>(args, 2, "up", f)?;
+                Ok(RelType::Mat4(Mat4::look_at_rh(eye, target, up)))
+            })(),
+            "Mat4.Perspective" => (|| {
+                expect_args(args, &["fov", "aspect", "near", "far"], f)?;
+                let fov = arg::<f32>(args, 0, "fov", f)?;
+                let aspect = arg::<f32>(args, 1, "aspect", f)?;
+                let near = arg::<f32>(args, 2, "near", f)?;
+                let far = arg::<f32>(args, 3, "far", f)?;
+                if fov <= 0.0 || aspect <= 0.0 || near <= 0.0 || far <= near {
+                    return Err(native_fault(f, "expects fov, aspect and near > 0 and far > near".into()));
+                }
+                Ok(RelType::Mat4(Mat4::perspective_rh(fov.to_radians(), aspect, near, far)))
+            })(),
+            "Mat4.Translate" => expect_args(args, &["offset"], f)
+                .and_then(|_| arg::<Vec3>(args, 0, "offset", f))
+                .map(|v| RelType::Mat4(Mat4::from_translation(v))),
+            "Mat4.Scale" => expect_args(args, &["scale"], f)
+                .and_then(|_| arg::<Vec3>(args, 0, "scale", f))
+                .map(|v| RelType::Mat4(Mat4::from_scale(v))),
+            // `Mat4.Rotate(quat)` or `Mat4.Rotate(axis, degrees)`.
+            "Mat4.Rotate" => match args {
+                [RelType::Quat(q)] => Ok(RelType::Mat4(Mat4::from_quat(q.normalize()))),
+                _ => axis_angle(args, f).map(|q| RelType::Mat4(Mat4::from_quat(q))),
+            },
+            "Quat.FromAxisAngle" => axis_angle(args, f).map(RelType::Quat),
+            "Mat4.Inverse" | "Mat4.Transpose" => expect_args(args, &["m"], f).and_then(|_| arg::<Mat4>(args, 0, "m", f)).and_then(|m| {
+                if f == "Mat4.Transpose" {
+                    Ok(RelType::Mat4(m.transpose()))
+                } else if m.determinant() == 0.0 {
+                    Err(native_fault(f, "matrix is not invertible".into()))
+                } else {
+                    Ok(RelType::Mat4(m.inverse()))
+                }
+            }),
+            _ => return None,
+        };
+        Some(res)
+    }
+}
+
+fn axis_angle(args: &[RelType], f: &str) -> Result<Quat, ExecResult> {
+    expect_args(args, &["axis", "degrees"], f)?;
+    let axis = arg::<Vec3>(args, 0, "axis", f)?;
+    let degrees = arg::<f32>(args, 1, "degrees", f)?;
+    let axis = axis.try_normalize().ok_or_else(|| native_fault(f, "axis must not be zero".into()))?;
+    Ok(Quat::from_axis_angle(axis, degrees.to_radians()))
+}
+
+impl NativeModule for VectorModule {
+    fn handle(&self, func_name: &str, args: &[RelType], _permissions: &AgentPermissions) -> Option<ExecResult> {
+        self.call(func_name, args).map(native_result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::ExecutionEngine;
+
+    fn run(src: &str) -> ExecResult {
+        ExecutionEngine::new().execute(&crate::parser::try_parse(src).unwrap())
+    }
+
+    #[test]
+    fn test_operators_and_swizzles() {
+        let src = "let a = Vec3(1, 2, 3);\nlet b = Vec3([4, 5, 6]);\n\
+            [a + b, b - a, a * 2, 2.0 * a, (a * b).zyx, Vec4(a.xy, 0, 1), a.z, Vec.Dot(a, b), Vec.Cross(a, b)]";
+        let expected = RelType::Array(vec![
+            RelType::Vec3(Vec3::new(5.0, 7.0, 9.0)),
+            RelType::Vec3(Vec3::splat(3.0)),
+            RelType::Vec3(Vec3::new(2.0, 4.0, 6.0)),
+            RelType::Vec3(Vec3::new(2.0, 4.0, 6.0)),
+            RelType::Vec3(Vec3::new(18.0, 10.0, 4.0)),
+            RelType::Vec4(Vec4::new(1.0, 2.0, 0.0, 1.0)),
+            RelType::Float(3.0),
+            RelType::Float(32.0),
+            RelType::Vec3(Vec3::new(-3.0, 6.0, -3.0)),
+        ]);
+        assert!(matches!(run(src), ExecResult::Value(v) if v == expected));
+        match run("let v = Vec2(1, 2);\nv.z") {
+            ExecResult::Fault { msg, node } => {
+                assert_eq!(msg, "Vec2 has no component 'z'");
+                assert_eq!(node, "Node::PropertyGet");
+            }
+            other => panic!("expected a swizzle fault, got {}", other),
+        }
+        match run("Vec3(1, 2)") {
+            ExecResult::Fault { msg, .. } => assert_eq!(msg, "Vec3: expected 3 components, got 2"),
+            other => panic!("expected a constructor fault, got {}", other),
+        }
+    }
+
+    #[test]
+    fn test_matrix_builders() {
+        let src = "let m = Mat4.Translate(Vec3(1, 0, 0)) * Mat4.Rotate(Vec3(0, 1, 0), 90) * Mat4.Scale([2, 2, 2]);\n\
+            [m * Vec3(1, 0, 0), Quat.FromAxisAngle([0, 0, 1], 90) * Vec3(1, 0, 0), Mat4(Mat4.ToArray(m)) == m]";
+        let ExecResult::Value(RelType::Array(out)) = run(src) else { panic!("expected an array") };
+        let RelType::Vec3(p) = out[0] else { panic!("expected a Vec3, got {}", out[0]) };
+        assert!(p.abs_diff_eq(Vec3::new(1.0, 0.0, -2.0), 1e-5), "{}", p);
+        let RelType::Vec3(r) = out[1] else { panic!("expected a Vec3, got {}", out[1]) };
+        assert!(r.abs_diff_eq(Vec3::Y, 1e-5), "{}", r);
+        assert_eq!(out[2], RelType::Bool(true));
+
+        let view_proj = Mat4::perspective_rh(60f32.to_radians(), 1.5, 0.1, 100.0) * Mat4::look_at_rh(Vec3::Z * 5.0, Vec3::ZERO, Vec3::Y);
+        let src = "Mat4.Perspective(60, 1.5, 0.1, 100) * Mat4.LookAt(Vec3(0, 0, 5), Vec3(0, 0, 0), Vec3(0, 1, 0))";
+        assert!(matches!(run(src), ExecResult::Value(RelType::Mat4(m)) if m.abs_diff_eq(view_proj, 1e-5)));
+    }
+
+    #[test]
+    fn test_camera3d_accepts_vec3() {
+        use crate::ast::Node;
+        let vec3 = |x, y, z| Box::new(Node::Call("Vec3".into(), vec![Node::IntLiteral(x), Node::IntLiteral(y), Node::IntLiteral(z)]));
+        let camera = |pos_y: Node, pos_z: Node| Node::Camera3D {
+            pos_x: vec3(0, 2, 5),
+            pos_y: Box::new(pos_y),
+            pos_z: Box::new(pos_z),
+            target_x: Box::new(Node::IntLiteral(0)),
+            target_y: Box::new(Node::IntLiteral(2)),
+            target_z: Box::new(Node::IntLiteral(0)),
+            fov: Box::new(Node::IntLiteral(70)),
+        };
+        let mut engine = ExecutionEngine::new();
+        assert!(matches!(engine.execute(&camera(Node::IntLiteral(0), Node::IntLiteral(0))), ExecResult::Value(RelType::Void)));
+        assert_eq!(engine.camera_pos, [0.0, 2.0, 5.0]);
+        assert!((engine.camera_yaw + 90.0).abs() < 1e-4 && engine.camera_pitch.abs() < 1e-4);
+        assert_eq!(engine.camera_fov, 70.0);
+
+        let explode = Node::Call("explode".into(), vec![]);
+        match engine.execute(&camera(explode.clone(), Node::IntLiteral(0))) {
+            ExecResult::Fault { msg, .. } => assert!(msg.contains("explode"), "{}", msg),
+            other => panic!("expected the placeholder's fault, got {}", other),
+        }
+        match engine.execute(&camera(Node::IntLiteral(0), Node::IntLiteral(1))) {
+            ExecResult::Fault { msg, .. } => assert_eq!(msg, "Camera3D expects three numbers, or a Vec3 followed by two 0 placeholders"),
+            other => panic!("expected a placeholder fault, got {}", other),
+        }
+        let aabb = Node::AddWorldAABB { min: vec3(0, 0, 0), max: Box::new(explode) };
+        assert!(matches!(engine.execute(&aabb), ExecResult::Fault { msg, .. } if msg.contains("explode")));
+        match run("CheckCollision([0, 0, 0], [1, 1, 1, 1], [0, 0, 0], [1, 1, 1])") {
+            ExecResult::Fault { msg, .. } => assert_eq!(msg, "a_max must be a Vec3 or an array of 3 numbers"),
+            other => panic!("expected a 4-element array to fault, got {}", other),
+        }
+    }
+}
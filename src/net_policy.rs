@@ -0,0 +1,220 @@
+//! Which hosts `Fetch` may reach.
+//!
+//! `allowed_domains` patterns are exact hosts (`api.example.com`,
+//! `127.0.0.1`), subdomain wildcards (`*.example.com`, which does not match
+//! `example.com` itself) or `*` for any host. An empty list allows any public
+//! host, as before. Loopback, private and link-local addresses (which include
+//! cloud metadata endpoints) are blocked unless a pattern other than `*` names
+//! the host. Hostnames are checked again once resolved, so a public name
+//! pointing at a private address is blocked too.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs};
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NetPolicy {
+    patterns: Vec<String>,
+}
+
+fn normalize(host: &str) -> String {
+    host.trim().trim_end_matches('.').trim_start_matches('[').trim_end_matches(']').to_ascii_lowercase()
+}
+
+fn matches(pattern: &str, host: &str) -> bool {
+    match pattern.strip_prefix("*.") {
+        Some(suffix) => host.len() > suffix.len() + 1 && host.ends_with(suffix) && host[..host.len() - suffix.len()].ends_with('.'),
+        None => pattern == "*" || pattern == host,
+    }
+}
+
+/// Loopback, private, link-local, shared (CGNAT), benchmarking, IETF protocol
+/// assignment, multicast, reserved and unspecified ranges, including IPv4
+/// addresses embedded in IPv6 ones (see [`embedded_v4`]).
+pub fn is_internal(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => is_internal_v4(v4),
+        IpAddr::V6(v6) => match embedded_v4(v6) {
+            Some(v4) => is_internal_v4(v4),
+            None => {
+                let first = v6.segments()[0];
+                v6.is_multicast()
+                    || (first & 0xfe00) == 0xfc00 // unique local fc00::/7
+                    || (first & 0xffc0) == 0xfe80 // link-local fe80::/10
+                    || v6 == Ipv6Addr::new(0xfd00, 0xec2, 0, 0, 0, 0, 0, 0x254) // AWS metadata over IPv6
+            }
+        },
+    }
+}
+
+/// The IPv4 address an IPv6 address routes to: IPv4-mapped `::ffff:a.b.c.d`,
+/// IPv4-compatible `::a.b.c.d` (which covers `::` and `::1`), NAT64
+/// `64:ff9b::a.b.c.d` and 6to4 `2002:aabb:ccdd::/48`.
+fn embedded_v4(ip: Ipv6Addr) -> Option<Ipv4Addr> {
+    let s = ip.segments();
+    let v4 = |hi: u16, lo: u16| Some(Ipv4Addr::from((u32::from(hi) << 16) | u32::from(lo)));
+    match s {
+        [0, 0, 0, 0, 0, 0 | 0xffff, hi, lo] | [0x64, 0xff9b, 0, 0, 0, 0, hi, lo] => v4(hi, lo),
+        [0x2002, hi, lo, ..] => v4(hi, lo),
+        _ => None,
+    }
+}
+
+fn is_internal_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_multicast()
+        || a == 0
+        || a >= 240 // reserved 240.0.0.0/4 and broadcast
+        || (a == 100 && (64..128).contains(&b)) // shared address space 100.64.0.0/10
+        || (a == 198 && (b & 0xfe) == 18) // benchmarking 198.18.0.0/15
+        || (a == 192 && b == 0 && c == 0) // IETF protocol assignments 192.0.0.0/24
+}
+
+impl NetPolicy {
+    pub fn new(allowed_domains: &[String]) -> Self {
+        Self { patterns: allowed_domains.iter().map(|p| normalize(p)).filter(|p| !p.is_empty()).collect() }
+    }
+
+    fn allows(&self, host: &str) -> bool {
+        self.patterns.is_empty() || self.patterns.iter().any(|p| matches(p, host))
+    }
+
+    /// Internal addresses need a pattern that names the host; `*` is not enough.
+    fn allows_internal(&self, host: &str) -> bool {
+        self.patterns.iter().any(|p| p != "*" && matches(p, host))
+    }
+
+    /// Checks a URL before any connection is made: scheme, allowlist, and
+    /// whether the host is an internal address or `localhost` name.
+    pub fn check_url(&self, url: &str) -> Result<url::Url, String> {
+        let parsed = url::Url::parse(url).map_err(|e| format!("Invalid URL '{}': {}", url, e))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(format!("Blocked URL '{}': only http and https are allowed", url));
+        }
+        let host = match parsed.host() {
+            Some(url::Host::Domain(d)) => normalize(d),
+            Some(url::Host::Ipv4(ip)) => ip.to_string(),
+            Some(url::Host::Ipv6(ip)) => ip.to_string(),
+            None => return Err(format!("Invalid URL '{}': no host", url)),
+        };
+        if !self.allows(&host) {
+            return Err(format!("Blocked host '{}': not in allowed_domains (use --allow-domain)", host));
+        }
+        let internal = match parsed.host() {
+            Some(url::Host::Ipv4(ip)) => is_internal(IpAddr::V4(ip)),
+            Some(url::Host::Ipv6(ip)) => is_internal(IpAddr::V6(ip)),
+            _ => host == "localhost" || host.ends_with(".localhost"),
+        };
+        if internal && !self.allows_internal(&host) {
+            return Err(format!("Blocked host '{}': loopback, private and link-local addresses need --allow-domain", host));
+        }
+        Ok(parsed)
+    }
+
+    /// Checks the addresses a host resolved to, right before connecting.
+    pub fn check_resolved(&self, host: &str, addrs: &[SocketAddr]) -> Result<(), String> {
+        let host = normalize(host);
+        if self.allows_internal(&host) {
+            return Ok(());
+        }
+        match addrs.iter().find(|a| is_internal(a.ip())) {
+            Some(addr) => Err(format!("Blocked host '{}': resolves to internal address {}", host, addr.ip())),
+            None => Ok(()),
+        }
+    }
+
+    /// A resolver for the HTTP agent that refuses internal addresses.
+    pub fn resolver(&self) -> impl Fn(&str) -> std::io::Result<Vec<SocketAddr>> + Send + Sync + 'static {
+        let policy = self.clone();
+        move |netloc: &str| {
+            let addrs: Vec<SocketAddr> = netloc.to_socket_addrs()?.collect();
+            let host = netloc.rsplit_once(':').map_or(netloc, |(host, _)| host);
+            policy
+                .check_resolved(host, &addrs)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::PermissionDenied, e))?;
+            Ok(addrs)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(patterns: &[&str]) -> NetPolicy {
+        NetPolicy::new(&patterns.iter().map(|p| p.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_allowlist_patterns() {
+        let p = policy(&["api.example.com", "*.cdn.example.org"]);
+        assert!(p.check_url("https://API.example.com./v1").is_ok());
+        assert!(p.check_url("https://img.cdn.example.org/a.png").is_ok());
+        assert_eq!(
+            p.check_url("https://cdn.example.org/").unwrap_err(),
+            "Blocked host 'cdn.example.org': not in allowed_domains (use --allow-domain)"
+        );
+        assert!(p.check_url("https://evilcdn.example.org/").is_err());
+        assert!(p.check_url("https://api.example.com.evil.net/").is_err());
+        assert_eq!(p.check_url("file:///etc/passwd").unwrap_err(), "Blocked URL 'file:///etc/passwd': only http and https are allowed");
+        assert!(NetPolicy::default().check_url("https://example.com/").is_ok());
+    }
+
+    #[test]
+    fn test_internal_addresses_need_explicit_patterns() {
+        let open = NetPolicy::default();
+        for url in [
+            "http://127.0.0.1:8080/",
+            "http://localhost/",
+            "http://169.254.169.254/latest/meta-data/",
+            "http://10.1.2.3/",
+            "http://[::1]/",
+            "http://[::ffff:192.168.0.1]/",
+            "http://0.0.0.0/",
+        ] {
+            let err = open.check_url(url).unwrap_err();
+            assert!(err.starts_with("Blocked host '") && err.contains("need --allow-domain"), "{}: {}", url, err);
+        }
+        assert!(policy(&["*"]).check_url("http://localhost/").is_err());
+        assert!(policy(&["localhost"]).check_url("http://localhost:3000/").is_ok());
+        assert!(policy(&["127.0.0.1"]).check_url("http://127.0.0.1/").is_ok());
+
+        let addr = |s: &str| vec![s.parse::<SocketAddr>().unwrap()];
+        assert_eq!(
+            open.check_resolved("metadata.example.com", &addr("169.254.169.254:80")).unwrap_err(),
+            "Blocked host 'metadata.example.com': resolves to internal address 169.254.169.254"
+        );
+        assert!(open.check_resolved("example.com", &addr("93.184.216.34:443")).is_ok());
+        assert!(policy(&["*.corp.internal"]).check_resolved("wiki.corp.internal", &addr("10.0.0.8:443")).is_ok());
+    }
+
+    #[test]
+    fn test_embedded_and_reserved_ranges_are_internal() {
+        let ip = |s: &str| s.parse::<IpAddr>().unwrap();
+        for internal in [
+            "64:ff9b::10.0.0.1",
+            "64:ff9b::169.254.169.254",
+            "2002:a9fe:a9fe::1",
+            "2002:7f00:1::",
+            "::127.0.0.1",
+            "::10.1.2.3",
+            "::",
+            "::1",
+            "198.18.0.1",
+            "198.19.255.255",
+            "192.0.0.170",
+            "224.0.0.1",
+            "239.255.255.250",
+            "240.0.0.1",
+            "255.255.255.255",
+            "ff02::1",
+        ] {
+            assert!(is_internal(ip(internal)), "{}", internal);
+        }
+        for public in ["64:ff9b::93.184.216.34", "2002:5db8:d822::1", "::93.184.216.34", "198.20.0.1", "192.0.2.1", "2606:4700::1111"] {
+            assert!(!is_internal(ip(public)), "{}", public);
+        }
+        assert!(NetPolicy::default().check_url("http://[64:ff9b::a9fe:a9fe]/latest/meta-data/").is_err());
+    }
+}
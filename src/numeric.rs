@@ -0,0 +1,373 @@
+//! Arithmetic, comparison and numeric coercion shared by every backend: the
+//! tree-walking evaluator, the bytecode VM, the optimizer's constant folding
+//! and the Rust emitted by the transpiler (through [`rt`]).
+//!
+//! Rules:
+//! - `Int op Int` stays `Int`. Overflow is an error ("Integer overflow in +"),
+//!   never a wrap or a panic, in debug and release builds alike.
+//! - An `Int` meeting a `Float` is promoted to `Float`.
+//! - Integer division truncates toward zero; dividing an `Int` by zero is an
+//!   error ("Div by zero"). `Float` division follows IEEE 754, so `1.0 / 0.0`
+//!   is infinity and `0.0 / 0.0` is NaN.
+//! - `String + String` and `Bytes + Bytes` concatenate. Vector values use
+//!   the operators from `natives::vector`.
+//! - `<` and `>` accept numbers only (mixed Int/Float is promoted). `==`
+//!   compares numbers by value (`1 == 1.0`) and everything else structurally.
+
+use crate::executor::RelType;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl BinOp {
+    pub fn from_symbol(op: char) -> Option<Self> {
+        match op {
+            '+' => Some(BinOp::Add),
+            '-' => Some(BinOp::Sub),
+            '*' => Some(BinOp::Mul),
+            '/' => Some(BinOp::Div),
+            _ => None,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            BinOp::Add => "+",
+            BinOp::Sub => "-",
+            BinOp::Mul => "*",
+            BinOp::Div => "/",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CmpOp {
+    Eq,
+    Lt,
+    Gt,
+}
+
+impl CmpOp {
+    fn ordering(self) -> std::cmp::Ordering {
+        match self {
+            CmpOp::Eq => std::cmp::Ordering::Equal,
+            CmpOp::Lt => std::cmp::Ordering::Less,
+            CmpOp::Gt => std::cmp::Ordering::Greater,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            CmpOp::Eq => "==",
+            CmpOp::Lt => "<",
+            CmpOp::Gt => ">",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum NumericError {
+    Overflow(BinOp),
+    DivByZero,
+    InvalidTypes(&'static str),
+}
+
+impl std::fmt::Display for NumericError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NumericError::Overflow(op) => write!(f, "Integer overflow in {}", op.symbol()),
+            NumericError::DivByZero => write!(f, "Div by zero"),
+            NumericError::InvalidTypes(op) => write!(f, "Invalid types for {}", op),
+        }
+    }
+}
+
+fn int_op(op: BinOp, a: i64, b: i64) -> Result<i64, NumericError> {
+    let res = match op {
+        BinOp::Add => a.checked_add(b),
+        BinOp::Sub => a.checked_sub(b),
+        BinOp::Mul => a.checked_mul(b),
+        BinOp::Div if b == 0 => return Err(NumericError::DivByZero),
+        // i64::MIN / -1 is the one quotient that overflows.
+        BinOp::Div => a.checked_div(b),
+    };
+    res.ok_or(NumericError::Overflow(op))
+}
+
+fn float_op(op: BinOp, a: f64, b: f64) -> f64 {
+    match op {
+        BinOp::Add => a + b,
+        BinOp::Sub => a - b,
+        BinOp::Mul => a * b,
+        BinOp::Div => a / b,
+    }
+}
+
+/// Both operands as floats, if they are numbers and at least one is a Float.
+fn promoted(l: &RelType, r: &RelType) -> Option<(f64, f64)> {
+    match (l, r) {
+        (RelType::Float(a), RelType::Float(b)) => Some((*a, *b)),
+        (RelType::Int(a), RelType::Float(b)) => Some((*a as f64, *b)),
+        (RelType::Float(a), RelType::Int(b)) => Some((*a, *b as f64)),
+        _ => None,
+    }
+}
+
+pub fn arith(op: BinOp, l: &RelType, r: &RelType) -> Result<RelType, NumericError> {
+    if let (RelType::Int(a), RelType::Int(b)) = (l, r) {
+        return int_op(op, *a, *b).map(RelType::Int);
+    }
+    if let Some((a, b)) = promoted(l, r) {
+        return Ok(RelType::Float(float_op(op, a, b)));
+    }
+    match (op, l, r) {
+        (BinOp::Add, RelType::Str(a), RelType::Str(b)) => return Ok(RelType::Str(format!("{}{}", a, b))),
+        (BinOp::Add, RelType::Bytes(a), RelType::Bytes(b)) => return Ok(RelType::Bytes([a.as_slice(), b].concat())),
+        _ => {}
+    }
+    crate::natives::vector::binary_op(op, l, r).ok_or(NumericError::InvalidTypes(op.symbol()))
+}
+
+pub fn compare(op: CmpOp, l: &RelType, r: &RelType) -> Result<bool, NumericError> {
+    let ordering = match (l, r) {
+        (RelType::Int(a), RelType::Int(b)) => Some(a.cmp(b)),
+        _ => match promoted(l, r) {
+            Some((a, b)) => a.partial_cmp(&b),
+            None if op == CmpOp::Eq => return Ok(l == r),
+            None => return Err(NumericError::InvalidTypes(op.symbol())),
+        },
+    };
+    // NaN is unordered: not equal to, smaller or larger than anything.
+    Ok(ordering == Some(op.ordering()))
+}
+
+/// How a Float prints: whole numbers keep one decimal (`2.0`) below 1e15.
+pub fn format_float(v: f64) -> String {
+    if v.fract() == 0.0 && v.abs() < 1e15 { format!("{:.1}", v) } else { format!("{}", v) }
+}
+
+/// Runtime support for transpiled programs, which work on plain Rust values.
+/// The rules match [`arith`] and [`compare`]; errors panic with the same
+/// message, since a compiled program has no fault channel.
+pub mod rt {
+    use super::{BinOp, CmpOp, int_op};
+
+    /// Numbers the transpiler may pass; mixed operands are promoted to f64.
+    pub trait Promote: Copy {
+        fn promote(self) -> f64;
+    }
+
+    impl Promote for i64 {
+        fn promote(self) -> f64 {
+            self as f64
+        }
+    }
+
+    impl Promote for f64 {
+        fn promote(self) -> f64 {
+            self
+        }
+    }
+
+    pub trait Arith<Rhs = Self> {
+        type Output;
+        fn arith(self, op: BinOp, rhs: Rhs) -> Self::Output;
+    }
+
+    impl Arith for i64 {
+        type Output = i64;
+        fn arith(self, op: BinOp, rhs: i64) -> i64 {
+            int_op(op, self, rhs).unwrap_or_else(|e| panic!("{}", e))
+        }
+    }
+
+    macro_rules! float_arith {
+        ($($l:ty, $r:ty);*) => {$(
+            impl Arith<$r> for $l {
+                type Output = f64;
+                fn arith(self, op: BinOp, rhs: $r) -> f64 {
+                    super::float_op(op, self.promote(), rhs.promote())
+                }
+            }
+        )*};
+    }
+
+    float_arith!(f64, f64; i64, f64; f64, i64);
+
+    impl Arith for String {
+        type Output = String;
+        fn arith(mut self, op: BinOp, rhs: String) -> String {
+            assert!(op == BinOp::Add, "Invalid types for {}", op.symbol());
+            self.push_str(&rhs);
+            self
+        }
+    }
+
+    pub trait Compare<Rhs = Self> {
+        fn compare(&self, op: CmpOp, rhs: &Rhs) -> bool;
+    }
+
+    impl Compare for i64 {
+        fn compare(&self, op: CmpOp, rhs: &i64) -> bool {
+            self.cmp(rhs) == op.ordering()
+        }
+    }
+
+    macro_rules! float_compare {
+        ($($l:ty, $r:ty);*) => {$(
+            impl Compare<$r> for $l {
+                fn compare(&self, op: CmpOp, rhs: &$r) -> bool {
+                    self.promote().partial_cmp(&rhs.promote()) == Some(op.ordering())
+                }
+            }
+        )*};
+    }
+
+    float_compare!(f64, f64; i64, f64; f64, i64);
+
+    macro_rules! eq_only {
+        ($($t:ty),*) => {$(
+            impl Compare for $t {
+                fn compare(&self, op: CmpOp, rhs: &$t) -> bool {
+                    assert!(op == CmpOp::Eq, "Invalid types for {}", op.symbol());
+                    self == rhs
+                }
+            }
+        )*};
+    }
+
+    eq_only!(String, bool);
+
+    /// Text `Print` writes for a value, matching `RelType`'s Display.
+    pub trait Show {
+        fn show(&self) -> String;
+    }
+
+    impl Show for i64 {
+        fn show(&self) -> String {
+            self.to_string()
+        }
+    }
+
+    impl Show for f64 {
+        fn show(&self) -> String {
+            super::format_float(*self)
+        }
+    }
+
+    impl Show for bool {
+        fn show(&self) -> String {
+            self.to_string()
+        }
+    }
+
+    impl Show for String {
+        fn show(&self) -> String {
+            self.clone()
+        }
+    }
+
+    impl Show for crate::executor::RelType {
+        fn show(&self) -> String {
+            self.to_string()
+        }
+    }
+
+    pub fn show<T: Show>(v: &T) -> String {
+        v.show()
+    }
+
+    pub fn add<L: Arith<R>, R>(l: L, r: R) -> L::Output {
+        l.arith(BinOp::Add, r)
+    }
+
+    pub fn sub<L: Arith<R>, R>(l: L, r: R) -> L::Output {
+        l.arith(BinOp::Sub, r)
+    }
+
+    pub fn mul<L: Arith<R>, R>(l: L, r: R) -> L::Output {
+        l.arith(BinOp::Mul, r)
+    }
+
+    pub fn div<L: Arith<R>, R>(l: L, r: R) -> L::Output {
+        l.arith(BinOp::Div, r)
+    }
+
+    pub fn eq<L: Compare<R>, R>(l: &L, r: &R) -> bool {
+        l.compare(CmpOp::Eq, r)
+    }
+
+    pub fn lt<L: Compare<R>, R>(l: &L, r: &R) -> bool {
+        l.compare(CmpOp::Lt, r)
+    }
+
+    pub fn gt<L: Compare<R>, R>(l: &L, r: &R) -> bool {
+        l.compare(CmpOp::Gt, r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arith_rules() {
+        use RelType::{Float, Int};
+        assert_eq!(arith(BinOp::Add, &Int(2), &Float(0.5)), Ok(Float(2.5)));
+        assert_eq!(arith(BinOp::Div, &Int(-7), &Int(2)), Ok(Int(-3)));
+        assert_eq!(arith(BinOp::Div, &Int(1), &Int(0)), Err(NumericError::DivByZero));
+        assert_eq!(arith(BinOp::Div, &Float(1.0), &Int(0)), Ok(Float(f64::INFINITY)));
+        assert_eq!(arith(BinOp::Add, &Int(i64::MAX), &Int(1)), Err(NumericError::Overflow(BinOp::Add)));
+        assert_eq!(arith(BinOp::Div, &Int(i64::MIN), &Int(-1)).unwrap_err().to_string(), "Integer overflow in /");
+        assert_eq!(arith(BinOp::Sub, &RelType::Str("a".into()), &Int(1)).unwrap_err().to_string(), "Invalid types for -");
+        assert_eq!(compare(CmpOp::Eq, &Int(1), &Float(1.0)), Ok(true));
+        assert_eq!(compare(CmpOp::Lt, &Int(1), &Float(1.5)), Ok(true));
+        assert_eq!(compare(CmpOp::Gt, &Float(f64::NAN), &Int(0)), Ok(false));
+        assert!(compare(CmpOp::Lt, &RelType::Str("a".into()), &Int(1)).is_err());
+
+        assert_eq!(rt::add(2, 0.5), 2.5);
+        assert!(rt::lt(&1, &1.5) && rt::eq(&2.0, &2) && !rt::gt(&f64::NAN, &0));
+        assert!(std::panic::catch_unwind(|| rt::mul(i64::MAX, 2)).is_err());
+    }
+
+    /// The evaluator, the VM and constant folding agree on every case.
+    #[test]
+    fn test_backends_agree() {
+        use crate::ast::Node;
+        use crate::executor::{ExecResult, ExecutionEngine};
+        let int = |v| Box::new(Node::IntLiteral(v));
+        let float = |v| Box::new(Node::FloatLiteral(v));
+        let cases = [
+            Node::Add(int(2), float(0.5)),
+            Node::Div(float(1.0), int(0)),
+            Node::Div(int(1), int(0)),
+            Node::Mul(int(i64::MAX), int(2)),
+            Node::Sub(int(-7), int(3)),
+            Node::Lt(int(1), float(1.5)),
+            Node::Eq(int(1), float(1.0)),
+        ];
+        for case in cases {
+            let evaluated = match ExecutionEngine::new().execute(&case) {
+                ExecResult::Value(v) => Ok(v),
+                ExecResult::Fault { msg, .. } => Err(msg),
+                ExecResult::ReturnBlockInfo(v) => Ok(v),
+            };
+            let mut compiler = crate::vm::Compiler::new();
+            assert!(compiler.compile_node(&case));
+            let vm = crate::vm::VM::new().run(&compiler.instructions, &compiler.constants);
+            assert_eq!(evaluated, vm, "{:?}", case);
+
+            let folded = match ExecutionEngine::new().execute(&crate::optimizer::optimize(case.clone())) {
+                ExecResult::Value(v) => Ok(v),
+                ExecResult::Fault { msg, .. } => Err(msg),
+                ExecResult::ReturnBlockInfo(v) => Ok(v),
+            };
+            assert_eq!(evaluated, folded, "{:?}", case);
+        }
+    }
+}
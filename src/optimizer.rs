@@ -1,4 +1,6 @@
 use crate::ast::Node;
+use crate::executor::RelType;
+use crate::numeric::{self, BinOp, CmpOp};
 
 pub fn count_nodes(node: &Node) -> usize {
     let mut count = 1;
@@ -40,6 +42,7 @@ pub fn count_nodes(node: &Node) -> usize {
         | Node::MapGet(l, r)
         | Node::MapHasKey(l, r)
         | Node::FileWrite(l, r)
+        | Node::FileWriteBytes(l, r)
         | Node::FSWrite(l, r)
         | Node::LoadTextureAtlas(l, r)
         | Node::LoadSample(l, r) => {
@@ -52,6 +55,7 @@ pub fn count_nodes(node: &Node) -> usize {
         | Node::Print(val)
         | Node::EvalJSONNative(val)
         | Node::ToString(val)
+        | Node::Await(val)
         | Node::LoadShader(val)
         | Node::PollEvents(val)
         | Node::PropertyGet(val, _)
@@ -66,6 +70,7 @@ pub fn count_nodes(node: &Node) -> usize {
         | Node::UITextInput(val)
         | Node::InitCamera(val)
         | Node::FileRead(val)
+        | Node::FileReadBytes(val)
         | Node::FSRead(val)
         | Node::DrawVoxelGrid(val)
         | Node::EnableInteraction(val)
@@ -110,7 +115,7 @@ pub fn count_nodes(node: &Node) -> usize {
                 count += count_nodes(n);
             }
         }
-        Node::FnDef(_, _, body) => {
+        Node::FnDef(_, _, body) | Node::Lambda(_, body) => {
             count += count_nodes(body);
         }
         Node::InitWindow(w, h, t)
@@ -246,9 +251,9 @@ pub fn optimize(node: Node) -> Node {
         Node::Div(l, r) => optimize_math_op(*l, *r, '/'),
 
         // Logic Folding
-        Node::Eq(l, r) => optimize_eq(*l, *r),
-        Node::Lt(l, r) => optimize_lt(*l, *r),
-        Node::Gt(l, r) => optimize_gt(*l, *r),
+        Node::Eq(l, r) => optimize_compare(*l, *r, CmpOp::Eq),
+        Node::Lt(l, r) => optimize_compare(*l, *r, CmpOp::Lt),
+        Node::Gt(l, r) => optimize_compare(*l, *r, CmpOp::Gt),
 
         // Bitwise Folding
         Node::BitAnd(l, r) => optimize_bitwise(*l, *r, '&'),
@@ -288,6 +293,7 @@ pub fn optimize(node: Node) -> Node {
 
         // Standard Traversals
         Node::FnDef(name, params, body) => Node::FnDef(name, params, Box::new(optimize(*body))),
+        Node::Lambda(params, body) => Node::Lambda(params, Box::new(optimize(*body))),
         Node::Call(name, args) => Node::Call(name, args.into_iter().map(optimize).collect()),
         Node::NativeCall(name, args) => {
             Node::NativeCall(name, args.into_iter().map(optimize).collect())
@@ -360,11 +366,14 @@ pub fn optimize(node: Node) -> Node {
         Node::GlobalTime => Node::GlobalTime,
         Node::FileRead(f) => Node::FileRead(Box::new(optimize(*f))), // Modified
         Node::FSRead(f) => Node::FSRead(Box::new(optimize(*f))),     // New
+        Node::FileReadBytes(f) => Node::FileReadBytes(Box::new(optimize(*f))),
+        Node::FileWriteBytes(f, d) => Node::FileWriteBytes(Box::new(optimize(*f)), Box::new(optimize(*d))),
         Node::FileWrite(f, d) => Node::FileWrite(Box::new(optimize(*f)), Box::new(optimize(*d))), // Modified
         Node::FSWrite(f, d) => Node::FSWrite(Box::new(optimize(*f)), Box::new(optimize(*d))), // New
         Node::Print(val) => Node::Print(Box::new(optimize(*val))),
         Node::EvalJSONNative(val) => Node::EvalJSONNative(Box::new(optimize(*val))),
         Node::ToString(val) => Node::ToString(Box::new(optimize(*val))),
+        Node::Await(val) => Node::Await(Box::new(optimize(*val))),
 
         Node::InitWindow(w, h, t) => Node::InitWindow(
             Box::new(optimize(*w)),
@@ -523,76 +532,60 @@ pub fn optimize(node: Node) -> Node {
     }
 }
 
-fn optimize_math_op(left: Node, right: Node, op: char) -> Node {
-    let opt_l = optimize(left);
-    let opt_r = optimize(right);
-
-    match (&opt_l, &opt_r) {
-        (Node::IntLiteral(l), Node::IntLiteral(r)) => match op {
-            '+' => Node::IntLiteral(l + r),
-            '-' => Node::IntLiteral(l - r),
-            '*' => Node::IntLiteral(l * r),
-            '/' => {
-                if *r != 0 {
-                    Node::IntLiteral(l / r)
-                } else {
-                    Node::Div(Box::new(opt_l), Box::new(opt_r))
-                }
-            }
-            _ => unreachable!(),
-        },
-        (Node::FloatLiteral(l), Node::FloatLiteral(r)) => match op {
-            '+' => Node::FloatLiteral(l + r),
-            '-' => Node::FloatLiteral(l - r),
-            '*' => Node::FloatLiteral(l * r),
-            '/' => {
-                if *r != 0.0 {
-                    Node::FloatLiteral(l / r)
-                } else {
-                    Node::Div(Box::new(opt_l), Box::new(opt_r))
-                }
-            }
-            _ => unreachable!(),
-        },
-        _ => match op {
-            '+' => Node::Add(Box::new(opt_l), Box::new(opt_r)),
-            '-' => Node::Sub(Box::new(opt_l), Box::new(opt_r)),
-            '*' => Node::Mul(Box::new(opt_l), Box::new(opt_r)),
-            '/' => Node::Div(Box::new(opt_l), Box::new(opt_r)),
-            _ => unreachable!(),
-        },
+/// A literal as a runtime value, so folding can use the `numeric` rules.
+fn literal_value(node: &Node) -> Option<RelType> {
+    match node {
+        Node::IntLiteral(v) => Some(RelType::Int(*v)),
+        Node::FloatLiteral(v) => Some(RelType::Float(*v)),
+        Node::BoolLiteral(v) => Some(RelType::Bool(*v)),
+        Node::StringLiteral(v) => Some(RelType::Str(v.clone())),
+        _ => None,
     }
 }
 
-fn optimize_eq(left: Node, right: Node) -> Node {
-    let opt_l = optimize(left);
-    let opt_r = optimize(right);
-    match (&opt_l, &opt_r) {
-        (Node::IntLiteral(l), Node::IntLiteral(r)) => Node::BoolLiteral(l == r),
-        (Node::FloatLiteral(l), Node::FloatLiteral(r)) => Node::BoolLiteral(l == r),
-        (Node::BoolLiteral(l), Node::BoolLiteral(r)) => Node::BoolLiteral(l == r),
-        (Node::StringLiteral(l), Node::StringLiteral(r)) => Node::BoolLiteral(l == r),
-        _ => Node::Eq(Box::new(opt_l), Box::new(opt_r)),
+/// Non-finite floats stay unfolded: they have no literal form in JSON or Rust.
+fn value_literal(value: RelType) -> Option<Node> {
+    match value {
+        RelType::Int(v) => Some(Node::IntLiteral(v)),
+        RelType::Float(v) if v.is_finite() => Some(Node::FloatLiteral(v)),
+        RelType::Bool(v) => Some(Node::BoolLiteral(v)),
+        RelType::Str(v) => Some(Node::StringLiteral(v)),
+        _ => None,
     }
 }
 
-fn optimize_lt(left: Node, right: Node) -> Node {
+fn optimize_math_op(left: Node, right: Node, op: char) -> Node {
     let opt_l = optimize(left);
     let opt_r = optimize(right);
-    match (&opt_l, &opt_r) {
-        (Node::IntLiteral(l), Node::IntLiteral(r)) => Node::BoolLiteral(l < r),
-        (Node::FloatLiteral(l), Node::FloatLiteral(r)) => Node::BoolLiteral(l < r),
-        _ => Node::Lt(Box::new(opt_l), Box::new(opt_r)),
+    let bin = BinOp::from_symbol(op).expect("arithmetic operator");
+
+    // Operations that fail (overflow, division by zero, bad operand types)
+    // are left in place so the fault is raised at runtime.
+    if let (Some(l), Some(r)) = (literal_value(&opt_l), literal_value(&opt_r))
+        && let Some(folded) = numeric::arith(bin, &l, &r).ok().and_then(value_literal)
+    {
+        return folded;
+    }
+    match bin {
+        BinOp::Add => Node::Add(Box::new(opt_l), Box::new(opt_r)),
+        BinOp::Sub => Node::Sub(Box::new(opt_l), Box::new(opt_r)),
+        BinOp::Mul => Node::Mul(Box::new(opt_l), Box::new(opt_r)),
+        BinOp::Div => Node::Div(Box::new(opt_l), Box::new(opt_r)),
     }
 }
 
-fn optimize_gt(left: Node, right: Node) -> Node {
+fn optimize_compare(left: Node, right: Node, op: CmpOp) -> Node {
     let opt_l = optimize(left);
     let opt_r = optimize(right);
-    match (&opt_l, &opt_r) {
-        (Node::IntLiteral(l), Node::IntLiteral(r)) => Node::BoolLiteral(l > r),
-        (Node::FloatLiteral(l), Node::FloatLiteral(r)) => Node::BoolLiteral(l > r),
-        _ => Node::Gt(Box::new(opt_l), Box::new(opt_r)),
+    if let (Some(l), Some(r)) = (literal_value(&opt_l), literal_value(&opt_r))
+        && let Ok(b) = numeric::compare(op, &l, &r)
+    {
+        return Node::BoolLiteral(b);
+    }
+    match op {
+        CmpOp::Eq => Node::Eq(Box::new(opt_l), Box::new(opt_r)),
+        CmpOp::Lt => Node::Lt(Box::new(opt_l), Box::new(opt_r)),
+        CmpOp::Gt => Node::Gt(Box::new(opt_l), Box::new(opt_r)),
     }
 }
 
@@ -621,6 +614,17 @@ fn optimize_bitwise(left: Node, right: Node, op: char) -> Node {
 use crate::ast::Type;
 use std::collections::HashMap;
 
+/// Whether a value of type `found` may be passed where `expected` is declared.
+/// Element types of arrays are not tracked precisely, so any array fits any array.
+fn type_compatible(expected: &Type, found: &Type) -> bool {
+    match (expected, found) {
+        (Type::Any, _) | (_, Type::Any) => true,
+        (Type::Float, Type::Int) => true,
+        (Type::Array(_), Type::Array(_)) => true,
+        _ => expected == found,
+    }
+}
+
 pub struct TypeChecker {
     pub scopes: Vec<HashMap<String, Type>>,
     pub errors: Vec<String>,
@@ -708,6 +712,10 @@ impl TypeChecker {
                         "TypeError: Cannot perform mathematics on Handle pointers"
                     ));
                 }
+                // Int mixed with Float is promoted, as in `numeric::arith`.
+                if matches!((&lt, &rt), (Type::Int, Type::Float) | (Type::Float, Type::Int)) {
+                    return Ok(Type::Float);
+                }
                 if lt != rt && lt != Type::Any && rt != Type::Any {
                     self.errors
                         .push(format!("TypeError: Math mismatch {:?} and {:?}", lt, rt));
@@ -775,6 +783,34 @@ impl TypeChecker {
                 Ok(Type::Any)
             }
 
+            // Built-in natives with a declared signature
+            Node::Call(name, args) => {
+                let arg_types = args.iter().map(|a| self.check(a)).collect::<Result<Vec<_>, _>>()?;
+                let Some((params, ret)) = crate::natives::signature(name) else {
+                    return Ok(Type::Any);
+                };
+                if params.len() != arg_types.len() {
+                    self.errors.push(format!(
+                        "TypeError: '{}' expects {} arguments, found {}",
+                        name,
+                        params.len(),
+                        arg_types.len()
+                    ));
+                }
+                for (i, (expected, found)) in params.iter().zip(&arg_types).enumerate() {
+                    if !type_compatible(expected, found) {
+                        self.errors.push(format!(
+                            "TypeError: '{}' argument {} expects {:?}, found {:?}",
+                            name,
+                            i + 1,
+                            expected,
+                            found
+                        ));
+                    }
+                }
+                Ok(ret)
+            }
+
             // ToString always produces a String
             Node::ToString(inner) => {
                 self.check(inner)?;
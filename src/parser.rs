@@ -35,6 +35,7 @@ pub enum Token {
     KeywordFn,
     KeywordReturn,
     KeywordImport,
+    KeywordAwait,
     BuiltinNull,
     EOF,
 }
@@ -96,11 +97,12 @@ impl<'a> Lexer<'a> {
         }
     }
 
-    pub fn next_token(&mut self) -> Token {
+    pub fn next_token(&mut self) -> Result<Token, String> {
         self.skip_whitespace();
         if self.pos >= self.input.len() {
-            return Token::EOF;
+            return Ok(Token::EOF);
         }
+        let (line, col) = (self.line, self.col);
 
         let c = self.peek_char().unwrap();
 
@@ -114,7 +116,7 @@ impl<'a> Lexer<'a> {
                     break;
                 }
             }
-            return match s.as_str() {
+            return Ok(match s.as_str() {
                 "let" => Token::KeywordLet,
                 "if" => Token::KeywordIf,
                 "else" => Token::KeywordElse,
@@ -122,9 +124,10 @@ impl<'a> Lexer<'a> {
                 "fn" => Token::KeywordFn,
                 "return" => Token::KeywordReturn,
                 "import" => Token::KeywordImport,
+                "await" => Token::KeywordAwait,
                 "null" => Token::BuiltinNull,
                 _ => Token::Ident(s),
-            };
+            });
         }
 
         if c.is_ascii_digit() {
@@ -151,11 +154,8 @@ impl<'a> Lexer<'a> {
                     break;
                 }
             }
-            if is_float {
-                return Token::Float(s.parse().unwrap());
-            } else {
-                return Token::Int(s.parse().unwrap());
-            }
+            let token = if is_float { s.parse().map(Token::Float).ok() } else { s.parse().map(Token::Int).ok() };
+            return token.ok_or_else(|| diagnostic(line, col, &format!("Invalid number literal '{}'", s)));
         }
 
         if c == '"' {
@@ -169,13 +169,13 @@ impl<'a> Lexer<'a> {
                 s.push(ch);
                 self.advance();
             }
-            return Token::Str(s);
+            return Ok(Token::Str(s));
         }
 
         self.advance();
         let next_c = self.peek_char().unwrap_or(' ');
 
-        match c {
+        Ok(match c {
             '(' => Token::LParen,
             ')' => Token::RParen,
             '{' => Token::LBrace,
@@ -224,43 +224,62 @@ impl<'a> Lexer<'a> {
                     Token::Gt
                 }
             }
-            _ => {
-                let escaped_hint = format!("Unexpected char '{}'", c).replace("\"", "\\\"");
-                let json = format!(
-                    r#"{{"diagnostic": {{"line": {}, "col": {}, "hint": "{}"}}}}"#,
-                    self.line, self.col, escaped_hint
-                );
-                panic!("{}", json);
-            }
-        }
+            _ => return Err(diagnostic(line, col, &format!("Unexpected char '{}'", c))),
+        })
     }
 }
 
+/// The JSON diagnostic every lexer and parser error is reported as.
+fn diagnostic(line: usize, col: usize, hint: &str) -> String {
+    format!(r#"{{"diagnostic": {{"line": {}, "col": {}, "hint": "{}"}}}}"#, line, col, hint.replace('"', "\\\""))
+}
+
+/// Parses DSL source into a program, or returns the JSON diagnostic of the
+/// first lexer or parser error.
+pub fn try_parse(input: &str) -> Result<Node, String> {
+    Parser::new(input).parse()
+}
+
 pub struct Parser {
     tokens: Vec<(Token, usize, usize)>,
     pos: usize,
+    /// Source line of every statement, in the order the statements start (pre-order).
+    /// Tooling such as coverage maps these back onto the AST.
+    pub stmt_lines: Vec<usize>,
+    lex_error: Option<String>,
 }
 
 impl Parser {
+    /// Tokenizes `input`. A lexer error is kept and returned by `parse`.
     pub fn new(input: &str) -> Self {
         let mut lexer = Lexer::new(input);
         let mut tokens = Vec::new();
+        let mut lex_error = None;
         loop {
+            // Record the position of the token itself, not of the whitespace before it.
+            lexer.skip_whitespace();
             let line = lexer.line;
             let col = lexer.col;
-            let t = lexer.next_token();
+            let t = lexer.next_token().unwrap_or_else(|e| {
+                lex_error = Some(e);
+                Token::EOF
+            });
             tokens.push((t.clone(), line, col));
             if t == Token::EOF {
                 break;
             }
         }
-        Self { tokens, pos: 0 }
+        Self { tokens, pos: 0, stmt_lines: Vec::new(), lex_error }
     }
 
     fn peek(&self) -> &Token {
         &self.tokens[self.pos].0
     }
 
+    fn peek_at(&self, offset: usize) -> &Token {
+        &self.tokens[(self.pos + offset).min(self.tokens.len() - 1)].0
+    }
+
     fn peek_pos(&self) -> (usize, usize) {
         (self.tokens[self.pos].1, self.tokens[self.pos].2)
     }
@@ -273,132 +292,143 @@ impl Parser {
         t
     }
 
-    fn diagnostic_panic(&self, hint: &str) -> ! {
+    /// A diagnostic at the current token.
+    fn error<T>(&self, hint: &str) -> Result<T, String> {
         let (line, col) = self.peek_pos();
-        let escaped_hint = hint.replace("\"", "\\\"");
-        let json = format!(
-            r#"{{"diagnostic": {{"line": {}, "col": {}, "hint": "{}"}}}}"#,
-            line, col, escaped_hint
-        );
-        panic!("{}", json);
+        Err(diagnostic(line, col, hint))
     }
 
-    fn expect(&mut self, expected: Token) {
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
         let (line, col) = self.peek_pos();
         let t = self.advance();
         if t != expected {
-            let escaped_hint =
-                format!("Expected {:?}, found {:?}", expected, t).replace("\"", "\\\"");
-            let json = format!(
-                r#"{{"diagnostic": {{"line": {}, "col": {}, "hint": "{}"}}}}"#,
-                line, col, escaped_hint
-            );
-            panic!("{}", json);
+            return Err(diagnostic(line, col, &format!("Expected {:?}, found {:?}", expected, t)));
+        }
+        Ok(())
+    }
+
+    /// Removes the next call argument, or reports that `name` needs more.
+    fn take_arg(&self, name: &str, args: &mut Vec<Node>) -> Result<Box<Node>, String> {
+        if args.is_empty() {
+            return self.error(&format!("{} is missing an argument", name));
         }
+        Ok(Box::new(args.remove(0)))
     }
 
-    pub fn parse(&mut self) -> Node {
+    pub fn parse(&mut self) -> Result<Node, String> {
+        if let Some(e) = self.lex_error.take() {
+            return Err(e);
+        }
         let mut statements = Vec::new();
         while *self.peek() != Token::EOF {
-            statements.push(self.parse_statement());
+            statements.push(self.parse_statement()?);
         }
-        Node::Block(statements)
+        Ok(Node::Block(statements))
     }
 
-    fn parse_statement(&mut self) -> Node {
-        match self.peek() {
+    fn parse_statement(&mut self) -> Result<Node, String> {
+        let line = self.peek_pos().0;
+        self.stmt_lines.push(line);
+        Ok(match self.peek() {
             Token::KeywordLet => {
                 self.advance();
                 let ident = match self.advance() {
                     Token::Ident(name) => name,
-                    _ => self.diagnostic_panic("Expected identifier after let"),
+                    _ => return self.error("Expected identifier after let"),
                 };
-                self.expect(Token::Assign);
-                let expr = self.parse_expression();
-                self.expect(Token::Semi);
+                self.expect(Token::Assign)?;
+                let expr = self.parse_awaitable()?;
+                self.expect(Token::Semi)?;
                 Node::Assign(ident, Box::new(expr))
             }
             Token::KeywordIf => {
                 self.advance();
-                self.expect(Token::LParen);
-                let cond = self.parse_expression();
-                self.expect(Token::RParen);
-                let then_branch = self.parse_block();
+                self.expect(Token::LParen)?;
+                let cond = self.parse_expression()?;
+                self.expect(Token::RParen)?;
+                let then_branch = self.parse_block()?;
                 let mut else_branch = None;
                 if *self.peek() == Token::KeywordElse {
                     self.advance();
-                    else_branch = Some(Box::new(self.parse_block()));
+                    else_branch = Some(Box::new(self.parse_block()?));
                 }
                 Node::If(Box::new(cond), Box::new(then_branch), else_branch)
             }
             Token::KeywordWhile => {
                 self.advance();
-                self.expect(Token::LParen);
-                let cond = self.parse_expression();
-                self.expect(Token::RParen);
-                let body = self.parse_block();
+                self.expect(Token::LParen)?;
+                let cond = self.parse_expression()?;
+                self.expect(Token::RParen)?;
+                let body = self.parse_block()?;
                 Node::While(Box::new(cond), Box::new(body))
             }
             Token::KeywordFn => {
                 self.advance();
                 let name = match self.advance() {
                     Token::Ident(name) => name,
-                    _ => self.diagnostic_panic("Expected function name"),
+                    _ => return self.error("Expected function name"),
                 };
-                self.expect(Token::LParen);
-                let mut args = Vec::new();
-                while *self.peek() != Token::RParen {
-                    if let Token::Ident(arg) = self.advance() {
-                        args.push(arg);
-                    }
-                    if *self.peek() == Token::Comma {
-                        self.advance();
-                    }
-                }
-                self.expect(Token::RParen);
-                let body = self.parse_block();
+                let args = self.parse_params()?;
+                let body = self.parse_block()?;
                 Node::FnDef(name, args, Box::new(body))
             }
             Token::KeywordReturn => {
                 self.advance();
-                let expr = self.parse_expression();
-                self.expect(Token::Semi);
+                let expr = self.parse_awaitable()?;
+                self.expect(Token::Semi)?;
                 Node::Return(Box::new(expr))
             }
-            Token::LBrace => self.parse_block(),
+            Token::LBrace => self.parse_block()?,
+            Token::KeywordAwait => {
+                let expr = self.parse_awaitable()?;
+                if *self.peek() == Token::Semi {
+                    self.advance();
+                }
+                expr
+            }
+            Token::Ident(name) if *self.peek_at(1) == Token::Assign && *self.peek_at(2) == Token::KeywordAwait => {
+                let name = name.clone();
+                self.advance();
+                self.advance();
+                let expr = self.parse_awaitable()?;
+                if *self.peek() == Token::Semi {
+                    self.advance();
+                }
+                Node::Assign(name, Box::new(expr))
+            }
             _ => {
-                let expr = self.parse_expression();
+                let expr = self.parse_expression()?;
 
                 // Check for -> { block } which is If(expr, Block, None)
                 if *self.peek() == Token::Arrow {
                     self.advance();
-                    let block = self.parse_block();
-                    return Node::If(Box::new(expr), Box::new(block), None);
+                    let block = self.parse_block()?;
+                    return Ok(Node::If(Box::new(expr), Box::new(block), None));
                 }
 
                 // Check for fat arrow => { block } for async callbacks (Fetch)
                 if *self.peek() == Token::FatArrow {
                     self.advance();
-                    let callback = self.parse_block();
+                    let callback = self.parse_block()?;
 
                     if let Node::Call(name, args) = expr && name == "Fetch" && args.len() == 2 {
                         let method = if let Node::StringLiteral(s) = &args[0] {
                             s.clone()
                         } else {
-                            self.diagnostic_panic("Fetch expects Method as string")
+                            return self.error("Fetch expects Method as string")
                         };
                         let url = if let Node::StringLiteral(s) = &args[1] {
                             s.clone()
                         } else {
-                            self.diagnostic_panic("Fetch expects URL as string")
+                            return self.error("Fetch expects URL as string")
                         };
-                        return Node::Fetch {
+                        return Ok(Node::Fetch {
                             method,
                             url,
                             callback: Box::new(callback),
-                        };
+                        });
                     }
-                    self.diagnostic_panic(
+                    return self.error(
                         "FatArrow '=>' can only be used with Fetch(method, url) calls",
                     );
                 }
@@ -408,112 +438,148 @@ impl Parser {
                 }
                 expr
             }
-        }
+        })
     }
 
-    fn parse_block(&mut self) -> Node {
-        self.expect(Token::LBrace);
+    fn parse_block(&mut self) -> Result<Node, String> {
+        self.expect(Token::LBrace)?;
         let mut stmts = Vec::new();
         while *self.peek() != Token::RBrace && *self.peek() != Token::EOF {
-            stmts.push(self.parse_statement());
+            stmts.push(self.parse_statement()?);
         }
-        self.expect(Token::RBrace);
-        Node::Block(stmts)
+        self.expect(Token::RBrace)?;
+        Ok(Node::Block(stmts))
     }
 
-    fn parse_expression(&mut self) -> Node {
+    /// `(a, b, ...)` after `fn` or `fn name`.
+    fn parse_params(&mut self) -> Result<Vec<String>, String> {
+        self.expect(Token::LParen)?;
+        let mut args = Vec::new();
+        while *self.peek() != Token::RParen {
+            match self.peek().clone() {
+                Token::Ident(arg) => {
+                    self.advance();
+                    args.push(arg);
+                }
+                // Without this an unterminated parameter list spins forever at EOF.
+                _ => return self.error("Expected parameter name"),
+            }
+            if *self.peek() == Token::Comma {
+                self.advance();
+            }
+        }
+        self.expect(Token::RParen)?;
+        Ok(args)
+    }
+
+    fn parse_expression(&mut self) -> Result<Node, String> {
         self.parse_assignment()
     }
 
-    fn parse_assignment(&mut self) -> Node {
-        let left = self.parse_comparison();
+    /// An expression, or `await` applied to a whole one. `await` may only
+    /// start a statement or the value a `let`, assignment or `return` takes, so
+    /// a script never waits in the middle of evaluating an expression.
+    fn parse_awaitable(&mut self) -> Result<Node, String> {
+        if *self.peek() != Token::KeywordAwait {
+            return self.parse_expression();
+        }
+        self.advance();
+        let awaited = Node::Await(Box::new(self.parse_primary()?));
+        if !matches!(self.peek(), Token::Semi | Token::RBrace | Token::EOF) {
+            return self.error("await must be the whole value of its statement; assign it first (let x = await p;)");
+        }
+        Ok(awaited)
+    }
+
+    fn parse_assignment(&mut self) -> Result<Node, String> {
+        let left = self.parse_comparison()?;
         if *self.peek() == Token::Assign {
             self.advance();
-            let right = self.parse_expression(); // right-associative
-            match left {
+            let right = self.parse_expression()?; // right-associative
+            Ok(match left {
                 Node::Identifier(name) => Node::Assign(name, Box::new(right)),
                 Node::ArrayGet(arr, index) => Node::ArraySet(arr, index, Box::new(right)),
                 Node::MapGet(map, key) => Node::MapSet(map, key, Box::new(right)),
                 Node::PropertyGet(obj, prop) => Node::PropertySet(obj, prop, Box::new(right)),
                 Node::Index(container, idx) => Node::ArraySet(container, idx, Box::new(right)), // Fallback mapping
-                _ => self.diagnostic_panic("Invalid assignment target"),
-            }
+                _ => return self.error("Invalid assignment target"),
+            })
         } else {
-            left
+            Ok(left)
         }
     }
 
-    fn parse_comparison(&mut self) -> Node {
-        let mut node = self.parse_term();
+    fn parse_comparison(&mut self) -> Result<Node, String> {
+        let mut node = self.parse_term()?;
         loop {
             match self.peek() {
                 Token::EqEq => {
                     self.advance();
-                    node = Node::Eq(Box::new(node), Box::new(self.parse_term()));
+                    node = Node::Eq(Box::new(node), Box::new(self.parse_term()?));
                 }
                 Token::Lt => {
                     self.advance();
-                    node = Node::Lt(Box::new(node), Box::new(self.parse_term()));
+                    node = Node::Lt(Box::new(node), Box::new(self.parse_term()?));
                 }
                 Token::Gt => {
                     self.advance();
-                    node = Node::Gt(Box::new(node), Box::new(self.parse_term()));
+                    node = Node::Gt(Box::new(node), Box::new(self.parse_term()?));
                 }
                 _ => break,
             }
         }
-        node
+        Ok(node)
     }
 
-    fn parse_term(&mut self) -> Node {
-        let mut node = self.parse_factor();
+    fn parse_term(&mut self) -> Result<Node, String> {
+        let mut node = self.parse_factor()?;
         loop {
             match self.peek() {
                 Token::Plus => {
                     self.advance();
-                    node = Node::Add(Box::new(node), Box::new(self.parse_factor()));
+                    node = Node::Add(Box::new(node), Box::new(self.parse_factor()?));
                 }
                 Token::Minus => {
                     self.advance();
-                    node = Node::Sub(Box::new(node), Box::new(self.parse_factor()));
+                    node = Node::Sub(Box::new(node), Box::new(self.parse_factor()?));
                 }
                 _ => break,
             }
         }
-        node
+        Ok(node)
     }
 
-    fn parse_factor(&mut self) -> Node {
-        let mut node = self.parse_primary();
+    fn parse_factor(&mut self) -> Result<Node, String> {
+        let mut node = self.parse_primary()?;
         loop {
             match self.peek() {
                 Token::Star => {
                     self.advance();
-                    node = Node::Mul(Box::new(node), Box::new(self.parse_primary()));
+                    node = Node::Mul(Box::new(node), Box::new(self.parse_primary()?));
                 }
                 Token::Slash => {
                     self.advance();
-                    node = Node::Div(Box::new(node), Box::new(self.parse_primary()));
+                    node = Node::Div(Box::new(node), Box::new(self.parse_primary()?));
                 }
                 Token::Shl => {
                     self.advance();
-                    node = Node::BitShiftLeft(Box::new(node), Box::new(self.parse_primary()));
+                    node = Node::BitShiftLeft(Box::new(node), Box::new(self.parse_primary()?));
                 }
                 Token::Shr => {
                     self.advance();
-                    node = Node::BitShiftRight(Box::new(node), Box::new(self.parse_primary()));
+                    node = Node::BitShiftRight(Box::new(node), Box::new(self.parse_primary()?));
                 }
                 Token::Amp => {
                     self.advance();
-                    node = Node::BitAnd(Box::new(node), Box::new(self.parse_primary()));
+                    node = Node::BitAnd(Box::new(node), Box::new(self.parse_primary()?));
                 }
                 _ => break,
             }
         }
-        node
+        Ok(node)
     }
 
-    fn parse_primary(&mut self) -> Node {
+    fn parse_primary(&mut self) -> Result<Node, String> {
         let mut node = match self.peek().clone() {
             Token::Int(v) => {
                 self.advance();
@@ -536,50 +602,70 @@ impl Parser {
                     Node::BoolLiteral(false)
                 } else {
                     self.advance();
+                    let mut name = name;
+                    // `Module.Function(...)` calls a native by its qualified name.
+                    if *self.peek() == Token::Dot
+                        && matches!(self.peek_at(1), Token::Ident(_))
+                        && *self.peek_at(2) == Token::LParen
+                    {
+                        self.advance();
+                        if let Token::Ident(member) = self.advance() {
+                            name = format!("{}.{}", name, member);
+                        }
+                    }
                     if *self.peek() == Token::LParen {
                         self.advance(); // consume '('
                         let mut args = Vec::new();
                         while *self.peek() != Token::RParen {
-                            args.push(self.parse_expression());
+                            args.push(self.parse_expression()?);
                             if *self.peek() == Token::Comma {
                                 self.advance();
                             }
                         }
-                        self.expect(Token::RParen);
+                        self.expect(Token::RParen)?;
 
                         // Trailing closure block support
                         let mut trailing_block = None;
                         if *self.peek() == Token::LBrace {
-                            trailing_block = Some(Box::new(self.parse_block()));
+                            trailing_block = Some(Box::new(self.parse_block()?));
                         }
 
-                        self.construct_node_from_call(&name, args, trailing_block)
+                        self.construct_node_from_call(&name, args, trailing_block)?
                     } else {
                         Node::Identifier(name)
                     }
                 }
             }
+            Token::KeywordFn => {
+                self.advance();
+                let params = self.parse_params()?;
+                let body = self.parse_block()?;
+                Node::Lambda(params, Box::new(body))
+            }
+            Token::KeywordAwait => {
+                return self.error("await is only allowed as a statement: await p; let x = await p; x = await p; or return await p;");
+            }
             Token::LParen => {
                 self.advance();
-                let expr = self.parse_expression();
-                self.expect(Token::RParen);
+                let expr = self.parse_expression()?;
+                self.expect(Token::RParen)?;
                 expr
             }
             Token::LBracket => {
                 self.advance();
                 let mut args = Vec::new();
                 while *self.peek() != Token::RBracket {
-                    args.push(self.parse_expression());
+                    args.push(self.parse_expression()?);
                     if *self.peek() == Token::Comma {
                         self.advance();
                     }
                 }
-                self.expect(Token::RBracket);
+                self.expect(Token::RBracket)?;
                 Node::ArrayCreate(args)
             }
             _ => {
                 let hint = format!("Unexpected token in expression: {:?}", self.peek());
-                self.diagnostic_panic(&hint)
+                return self.error(&hint)
             }
         };
 
@@ -587,21 +673,21 @@ impl Parser {
         loop {
             if *self.peek() == Token::LBracket {
                 self.advance();
-                let idx = self.parse_expression();
-                self.expect(Token::RBracket);
+                let idx = self.parse_expression()?;
+                self.expect(Token::RBracket)?;
                 node = Node::Index(Box::new(node), Box::new(idx));
             } else if *self.peek() == Token::Dot {
                 self.advance();
                 if let Token::Ident(prop) = self.advance() {
                     node = Node::PropertyGet(Box::new(node), prop);
                 } else {
-                    self.diagnostic_panic("Expected property name after dot");
+                    return self.error("Expected property name after dot");
                 }
             } else {
                 break;
             }
         }
-        node
+        Ok(node)
     }
 
     fn construct_node_from_call(
@@ -609,93 +695,132 @@ impl Parser {
         name: &str,
         mut args: Vec<Node>,
         trailing_block: Option<Box<Node>>,
-    ) -> Node {
+    ) -> Result<Node, String> {
         // Automatically append trailing block if present
         if let Some(b) = trailing_block {
             args.push(*b);
         }
 
-        match name {
+        Ok(match name {
             // AST Map generated directly by Agent
-            "Print" | "print" => Node::Print(Box::new(args.remove(0))),
+            "Print" | "print" => Node::Print(self.take_arg(name, &mut args)?),
             "Time" => Node::Time,
             "GlobalTime" => Node::GlobalTime,
-            "Sin" => Node::Sin(Box::new(args.remove(0))),
-            "Cos" => Node::Cos(Box::new(args.remove(0))),
-            "Abs" => Node::Abs(Box::new(args.remove(0))),
+            "Sin" => Node::Sin(self.take_arg(name, &mut args)?),
+            "Cos" => Node::Cos(self.take_arg(name, &mut args)?),
+            "Abs" => Node::Abs(self.take_arg(name, &mut args)?),
             "InitGraphics" => Node::InitGraphics,
             "InitAudio" => Node::InitAudio,
             "GetLastKeypress" => Node::GetLastKeypress,
             "UIWindow" => Node::UIWindow(
-                if let Node::StringLiteral(s) = args.remove(0) {
+                if let Node::StringLiteral(s) = *self.take_arg(name, &mut args)? {
                     s
                 } else {
-                    self.diagnostic_panic("UIWindow expects exact String ID arg")
+                    return self.error("UIWindow expects exact String ID arg")
                 },
-                Box::new(args.remove(0)),
-                Box::new(args.remove(0)),
+                self.take_arg(name, &mut args)?,
+                self.take_arg(name, &mut args)?,
             ),
-            "UILabel" => Node::UILabel(Box::new(args.remove(0))),
-            "UIButton" => Node::UIButton(Box::new(args.remove(0))),
-            "UITextInput" => Node::UITextInput(Box::new(args.remove(0))),
+            "UILabel" => Node::UILabel(self.take_arg(name, &mut args)?),
+            "UIButton" => Node::UIButton(self.take_arg(name, &mut args)?),
+            "UITextInput" => Node::UITextInput(self.take_arg(name, &mut args)?),
             "UIScrollArea" => Node::UIScrollArea(
-                if let Node::StringLiteral(s) = args.remove(0) {
+                if let Node::StringLiteral(s) = *self.take_arg(name, &mut args)? {
                     s
                 } else {
-                    self.diagnostic_panic("UIScrollArea expects exact String ID arg")
+                    return self.error("UIScrollArea expects exact String ID arg")
                 },
-                Box::new(args.remove(0)),
+                self.take_arg(name, &mut args)?,
             ),
-            "UIHorizontal" => Node::UIHorizontal(Box::new(args.remove(0))),
-            "UIFullscreen" => Node::UIFullscreen(Box::new(args.remove(0))),
+            "UIHorizontal" => Node::UIHorizontal(self.take_arg(name, &mut args)?),
+            "UIFullscreen" => Node::UIFullscreen(self.take_arg(name, &mut args)?),
             "UIGrid" => Node::UIGrid(
-                if let Node::IntLiteral(i) = args.remove(0) {
+                if let Node::IntLiteral(i) = *self.take_arg(name, &mut args)? {
                     i
                 } else {
-                    self.diagnostic_panic("UIGrid expects Int args")
+                    return self.error("UIGrid expects Int args")
                 },
-                if let Node::StringLiteral(s) = args.remove(0) {
+                if let Node::StringLiteral(s) = *self.take_arg(name, &mut args)? {
                     s
                 } else {
-                    self.diagnostic_panic("UIGrid expects String ID")
+                    return self.error("UIGrid expects String ID")
                 },
-                Box::new(args.remove(0)),
+                self.take_arg(name, &mut args)?,
             ),
             "UISetStyle" => {
-                let r = Box::new(args.remove(0));
-                let s = Box::new(args.remove(0));
-                let a = Box::new(args.remove(0));
-                let f = Box::new(args.remove(0));
+                let r = self.take_arg(name, &mut args)?;
+                let s = self.take_arg(name, &mut args)?;
+                let a = self.take_arg(name, &mut args)?;
+                let f = self.take_arg(name, &mut args)?;
                 let (i, h) = if args.len() >= 2 {
                     (
-                        Some(Box::new(args.remove(0))),
-                        Some(Box::new(args.remove(0))),
+                        Some(self.take_arg(name, &mut args)?),
+                        Some(self.take_arg(name, &mut args)?),
                     )
                 } else {
                     (None, None)
                 };
                 Node::UISetStyle(r, s, a, f, i, h)
             }
-            "Concat" => Node::Concat(Box::new(args.remove(0)), Box::new(args.remove(0))),
-            "ArrayLen" => Node::ArrayLen(Box::new(args.remove(0))),
-            "ArrayPush" => Node::ArrayPush(Box::new(args.remove(0)), Box::new(args.remove(0))),
-            "ArrayGet" => Node::ArrayGet(Box::new(args.remove(0)), Box::new(args.remove(0))),
-            "ArraySet" => Node::ArraySet(Box::new(args.remove(0)), Box::new(args.remove(0)), Box::new(args.remove(0))),
+            "Concat" => Node::Concat(self.take_arg(name, &mut args)?, self.take_arg(name, &mut args)?),
+            "ArrayLen" => Node::ArrayLen(self.take_arg(name, &mut args)?),
+            "ArrayPush" => Node::ArrayPush(self.take_arg(name, &mut args)?, self.take_arg(name, &mut args)?),
+            "ArrayGet" => Node::ArrayGet(self.take_arg(name, &mut args)?, self.take_arg(name, &mut args)?),
+            "ArraySet" => Node::ArraySet(self.take_arg(name, &mut args)?, self.take_arg(name, &mut args)?, self.take_arg(name, &mut args)?),
             "MapCreate" => Node::MapCreate,
-            "MapGet" => Node::MapGet(Box::new(args.remove(0)), Box::new(args.remove(0))),
-            "MapSet" => Node::MapSet(Box::new(args.remove(0)), Box::new(args.remove(0)), Box::new(args.remove(0))),
-            "MapHasKey" => Node::MapHasKey(Box::new(args.remove(0)), Box::new(args.remove(0))),
-            "ToString" => Node::ToString(Box::new(args.remove(0))),
-            "FileRead" => Node::FileRead(Box::new(args.remove(0))),
-            "FSRead" => Node::FSRead(Box::new(args.remove(0))),
-            "FSWrite" => Node::FSWrite(Box::new(args.remove(0)), Box::new(args.remove(0))),
+            "MapGet" => Node::MapGet(self.take_arg(name, &mut args)?, self.take_arg(name, &mut args)?),
+            "MapSet" => Node::MapSet(self.take_arg(name, &mut args)?, self.take_arg(name, &mut args)?, self.take_arg(name, &mut args)?),
+            "MapHasKey" => Node::MapHasKey(self.take_arg(name, &mut args)?, self.take_arg(name, &mut args)?),
+            "ToString" => Node::ToString(self.take_arg(name, &mut args)?),
+            "FileRead" => Node::FileRead(self.take_arg(name, &mut args)?),
+            "FileReadBytes" => Node::FileReadBytes(self.take_arg(name, &mut args)?),
+            "FileWriteBytes" => Node::FileWriteBytes(self.take_arg(name, &mut args)?, self.take_arg(name, &mut args)?),
+            "FSRead" => Node::FSRead(self.take_arg(name, &mut args)?),
+            "FSWrite" => Node::FSWrite(self.take_arg(name, &mut args)?, self.take_arg(name, &mut args)?),
             "CheckCollision" => Node::CheckCollision {
-                a_min: Box::new(args.remove(0)),
-                a_max: Box::new(args.remove(0)),
-                b_min: Box::new(args.remove(0)),
-                b_max: Box::new(args.remove(0)),
+                a_min: self.take_arg(name, &mut args)?,
+                a_max: self.take_arg(name, &mut args)?,
+                b_min: self.take_arg(name, &mut args)?,
+                b_max: self.take_arg(name, &mut args)?,
             },
             _ => Node::Call(name.to_string(), args), // Default to local Call
-        }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_errors_are_returned_as_diagnostics() {
+        assert_eq!(
+            try_parse("let x = 1;\nlet y = 2 $ 3;").unwrap_err(),
+            r#"{"diagnostic": {"line": 2, "col": 11, "hint": "Unexpected char '$'"}}"#
+        );
+        assert_eq!(
+            try_parse("let x = 99999999999999999999;").unwrap_err(),
+            r#"{"diagnostic": {"line": 1, "col": 9, "hint": "Invalid number literal '99999999999999999999'"}}"#
+        );
+        assert!(try_parse("Print();").unwrap_err().contains("Print is missing an argument"));
+        assert!(try_parse("if (x { }").unwrap_err().contains(r#"Expected RParen, found LBrace"#));
+        assert!(try_parse("let s = \"ok\";").is_ok());
+    }
+
+    #[test]
+    fn test_await_is_a_statement() {
+        let await_p = || Box::new(Node::Await(Box::new(Node::Identifier("p".into()))));
+        assert_eq!(
+            try_parse("await p;\nlet a = await p;\na = await p;\nfn f() { return await p; }").unwrap(),
+            Node::Block(vec![
+                *await_p(),
+                Node::Assign("a".into(), await_p()),
+                Node::Assign("a".into(), await_p()),
+                Node::FnDef("f".into(), vec![], Box::new(Node::Block(vec![Node::Return(await_p())]))),
+            ])
+        );
+        assert!(try_parse("let x = 1 + await p;").unwrap_err().contains("await is only allowed as a statement"));
+        assert!(try_parse("Print(await p);").unwrap_err().contains("await is only allowed as a statement"));
+        assert!(try_parse("let x = await p + 1;").unwrap_err().contains("await must be the whole value of its statement"));
     }
 }
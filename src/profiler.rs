@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Accumulated statistics for one function or one node kind.
+#[derive(Debug, Clone, Default)]
+pub struct ProfileStat {
+    pub count: u64,
+    /// Wall time from entry to exit. Recursive re-entries are only counted once.
+    pub inclusive: Duration,
+    /// Inclusive time minus time spent in nested entries of the same table.
+    pub exclusive: Duration,
+}
+
+struct Frame {
+    start: Instant,
+    child: Duration,
+}
+
+/// Opt-in evaluator profiler. Attach with `engine.profiler = Some(Box::new(Profiler::new()))`;
+/// when `None` the evaluator skips all bookkeeping.
+#[derive(Default)]
+pub struct Profiler {
+    pub nodes: HashMap<&'static str, ProfileStat>,
+    pub functions: HashMap<String, ProfileStat>,
+    node_stack: Vec<(&'static str, Frame)>,
+    fn_stack: Vec<(String, Frame)>,
+    node_active: HashMap<&'static str, u32>,
+    fn_active: HashMap<String, u32>,
+    /// Exclusive time per function call path (`<main>;a;b`), for folded-stack output.
+    stacks: HashMap<String, Duration>,
+    /// Inclusive time of calls made directly from top-level code.
+    top_level_fn_time: Duration,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn enter_node(&mut self, kind: &'static str) {
+        *self.node_active.entry(kind).or_insert(0) += 1;
+        self.node_stack.push((kind, Frame { start: Instant::now(), child: Duration::ZERO }));
+    }
+
+    pub fn exit_node(&mut self) {
+        let Some((kind, frame)) = self.node_stack.pop() else { return };
+        let elapsed = frame.start.elapsed();
+        let active = self.node_active.entry(kind).or_insert(1);
+        *active -= 1;
+        let outermost = *active == 0;
+        let stat = self.nodes.entry(kind).or_default();
+        stat.count += 1;
+        stat.exclusive += elapsed.saturating_sub(frame.child);
+        if outermost {
+            stat.inclusive += elapsed;
+        }
+        if let Some((_, parent)) = self.node_stack.last_mut() {
+            parent.child += elapsed;
+        }
+    }
+
+    pub fn enter_fn(&mut self, name: &str) {
+        *self.fn_active.entry(name.to_string()).or_insert(0) += 1;
+        self.fn_stack.push((name.to_string(), Frame { start: Instant::now(), child: Duration::ZERO }));
+    }
+
+    pub fn exit_fn(&mut self) {
+        let path = self.fn_path();
+        let Some((name, frame)) = self.fn_stack.pop() else { return };
+        let elapsed = frame.start.elapsed();
+        let exclusive = elapsed.saturating_sub(frame.child);
+        let active = self.fn_active.entry(name.clone()).or_insert(1);
+        *active -= 1;
+        let outermost = *active == 0;
+        let stat = self.functions.entry(name).or_default();
+        stat.count += 1;
+        stat.exclusive += exclusive;
+        if outermost {
+            stat.inclusive += elapsed;
+        }
+        *self.stacks.entry(path).or_default() += exclusive;
+        match self.fn_stack.last_mut() {
+            Some((_, parent)) => parent.child += elapsed,
+            None => self.top_level_fn_time += elapsed,
+        }
+    }
+
+    fn fn_path(&self) -> String {
+        let mut path = String::from("<main>");
+        for (name, _) in &self.fn_stack {
+            path.push(';');
+            path.push_str(name);
+        }
+        path
+    }
+
+    /// Total evaluated time (sum of exclusive time over all node kinds).
+    pub fn total(&self) -> Duration {
+        self.nodes.values().map(|s| s.exclusive).sum()
+    }
+
+    /// Time spent outside of any function body.
+    pub fn main_self(&self) -> Duration {
+        self.total().saturating_sub(self.top_level_fn_time)
+    }
+
+    /// Human-readable tables, each sorted by exclusive time (descending).
+    pub fn report(&self) -> String {
+        let total = self.total().as_secs_f64().max(f64::EPSILON);
+        let mut out = String::new();
+        out.push_str(&format!("=== Profile: {:.3} ms evaluated ===\n", total * 1000.0));
+
+        let mut fns: Vec<(&str, &ProfileStat)> = self.functions.iter().map(|(k, v)| (k.as_str(), v)).collect();
+        let main = ProfileStat { count: 1, inclusive: self.total(), exclusive: self.main_self() };
+        fns.push(("<main>", &main));
+        out.push_str(&Self::table("Function", &mut fns, total));
+
+        let mut nodes: Vec<(&str, &ProfileStat)> = self.nodes.iter().map(|(k, v)| (*k, v)).collect();
+        out.push('\n');
+        out.push_str(&Self::table("Node kind", &mut nodes, total));
+        out
+    }
+
+    fn table(title: &str, rows: &mut Vec<(&str, &ProfileStat)>, total: f64) -> String {
+        rows.sort_by(|a, b| b.1.exclusive.cmp(&a.1.exclusive).then(a.0.cmp(b.0)));
+        let mut out = format!(
+            "{:<28} {:>10} {:>12} {:>12} {:>7}\n",
+            title, "calls", "incl (ms)", "excl (ms)", "excl %"
+        );
+        for (name, s) in rows.iter() {
+            out.push_str(&format!(
+                "{:<28} {:>10} {:>12.3} {:>12.3} {:>6.1}%\n",
+                name,
+                s.count,
+                s.inclusive.as_secs_f64() * 1000.0,
+                s.exclusive.as_secs_f64() * 1000.0,
+                s.exclusive.as_secs_f64() / total * 100.0
+            ));
+        }
+        out
+    }
+
+    /// Folded-stack lines (`<main>;outer;inner <microseconds>`), as consumed by
+    /// flamegraph.pl and inferno-flamegraph.
+    pub fn folded(&self) -> String {
+        let mut stacks: Vec<(&String, &Duration)> = self.stacks.iter().collect();
+        stacks.sort();
+        let mut out = String::new();
+        let main_us = self.main_self().as_micros();
+        if main_us > 0 {
+            out.push_str(&format!("<main> {}\n", main_us));
+        }
+        for (path, d) in stacks {
+            let us = d.as_micros();
+            if us > 0 {
+                out.push_str(&format!("{} {}\n", path, us));
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::{ExecResult, ExecutionEngine, RelType};
+
+    #[test]
+    fn test_profiler_counts_functions_and_nodes() {
+        let src = "fn fib(n) { if (n < 2) { return n; } return fib(n - 1) + fib(n - 2); }\nfn run() { return fib(10); }\nrun();";
+        let ast = crate::parser::try_parse(src).unwrap();
+        let mut engine = ExecutionEngine::new();
+        engine.profiler = Some(Box::new(Profiler::new()));
+        assert!(matches!(engine.execute(&ast), ExecResult::Value(RelType::Int(55))));
+
+        let p = engine.profiler.take().unwrap();
+        assert_eq!(p.functions["run"].count, 1);
+        assert_eq!(p.functions["fib"].count, 177);
+        assert_eq!(p.nodes["Call"].count, 178);
+        assert!(p.functions["fib"].inclusive <= p.functions["run"].inclusive);
+
+        let folded = p.folded();
+        assert!(folded.lines().all(|l| l.starts_with("<main>")));
+        assert!(p.report().contains("fib"));
+    }
+}
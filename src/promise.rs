@@ -0,0 +1,176 @@
+use crate::executor::{ExecResult, ExecutionEngine, NativeHandle, RelType};
+use crate::natives::convert::{arg, expect_args, native_result};
+use crate::natives::registry::{self, PromiseState};
+
+/// Wraps a plain value in an already-resolved promise so combinators can mix both.
+fn into_promise(v: RelType) -> NativeHandle {
+    if let RelType::Handle(h) = &v
+        && registry::registry_promise_state(h.0).is_some()
+    {
+        return h.clone();
+    }
+    let id = registry::registry_promise_new();
+    registry::registry_promise_settle(id, Ok(v));
+    NativeHandle(id)
+}
+
+impl ExecutionEngine {
+    /// Natives that produce or combine promises:
+    /// `Fetch(method, url)`, `all(promises)` and `race(promises)`.
+    pub(crate) fn call_promise_native(&mut self, name: &str, args: &[RelType]) -> Option<ExecResult> {
+        let res = match name {
+            "Fetch" => self.fetch_promise(args),
+            "all" | "race" => expect_args(args, &["promises"], name)
+                .and_then(|_| arg::<Vec<RelType>>(args, 0, "promises", name))
+                .map(|items| {
+                    let inputs = items.into_iter().map(into_promise).collect();
+                    RelType::Handle(NativeHandle(registry::registry_promise_combine(inputs, name == "race")))
+                }),
+            _ => return None,
+        };
+        Some(native_result(res))
+    }
+
+    fn fetch_promise(&mut self, args: &[RelType]) -> Result<RelType, ExecResult> {
+        expect_args(args, &["method", "url"], "Fetch")?;
+        let method = arg::<String>(args, 0, "method", "Fetch")?;
+        let url = arg::<String>(args, 1, "url", "Fetch")?;
+        if !self.permissions.allow_network {
+            return Err(ExecResult::Fault {
+                msg: "Permission Denied: allow_network is false. Use --allow-network flag.".into(),
+                node: "Native::Fetch".into(),
+            });
+        }
+        let policy = crate::net_policy::NetPolicy::new(&self.permissions.allowed_domains);
+        if let Err(e) = policy.check_url(&url) {
+            return Err(ExecResult::Fault { msg: format!("Fetch: {}", e), node: "Native::Fetch".into() });
+        }
+        let Some(bridge) = &self.async_bridge else {
+            return Err(ExecResult::Fault { msg: "AsyncBridge not initialized".into(), node: "Native::Fetch".into() });
+        };
+        let (promise, settler) = registry::PromiseSettler::new();
+        bridge.dispatch_fetch_promise(method, url, policy, settler);
+        Ok(RelType::Handle(promise))
+    }
+
+    /// `await v`: blocks the script until the promise settles. Non-promise
+    /// values are returned unchanged.
+    ///
+    /// `await` is a statement (see `Node::Await`), and no script code runs
+    /// while it waits: only `Delay` timers and `Fetch` replies are settled
+    /// (`settle_ready_promises`), and tasks and the host settle theirs from
+    /// other threads. Timer callbacks, `On` handlers, legacy
+    /// `Fetch(...) => { }` blocks and hot reloads stay queued until the script
+    /// returns to the event loop, so a promise only such a callback would
+    /// settle is waited on until the engine is interrupted. Promises made by
+    /// `Fetch`, `Delay` and `Spawn` reject when their producer goes away
+    /// without settling them.
+    pub fn await_value(&mut self, v: RelType) -> ExecResult {
+        let RelType::Handle(h) = &v else { return ExecResult::Value(v) };
+        let id = h.0;
+        loop {
+            let state = registry::registry_promise_state(id);
+            if matches!(state, Some(PromiseState::Resolved(_) | PromiseState::Rejected(_))) {
+                // The task is done; a later `Join` reads the promise instead.
+                self.tasks.remove(&id);
+            }
+            match state {
+                None => return ExecResult::Value(v),
+                Some(PromiseState::Resolved(val)) => return ExecResult::Value(val),
+                Some(PromiseState::Rejected(msg)) => return ExecResult::Fault { msg, node: "Node::Await".into() },
+                Some(PromiseState::Pending) => {
+                    if let Err(fault) = self.check_interrupt() {
+                        return fault;
+                    }
+                    self.settle_ready_promises();
+                    if matches!(registry::registry_promise_state(id), Some(PromiseState::Pending)) {
+                        self.wait_for_promises();
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn run(engine: &mut ExecutionEngine, src: &str) -> ExecResult {
+        engine.execute(&crate::parser::try_parse(src).unwrap())
+    }
+
+    #[test]
+    fn test_await_settled_from_another_thread() {
+        let mut engine = ExecutionEngine::new();
+        let id = registry::registry_promise_new();
+        engine.memory.insert("p".into(), RelType::Handle(NativeHandle(id)));
+        let worker = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            registry::registry_promise_settle(id, Ok(RelType::Int(41)))
+        });
+        assert!(matches!(run(&mut engine, "let v = await p;\nv + 1"), ExecResult::Value(RelType::Int(42))));
+        assert!(worker.join().unwrap());
+        assert!(matches!(run(&mut engine, "await 7"), ExecResult::Value(RelType::Int(7))));
+    }
+
+    #[test]
+    fn test_await_abandoned_promise_faults() {
+        let mut engine = ExecutionEngine::new();
+        let (promise, settler) = registry::PromiseSettler::new();
+        engine.memory.insert("p".into(), RelType::Handle(promise));
+        let producer = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            drop(settler);
+        });
+        match run(&mut engine, "await p") {
+            ExecResult::Fault { msg, node } => {
+                assert_eq!(msg, "Promise abandoned: its producer stopped before settling it");
+                assert_eq!(node, "Node::Await");
+            }
+            other => panic!("expected the abandoned promise to reject, got {}", other),
+        }
+        producer.join().unwrap();
+    }
+
+    #[test]
+    fn test_await_runs_no_callbacks() {
+        let mut engine = ExecutionEngine::new();
+        let clock = engine.use_virtual_clock();
+        let src = "let log = [];\nfn tick() { ArrayPush(log, \"tick\"); }\nSetTimeout(tick, 10);\n\
+            await Delay(50);\nArrayPush(log, \"resumed\");";
+        assert!(!matches!(run(&mut engine, src), ExecResult::Fault { .. }));
+        assert_eq!(clock.now_ms(), 50);
+        assert_eq!(engine.memory["log"], RelType::Array(vec![RelType::Str("resumed".into())]));
+        assert!(matches!(engine.run_event_loop(), ExecResult::Value(RelType::Void)));
+        assert_eq!(engine.memory["log"], RelType::Array(vec![RelType::Str("resumed".into()), RelType::Str("tick".into())]));
+    }
+
+    #[test]
+    fn test_all_and_race() {
+        let mut engine = ExecutionEngine::new();
+        let a = registry::registry_promise_new();
+        let b = registry::registry_promise_new();
+        engine.memory.insert("a".into(), RelType::Handle(NativeHandle(a)));
+        engine.memory.insert("b".into(), RelType::Handle(NativeHandle(b)));
+        registry::registry_promise_settle(b, Ok(RelType::Str("fast".into())));
+
+        assert!(matches!(run(&mut engine, "await race([a, b])"), ExecResult::Value(RelType::Str(s)) if s == "fast"));
+        registry::registry_promise_settle(a, Ok(RelType::Int(1)));
+        let expected = RelType::Array(vec![RelType::Int(1), RelType::Str("fast".into()), RelType::Int(3)]);
+        assert!(matches!(run(&mut engine, "await all([a, b, 3])"), ExecResult::Value(v) if v == expected));
+
+        let c = registry::registry_promise_new();
+        engine.memory.insert("c".into(), RelType::Handle(NativeHandle(c)));
+        registry::registry_promise_settle(c, Err("HTTP 404 Error: gone".into()));
+        match run(&mut engine, "await all([a, c])") {
+            ExecResult::Fault { msg, node } => assert_eq!((msg.as_str(), node.as_str()), ("HTTP 404 Error: gone", "Node::Await")),
+            other => panic!("expected rejection, got {}", other),
+        }
+        match run(&mut engine, "Fetch(\"GET\", \"http://localhost\")") {
+            ExecResult::Fault { msg, .. } => assert!(msg.starts_with("Permission Denied"), "{}", msg),
+            other => panic!("expected permission fault, got {}", other),
+        }
+    }
+}
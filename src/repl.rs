@@ -0,0 +1,316 @@
+use crate::ast::Node;
+use crate::executor::{ExecResult, ExecutionEngine, RelType};
+use crate::optimizer::{self, TypeChecker};
+use std::io::{BufRead, Write};
+
+/// Result of feeding one line of input into the REPL.
+pub enum ReplStep {
+    /// The input is not complete yet (unbalanced braces); keep reading.
+    NeedMore,
+    /// Text to show the user (may be empty).
+    Output(String),
+    Quit,
+}
+
+/// Interactive session that keeps a single `ExecutionEngine` alive across inputs.
+/// Globals, functions and permissions persist until the session ends.
+pub struct Repl {
+    pub engine: ExecutionEngine,
+    pub typer: TypeChecker,
+    buffer: String,
+}
+
+impl Default for Repl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+const HELP: &str = "\
+Meta-commands:
+  :ast <code>     Show the parsed Node tree as JSON
+  :type <code>    Run the TypeChecker on <code> without executing it
+  :opt <code>     Show the optimizer output for <code>
+  :vars           List global variables
+  :load <file>    Execute a .knoten or JSON AST file in this session
+  :perm [+/-flag] Show or toggle permissions (read, write, network)
+  :help           Show this help
+  :quit           Leave the REPL";
+
+impl Repl {
+    pub fn new() -> Self {
+        Self::with_engine(ExecutionEngine::new())
+    }
+
+    pub fn with_engine(engine: ExecutionEngine) -> Self {
+        Self { engine, typer: TypeChecker::new(), buffer: String::new() }
+    }
+
+    /// True while a multiline statement is being collected.
+    pub fn is_continuing(&self) -> bool {
+        !self.buffer.is_empty()
+    }
+
+    pub fn feed_line(&mut self, line: &str) -> ReplStep {
+        if self.buffer.is_empty() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                return ReplStep::Output(String::new());
+            }
+            if let Some(cmd) = trimmed.strip_prefix(':') {
+                return self.meta_command(cmd);
+            }
+        }
+        self.buffer.push_str(line);
+        self.buffer.push('\n');
+        if nesting_depth(&self.buffer) > 0 {
+            return ReplStep::NeedMore;
+        }
+        let src = std::mem::take(&mut self.buffer);
+        ReplStep::Output(self.eval_source(&src))
+    }
+
+    /// Parses, type-checks, optimizes and executes `src` in the persistent engine.
+    /// Returns the text the REPL should print.
+    pub fn eval_source(&mut self, src: &str) -> String {
+        let ast = match crate::parser::try_parse(src) {
+            Ok(ast) => ast,
+            Err(diag) => return format!("[ParseError] {}", diag),
+        };
+        self.eval_node(ast)
+    }
+
+    fn eval_node(&mut self, ast: Node) -> String {
+        // Top-level statements are checked one by one so that their bindings land
+        // in the checker's global scope and stay visible to later inputs.
+        let statements = match ast {
+            Node::Block(stmts) => stmts,
+            other => vec![other],
+        };
+        for stmt in &statements {
+            let _ = self.typer.check(stmt);
+        }
+        if !self.typer.errors.is_empty() {
+            let errs: Vec<String> = self.typer.errors.drain(..).map(|e| format!(" - {}", e)).collect();
+            return format!("[TypeError] Static Type Inference Failed:\n{}", errs.join("\n"));
+        }
+
+        let mut last = RelType::Void;
+        for stmt in statements {
+            let stmt = optimizer::optimize(stmt);
+            match self.engine.execute(&stmt) {
+                ExecResult::Value(v) | ExecResult::ReturnBlockInfo(v) => last = v,
+                fault => return fault.to_string(),
+            }
+        }
+        match last {
+            RelType::Void => String::new(),
+            RelType::Str(s) => format!("\"{}\"", s),
+            v => v.to_string(),
+        }
+    }
+
+    fn meta_command(&mut self, cmd: &str) -> ReplStep {
+        let (name, rest) = match cmd.split_once(char::is_whitespace) {
+            Some((n, r)) => (n, r.trim()),
+            None => (cmd, ""),
+        };
+        let out = match name {
+            "q" | "quit" | "exit" => return ReplStep::Quit,
+            "help" | "h" => HELP.to_string(),
+            "ast" => match crate::parser::try_parse(rest) {
+                Ok(ast) => serde_json::to_string_pretty(&ast).unwrap_or_else(|e| e.to_string()),
+                Err(diag) => format!("[ParseError] {}", diag),
+            },
+            "type" => match crate::parser::try_parse(rest) {
+                Ok(ast) => self.describe_type(&ast),
+                Err(diag) => format!("[ParseError] {}", diag),
+            },
+            "opt" => match crate::parser::try_parse(rest) {
+                Ok(ast) => {
+                    let before = optimizer::count_nodes(&ast);
+                    let opt = optimizer::optimize(ast);
+                    let after = optimizer::count_nodes(&opt);
+                    format!(
+                        "{}\n// {} -> {} nodes",
+                        serde_json::to_string_pretty(&opt).unwrap_or_else(|e| e.to_string()),
+                        before,
+                        after
+                    )
+                }
+                Err(diag) => format!("[ParseError] {}", diag),
+            },
+            "vars" => self.describe_vars(),
+            "load" => self.load_file(rest),
+            "perm" => self.toggle_permissions(rest),
+            _ => format!("Unknown command ':{}'. Type :help for a list of commands.", name),
+        };
+        ReplStep::Output(out)
+    }
+
+    fn describe_type(&mut self, ast: &Node) -> String {
+        // Checking must not leak bindings into the session's scopes.
+        let saved_scopes = self.typer.scopes.clone();
+        let stmts: Vec<&Node> = match ast {
+            Node::Block(stmts) => stmts.iter().collect(),
+            other => vec![other],
+        };
+        let mut ty = Ok(crate::ast::Type::Void);
+        for stmt in stmts {
+            ty = self.typer.check(stmt);
+        }
+        self.typer.scopes = saved_scopes;
+        let errors: Vec<String> = self.typer.errors.drain(..).collect();
+        let mut out = match ty {
+            Ok(t) => format!("{:?}", t),
+            Err(e) => format!("[TypeError] {}", e),
+        };
+        for e in errors {
+            out.push_str(&format!("\n - {}", e));
+        }
+        out
+    }
+
+    fn describe_vars(&self) -> String {
+        let mut keys: Vec<&String> = self.engine.memory.keys().collect();
+        keys.sort();
+        let lines: Vec<String> = keys
+            .into_iter()
+            .map(|k| match &self.engine.memory[k] {
+                RelType::FnDef(_, params, _) => format!("{} = fn({})", k, params.join(", ")),
+                RelType::Str(s) => format!("{} = \"{}\"", k, s),
+                v => format!("{} = {}", k, v),
+            })
+            .collect();
+        if lines.is_empty() { "(no globals)".to_string() } else { lines.join("\n") }
+    }
+
+    fn load_file(&mut self, path: &str) -> String {
+        if path.is_empty() {
+            return "Usage: :load <file>".to_string();
+        }
+        let src = match std::fs::read_to_string(path) {
+            Ok(s) => s,
+            Err(e) => return format!("Cannot read '{}': {}", path, e),
+        };
+        if path.ends_with(".knoten") {
+            self.eval_source(&src)
+        } else {
+            match serde_json::from_str::<Node>(&src) {
+                Ok(ast) => self.eval_node(ast),
+                Err(e) => format!("[ParseError] Invalid AST JSON: {}", e),
+            }
+        }
+    }
+
+    fn toggle_permissions(&mut self, spec: &str) -> String {
+        for token in spec.split_whitespace() {
+            let (enable, flag) = match token.split_at(1) {
+                ("+", f) => (true, f),
+                ("-", f) => (false, f),
+                _ => return format!("Invalid permission toggle '{}'. Use +read, -write, +network.", token),
+            };
+            match flag {
+                "read" => self.engine.permissions.allow_fs_read = enable,
+                "write" => self.engine.permissions.allow_fs_write = enable,
+                "network" | "net" => self.engine.permissions.allow_network = enable,
+                _ => return format!("Unknown permission '{}'. Known: read, write, network.", flag),
+            }
+        }
+        let p = &self.engine.permissions;
+        format!("read={} write={} network={}", p.allow_fs_read, p.allow_fs_write, p.allow_network)
+    }
+}
+
+/// Counts unclosed `{`, `(` and `[` outside of string literals and `//` comments.
+fn nesting_depth(src: &str) -> i64 {
+    let mut depth = 0i64;
+    let mut in_string = false;
+    let mut chars = src.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_string {
+            if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '/' if chars.peek() == Some(&'/') => {
+                for c2 in chars.by_ref() {
+                    if c2 == '\n' {
+                        break;
+                    }
+                }
+            }
+            '{' | '(' | '[' => depth += 1,
+            '}' | ')' | ']' => depth -= 1,
+            _ => {}
+        }
+    }
+    depth
+}
+
+/// Runs the interactive read-eval-print loop on stdin/stdout.
+pub fn run(engine: ExecutionEngine) {
+    let mut repl = Repl::with_engine(engine);
+    println!("KnotenCore REPL — type :help for commands, :quit to exit.");
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("{}", if repl.is_continuing() { "...> " } else { "knc> " });
+        let _ = std::io::stdout().flush();
+        let line = match lines.next() {
+            Some(Ok(l)) => l,
+            _ => break,
+        };
+        match repl.feed_line(&line) {
+            ReplStep::NeedMore => {}
+            ReplStep::Output(out) => {
+                if !out.is_empty() {
+                    println!("{}", out);
+                }
+            }
+            ReplStep::Quit => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(step: ReplStep) -> String {
+        match step {
+            ReplStep::Output(s) => s,
+            ReplStep::NeedMore => "<more>".into(),
+            ReplStep::Quit => "<quit>".into(),
+        }
+    }
+
+    #[test]
+    fn test_state_persists_between_inputs() {
+        let mut repl = Repl::new();
+        assert_eq!(output(repl.feed_line("let x = 40;")), "40");
+        assert_eq!(output(repl.feed_line("x + 2")), "42");
+    }
+
+    #[test]
+    fn test_multiline_function_definition() {
+        let mut repl = Repl::new();
+        assert!(matches!(repl.feed_line("fn double(n) {"), ReplStep::NeedMore));
+        assert!(matches!(repl.feed_line("    return n * 2;"), ReplStep::NeedMore));
+        assert_eq!(output(repl.feed_line("}")), "");
+        assert_eq!(output(repl.feed_line("double(21)")), "42");
+        assert!(output(repl.feed_line(":vars")).contains("double = fn(n)"));
+    }
+
+    #[test]
+    fn test_permission_toggle_and_parse_errors() {
+        let mut repl = Repl::new();
+        assert_eq!(output(repl.feed_line(":perm +read")), "read=true write=false network=false");
+        assert!(repl.engine.permissions.allow_fs_read);
+        assert!(output(repl.feed_line("let = 1;")).starts_with("[ParseError]"));
+        assert_eq!(output(repl.feed_line(":type 1 + 2")), "Int");
+    }
+}
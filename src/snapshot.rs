@@ -0,0 +1,285 @@
+use crate::executor::{ExecutionEngine, RelType, StackFrame};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Bumped whenever the snapshot layout changes incompatibly.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+pub struct CameraSnapshot {
+    pub active: bool,
+    pub pos: [f32; 3],
+    pub yaw: f32,
+    pub pitch: f32,
+    pub fov: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PhysicsSnapshot {
+    pub enabled: bool,
+    pub interaction_enabled: bool,
+    pub velocity_y: f32,
+    pub is_grounded: bool,
+}
+
+/// Versioned, JSON-serializable copy of the resumable parts of an `ExecutionEngine`.
+///
+/// Native handles inside values keep their original ids; `handles` holds the
+/// registry-provided description of each one so `restore` can recreate them.
+#[derive(Serialize, Deserialize)]
+pub struct EngineSnapshot {
+    pub version: u32,
+    pub elapsed_ms: u64,
+    pub globals: HashMap<String, RelType>,
+    pub call_stack: Vec<HashMap<String, RelType>>,
+    pub handles: BTreeMap<i64, serde_json::Value>,
+    pub voxel_map_active: bool,
+    pub voxel_map: Vec<([i64; 3], u8)>,
+    pub camera: CameraSnapshot,
+    pub physics: PhysicsSnapshot,
+    pub world_aabbs: Vec<([f32; 3], [f32; 3])>,
+}
+
+/// Collects every handle id in `val`, failing with the location of the first
+/// handle the registry cannot serialize.
+fn collect_handles(val: &RelType, path: &str, out: &mut BTreeMap<i64, serde_json::Value>) -> Result<(), String> {
+    match val {
+        RelType::Handle(h) => {
+            if let std::collections::btree_map::Entry::Vacant(slot) = out.entry(h.0) {
+                let desc = crate::natives::registry::registry_snapshot_handle(h.0)
+                    .map_err(|e| format!("Cannot snapshot '{}': {}", path, e))?;
+                slot.insert(desc);
+            }
+        }
+        RelType::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                collect_handles(item, &format!("{}[{}]", path, i), out)?;
+            }
+        }
+        RelType::Object(map) => {
+            for (k, v) in map {
+                collect_handles(v, &format!("{}.{}", path, k), out)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Walks a serialized `RelType` and calls `f` on the id of every `Handle`.
+/// Only the value shape is followed, so user data that merely looks like a handle is untouched.
+fn visit_handle_ids(val: &mut serde_json::Value, f: &mut dyn FnMut(&mut serde_json::Value)) {
+    let Some(obj) = val.as_object_mut() else { return };
+    if let Some(id) = obj.get_mut("Handle") {
+        f(id);
+    } else if let Some(serde_json::Value::Array(items)) = obj.get_mut("Array") {
+        for item in items {
+            visit_handle_ids(item, f);
+        }
+    } else if let Some(serde_json::Value::Object(map)) = obj.get_mut("Object") {
+        for v in map.values_mut() {
+            visit_handle_ids(v, f);
+        }
+    }
+}
+
+fn visit_snapshot_values(doc: &mut serde_json::Value, f: &mut dyn FnMut(&mut serde_json::Value)) {
+    if let Some(serde_json::Value::Object(globals)) = doc.get_mut("globals") {
+        for v in globals.values_mut() {
+            visit_handle_ids(v, f);
+        }
+    }
+    if let Some(serde_json::Value::Array(frames)) = doc.get_mut("call_stack") {
+        for frame in frames {
+            if let serde_json::Value::Object(locals) = frame {
+                for v in locals.values_mut() {
+                    visit_handle_ids(v, f);
+                }
+            }
+        }
+    }
+}
+
+impl ExecutionEngine {
+    /// Captures globals, the call stack, voxel map, camera, physics and world AABBs
+    /// as a versioned JSON document.
+    pub fn snapshot(&self) -> Result<String, String> {
+        let mut handles = BTreeMap::new();
+        for (name, val) in &self.memory {
+            collect_handles(val, name, &mut handles)?;
+        }
+        for (depth, frame) in self.call_stack.iter().enumerate() {
+            for (name, val) in &frame.locals {
+                collect_handles(val, &format!("frame#{}:{}", depth, name), &mut handles)?;
+            }
+        }
+
+        let mut voxel_map: Vec<([i64; 3], u8)> = self.voxel_map.iter().map(|(k, v)| (*k, *v)).collect();
+        voxel_map.sort();
+
+        let snap = EngineSnapshot {
+            version: SNAPSHOT_VERSION,
+            elapsed_ms: self.now_ms(),
+            globals: self.memory.clone(),
+            call_stack: self.call_stack.iter().map(|f| f.locals.clone()).collect(),
+            handles,
+            voxel_map_active: self.voxel_map_active,
+            voxel_map,
+            camera: CameraSnapshot {
+                active: self.camera_active,
+                pos: self.camera_pos,
+                yaw: self.camera_yaw,
+                pitch: self.camera_pitch,
+                fov: self.camera_fov,
+            },
+            physics: PhysicsSnapshot {
+                enabled: self.physics_enabled,
+                interaction_enabled: self.interaction_enabled,
+                velocity_y: self.velocity_y,
+                is_grounded: self.is_grounded,
+            },
+            world_aabbs: self.world_aabbs.iter().map(|a| (a.min, a.max)).collect(),
+        };
+        serde_json::to_string(&snap).map_err(|e| format!("Snapshot serialization failed: {}", e))
+    }
+
+    /// Replaces the engine state with a document produced by `snapshot`.
+    /// Native handles are recreated in the registry under fresh ids. On error the
+    /// engine is left unchanged.
+    pub fn restore(&mut self, data: &str) -> Result<(), String> {
+        let mut doc: serde_json::Value =
+            serde_json::from_str(data).map_err(|e| format!("Invalid snapshot: {}", e))?;
+        match doc.get("version").and_then(|v| v.as_u64()) {
+            Some(v) if v == SNAPSHOT_VERSION as u64 => {}
+            Some(v) => return Err(format!("Unsupported snapshot version {} (expected {})", v, SNAPSHOT_VERSION)),
+            None => return Err("Invalid snapshot: missing version".to_string()),
+        }
+
+        // Every deserialized `NativeHandle` owns one registry reference, so each
+        // recreated handle starts with as many references as the snapshot holds.
+        let mut ref_counts: BTreeMap<i64, usize> = BTreeMap::new();
+        visit_snapshot_values(&mut doc, &mut |id| {
+            if let Some(id) = id.as_i64() {
+                *ref_counts.entry(id).or_insert(0) += 1;
+            }
+        });
+        // Validate the layout before touching the registry. Handle ids are replaced with -1,
+        // which the registry ignores, so the throwaway values release nothing when dropped.
+        let mut shape = doc.clone();
+        visit_snapshot_values(&mut shape, &mut |id| *id = (-1).into());
+        serde_json::from_value::<EngineSnapshot>(shape).map_err(|e| format!("Invalid snapshot: {}", e))?;
+
+        let descs: BTreeMap<i64, serde_json::Value> = match doc.get("handles") {
+            Some(h) => serde_json::from_value(h.clone()).map_err(|e| format!("Invalid snapshot handles: {}", e))?,
+            None => BTreeMap::new(),
+        };
+        for old_id in ref_counts.keys() {
+            if !descs.contains_key(old_id) {
+                return Err(format!("Snapshot references handle {} without a description", old_id));
+            }
+        }
+
+        let mut remap: BTreeMap<i64, i64> = BTreeMap::new();
+        for (old_id, count) in &ref_counts {
+            match crate::natives::registry::registry_restore_handle(&descs[old_id], *count) {
+                Ok(new_id) => {
+                    remap.insert(*old_id, new_id);
+                }
+                Err(e) => {
+                    for new_id in remap.values() {
+                        crate::natives::registry::registry_free(*new_id);
+                    }
+                    return Err(format!("Cannot restore handle {}: {}", old_id, e));
+                }
+            }
+        }
+        visit_snapshot_values(&mut doc, &mut |id| {
+            if let Some(new_id) = id.as_i64().and_then(|old| remap.get(&old)) {
+                *id = (*new_id).into();
+            }
+        });
+
+        let snap: EngineSnapshot = serde_json::from_value(doc).map_err(|e| format!("Invalid snapshot: {}", e))?;
+
+        self.memory = snap.globals;
+        self.call_stack = snap.call_stack.into_iter().map(|locals| StackFrame { locals }).collect();
+        if self.call_stack.is_empty() {
+            self.call_stack.push(StackFrame { locals: HashMap::new() });
+        }
+        let now = std::time::Instant::now();
+        self.startup_time = now.checked_sub(std::time::Duration::from_millis(snap.elapsed_ms)).unwrap_or(now);
+        if let crate::timers::Clock::Virtual(clock) = &self.clock {
+            clock.set_ms(snap.elapsed_ms);
+        }
+        self.voxel_map = snap.voxel_map.into_iter().collect();
+        self.voxel_map_active = snap.voxel_map_active;
+        self.voxel_map_dirty = true;
+        self.camera_active = snap.camera.active;
+        self.camera_pos = snap.camera.pos;
+        self.camera_yaw = snap.camera.yaw;
+        self.camera_pitch = snap.camera.pitch;
+        self.camera_fov = snap.camera.fov;
+        self.physics_enabled = snap.physics.enabled;
+        self.interaction_enabled = snap.physics.interaction_enabled;
+        self.velocity_y = snap.physics.velocity_y;
+        self.is_grounded = snap.physics.is_grounded;
+        self.world_aabbs = snap.world_aabbs.into_iter().map(|(min, max)| crate::math::AABB::new(min, max)).collect();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::{ExecResult, NativeHandle};
+    use crate::natives::registry;
+
+    #[test]
+    fn test_snapshot_roundtrip_with_handles() {
+        let mut engine = ExecutionEngine::new();
+        let src = "let score = 41;\nfn bump(x) { return x + 1; }\nlet inv = [\"sword\", 2.5, true];";
+        let ast = crate::parser::try_parse(src).unwrap();
+        assert!(!matches!(engine.execute(&ast), ExecResult::Fault { .. }));
+
+        let counter = registry::registry_create_counter();
+        registry::registry_increment(counter);
+        let h = RelType::Handle(NativeHandle(counter));
+        engine.memory.insert("c".into(), h.clone());
+        engine.memory.insert("same".into(), RelType::Array(vec![h]));
+        engine.voxel_map.insert([1, 2, 3], 7);
+        engine.camera_pos = [4.0, 5.0, 6.0];
+        engine.world_aabbs.push(crate::math::AABB::new([0.0; 3], [1.0; 3]));
+
+        let doc = engine.snapshot().unwrap();
+        let mut restored = ExecutionEngine::new();
+        restored.restore(&doc).unwrap();
+
+        let ast = crate::parser::try_parse("bump(score)").unwrap();
+        assert!(matches!(restored.execute(&ast), ExecResult::Value(RelType::Int(42))));
+        assert_eq!(restored.voxel_map.get(&[1, 2, 3]), Some(&7));
+        assert_eq!(restored.camera_pos, [4.0, 5.0, 6.0]);
+        assert_eq!(restored.world_aabbs.len(), 1);
+
+        // Both references point at one fresh counter that kept its state.
+        let Some(RelType::Handle(NativeHandle(new_id))) = restored.memory.get("c") else { panic!("handle lost") };
+        assert_ne!(*new_id, counter);
+        assert_eq!(registry::registry_get_value(*new_id), 1);
+        assert_eq!(restored.memory["same"], RelType::Array(vec![RelType::Handle(NativeHandle(*new_id))]));
+    }
+
+    #[test]
+    fn test_snapshot_rejects_unserializable_handle() {
+        let mut engine = ExecutionEngine::new();
+        let id = registry::registry_file_create("target/knoten_snapshot_handle.tmp".into());
+        engine.memory.insert("log".into(), RelType::Handle(NativeHandle(id)));
+        let err = engine.snapshot().unwrap_err();
+        engine.memory.clear();
+        let _ = std::fs::remove_file("target/knoten_snapshot_handle.tmp");
+        assert!(err.contains("'log'") && err.contains("File"), "{}", err);
+        assert!(restore_err("{\"version\": 99}").contains("Unsupported snapshot version 99"));
+    }
+
+    fn restore_err(doc: &str) -> String {
+        ExecutionEngine::new().restore(doc).unwrap_err()
+    }
+}
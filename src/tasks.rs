@@ -0,0 +1,264 @@
+use crate::executor::{AgentPermissions, ExecResult, ExecutionEngine, NativeHandle, RelType};
+use crate::natives::convert::{arg, expect_args, native_fault, native_result, rel_type_name};
+use crate::natives::registry;
+use std::time::Duration;
+
+const RECV_SLICE: Duration = Duration::from_millis(50);
+
+/// Errors if `v` holds a handle that must not leave this thread.
+fn check_shareable(v: &RelType) -> Result<(), String> {
+    match v {
+        RelType::Handle(h) => registry::registry_check_shareable(h.0),
+        RelType::Array(items) => items.iter().try_for_each(check_shareable),
+        RelType::Object(map) => map.values().try_for_each(check_shareable),
+        _ => Ok(()),
+    }
+}
+
+/// Child permissions: each flag is the parent's, unless the script turns it off.
+fn narrow(parent: &AgentPermissions, requested: Option<&RelType>) -> Result<AgentPermissions, ExecResult> {
+    let mut perms = parent.clone();
+    let Some(requested) = requested else { return Ok(perms) };
+    let RelType::Object(map) = requested else {
+        return Err(native_fault("Spawn", format!("argument 3 'permissions' expected Object, found {}", rel_type_name(requested))));
+    };
+    for (key, value) in map {
+        let flag = match key.as_str() {
+            "allow_network" => &mut perms.allow_network,
+            "allow_fs_read" => &mut perms.allow_fs_read,
+            "allow_fs_write" => &mut perms.allow_fs_write,
+            other => return Err(native_fault("Spawn", format!("unknown permission '{}'", other))),
+        };
+        match value {
+            RelType::Bool(allowed) => *flag &= *allowed,
+            other => return Err(native_fault("Spawn", format!("permission '{}' expected Bool, found {}", key, rel_type_name(other)))),
+        }
+    }
+    Ok(perms)
+}
+
+fn channel_arg(args: &[RelType], func: &str) -> Result<std::sync::Arc<registry::ChannelQueue>, ExecResult> {
+    match args.first() {
+        Some(RelType::Handle(h)) => registry::registry_channel(h.0)
+            .ok_or_else(|| native_fault(func, format!("argument 1 'channel' expected Channel, found Handle<{}>", h.0))),
+        Some(other) => Err(native_fault(func, format!("argument 1 'channel' expected Channel, found {}", rel_type_name(other)))),
+        None => Err(native_fault(func, "missing argument 1 'channel'".into())),
+    }
+}
+
+impl ExecutionEngine {
+    /// Script tasks and channels:
+    /// - `Spawn(fn, args)` / `Spawn(fn, args, permissions)` runs `fn` in a child
+    ///   engine on its own thread and returns a task handle (also a promise).
+    ///   The child sees this engine's top-level functions and the built-in
+    ///   modules, but not host functions or globals. `permissions` may only
+    ///   switch flags off. The child shares this engine's interrupt flag.
+    /// - `Join(task)` waits for the task and returns its result or its Fault.
+    /// - `Channel()`, `ChannelSend(ch, v)`, `ChannelRecv(ch)` / `ChannelRecv(ch, ms)`
+    ///   (Void on timeout) and `ChannelTryRecv(ch)` (Void when empty).
+    ///
+    /// Values crossing to another task may not contain thread-bound handles
+    /// such as windows, files or GPU resources.
+    pub(crate) fn call_task_native(&mut self, name: &str, args: &[RelType]) -> Option<ExecResult> {
+        let res = match name {
+            "Spawn" => self.spawn_task(args),
+            "Join" => return Some(self.join_task(args)),
+            "Channel" => expect_args(args, &[], name).map(|_| RelType::Handle(NativeHandle(registry::registry_channel_new()))),
+            "ChannelSend" => (|| {
+                let queue = channel_arg(args, name)?;
+                let value = args.get(1).ok_or_else(|| native_fault(name, "missing argument 2 'value'".into()))?;
+                check_shareable(value).map_err(|e| native_fault(name, e))?;
+                queue.send(value.clone());
+                Ok(RelType::Void)
+            })(),
+            "ChannelRecv" => channel_arg(args, name).and_then(|queue| {
+                let timeout = match args.get(1) {
+                    Some(_) => Some(Duration::from_millis(arg::<u64>(args, 1, "ms", name)?)),
+                    None => None,
+                };
+                self.recv_interruptible(&queue, timeout)
+            }),
+            "ChannelTryRecv" => channel_arg(args, name).map(|queue| queue.try_recv().unwrap_or(RelType::Void)),
+            _ => return None,
+        };
+        Some(native_result(res))
+    }
+
+    /// Waits in short slices so an interrupt can end a blocked receive.
+    fn recv_interruptible(&self, queue: &registry::ChannelQueue, timeout: Option<Duration>) -> Result<RelType, ExecResult> {
+        let deadline = timeout.map(|t| std::time::Instant::now() + t);
+        loop {
+            self.check_interrupt()?;
+            let slice = match deadline {
+                Some(d) => d.saturating_duration_since(std::time::Instant::now()).min(RECV_SLICE),
+                None => RECV_SLICE,
+            };
+            if let Some(v) = queue.recv(Some(slice)) {
+                return Ok(v);
+            }
+            if deadline.is_some_and(|d| std::time::Instant::now() >= d) {
+                return Ok(RelType::Void);
+            }
+        }
+    }
+
+    fn spawn_task(&mut self, args: &[RelType]) -> Result<RelType, ExecResult> {
+        if !(2..=3).contains(&args.len()) {
+            return Err(native_fault("Spawn", format!("expected 2 or 3 arguments, got {}", args.len())));
+        }
+        let func = match &args[0] {
+            RelType::Str(name) => match self.memory.get(name) {
+                Some(f @ RelType::FnDef(..)) => f.clone(),
+                _ => return Err(native_fault("Spawn", format!("unknown function '{}'", name))),
+            },
+            f @ RelType::FnDef(..) => f.clone(),
+            other => return Err(native_fault("Spawn", format!("argument 1 'fn' expected Function, found {}", rel_type_name(other)))),
+        };
+        let call_args = arg::<Vec<RelType>>(args, 1, "args", "Spawn")?;
+        check_shareable(&args[1]).map_err(|e| native_fault("Spawn", e))?;
+        let permissions = narrow(&self.permissions, args.get(2))?;
+        let functions: Vec<(String, RelType)> =
+            self.memory.iter().filter(|(_, v)| matches!(v, RelType::FnDef(..))).map(|(k, v)| (k.clone(), v.clone())).collect();
+
+        let interrupt = self.interrupt.clone();
+        // The worker holds the settler, which keeps the promise alive even if
+        // the script discards the task handle, and rejects it if the worker dies.
+        let (promise, settler) = registry::PromiseSettler::new();
+        let id = promise.0;
+        let worker = std::thread::spawn(move || {
+            let mut child = ExecutionEngine::new();
+            child.permissions = permissions;
+            child.interrupt = interrupt;
+            child.memory.extend(functions);
+            // A thread-bound handle created by the child must not escape to the parent.
+            let res = match child.call_value(&func, call_args) {
+                ExecResult::Value(v) | ExecResult::ReturnBlockInfo(v) => match check_shareable(&v) {
+                    Ok(()) => ExecResult::Value(v),
+                    Err(e) => native_fault("Spawn", e),
+                },
+                fault => fault,
+            };
+            let settled = match &res {
+                ExecResult::Value(v) => Ok(v.clone()),
+                ExecResult::Fault { msg, .. } => Err(msg.clone()),
+                ExecResult::ReturnBlockInfo(_) => unreachable!(),
+            };
+            settler.settle(settled);
+            res
+        });
+        self.reap_tasks();
+        self.tasks.insert(id, worker);
+        Ok(RelType::Handle(promise))
+    }
+
+    /// Drops the join handles of finished tasks, so fire-and-forget spawns
+    /// don't pile up. Their results stay reachable through the task promise.
+    pub(crate) fn reap_tasks(&mut self) {
+        self.tasks.retain(|_, worker| !worker.is_finished());
+    }
+
+    fn join_task(&mut self, args: &[RelType]) -> ExecResult {
+        let task = match args {
+            [RelType::Handle(h)] if registry::registry_promise_state(h.0).is_some() => h.clone(),
+            [other] => return native_fault("Join", format!("argument 1 'task' expected Task, found {}", rel_type_name(other))),
+            _ => return native_fault("Join", format!("expected 1 argument, got {}", args.len())),
+        };
+        // Tasks spawned elsewhere (e.g. received over a channel) can only be awaited.
+        let Some(worker) = self.tasks.remove(&task.0) else {
+            return match self.await_value(RelType::Handle(task)) {
+                ExecResult::Fault { msg, .. } => ExecResult::Fault { msg, node: "Native::Join".into() },
+                res => res,
+            };
+        };
+        worker.join().unwrap_or_else(|_| native_fault("Join", "task panicked".into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(engine: &mut ExecutionEngine, src: &str) -> ExecResult {
+        engine.execute(&crate::parser::try_parse(src).unwrap())
+    }
+
+    #[test]
+    fn test_spawn_with_channels_and_join() {
+        let mut engine = ExecutionEngine::new();
+        let src = "fn square(x) { return x * x; }\n\
+            fn worker(jobs, results) {\n\
+              let job = ChannelRecv(jobs);\n\
+              while (job > 0) { ChannelSend(results, square(job)); job = ChannelRecv(jobs); }\n\
+              return \"done\";\n\
+            }\n\
+            let jobs = Channel();\n\
+            let results = Channel();\n\
+            let task = Spawn(worker, [jobs, results]);\n\
+            ChannelSend(jobs, 3); ChannelSend(jobs, 4); ChannelSend(jobs, 0);\n\
+            let status = Join(task);\n\
+            [ChannelRecv(results), ChannelRecv(results, 10), ChannelTryRecv(results), status]";
+        let expected = RelType::Array(vec![RelType::Int(9), RelType::Int(16), RelType::Void, RelType::Str("done".into())]);
+        assert!(matches!(run(&mut engine, src), ExecResult::Value(v) if v == expected));
+        assert!(matches!(run(&mut engine, "await Spawn(fn(a, b) { return a + b; }, [2, 3])"), ExecResult::Value(RelType::Int(5))));
+        assert!(engine.tasks.is_empty());
+    }
+
+    #[test]
+    fn test_finished_tasks_are_reaped() {
+        let mut engine = ExecutionEngine::new();
+        run(&mut engine, "fn one() { return 1; }\nlet t = Spawn(one, []);\nSpawn(one, []);");
+        while engine.tasks.values().any(|worker| !worker.is_finished()) {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert!(engine.service_events().is_ok());
+        assert!(engine.tasks.is_empty());
+        // A reaped task can still be joined through its promise.
+        assert!(matches!(run(&mut engine, "Join(t)"), ExecResult::Value(RelType::Int(1))));
+    }
+
+    #[test]
+    fn test_task_faults_and_permissions() {
+        let mut engine = ExecutionEngine::new();
+        engine.permissions.allow_fs_read = true;
+        let src = "let perms = MapCreate();\nMapSet(perms, \"allow_fs_read\", false);\n\
+            let t = Spawn(fn(p) { return FileRead(p); }, [\"Cargo.toml\"], perms);\nJoin(t)";
+        match run(&mut engine, src) {
+            ExecResult::Fault { msg, .. } => assert!(msg.contains("Permission Denied"), "{}", msg),
+            other => panic!("expected the child's fault, got {}", other),
+        }
+        match run(&mut engine, "let bad = MapCreate();\nMapSet(bad, \"allow_network\", 1);\nSpawn(fn(x) { return x; }, [1], bad)") {
+            ExecResult::Fault { msg, node } => {
+                assert_eq!(msg, "Spawn: permission 'allow_network' expected Bool, found Int");
+                assert_eq!(node, "Native::Spawn");
+            }
+            other => panic!("expected a permissions fault, got {}", other),
+        }
+        let path = "target/knoten_task_share.txt";
+        let file = RelType::Handle(NativeHandle(registry::registry_file_create(path.into())));
+        engine.memory.insert("f".into(), file);
+        match run(&mut engine, "ChannelSend(Channel(), [f])") {
+            ExecResult::Fault { msg, .. } => assert!(msg.contains("cannot be shared with another task"), "{}", msg),
+            other => panic!("expected a sharing fault, got {}", other),
+        }
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_discarded_task_handle_settles_without_deadlock() {
+        let (done_tx, done_rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let mut engine = ExecutionEngine::new();
+            let src = "fn worker(gate) { ChannelRecv(gate); return Channel(); }\nlet gate = Channel();\nSpawn(worker, [gate]);";
+            run(&mut engine, src);
+            // The task handle is gone before the task finishes.
+            run(&mut engine, "ChannelSend(gate, 1);");
+            for (_, worker) in engine.tasks.drain() {
+                let _ = worker.join();
+            }
+            let res = run(&mut engine, "Channel()");
+            let _ = done_tx.send(matches!(res, ExecResult::Value(RelType::Handle(_))));
+        });
+        let finished = done_rx.recv_timeout(Duration::from_secs(10));
+        assert_eq!(finished, Ok(true), "registry deadlocked after a discarded task settled");
+    }
+}
@@ -0,0 +1,312 @@
+use crate::executor::{ExecResult, ExecutionEngine, RelType};
+use crate::natives::convert::{arg, expect_args, native_result};
+use crate::natives::registry::PromiseSettler;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Time source for `Time()` and timers. `Virtual` only moves when advanced,
+/// either by the host or by `run_event_loop` jumping to the next due timer.
+#[derive(Clone, Default)]
+pub enum Clock {
+    #[default]
+    Real,
+    Virtual(VirtualClock),
+}
+
+/// Shared handle to a deterministic clock, in milliseconds.
+#[derive(Clone, Default)]
+pub struct VirtualClock(Arc<AtomicU64>);
+
+impl VirtualClock {
+    pub fn now_ms(&self) -> u64 {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    pub fn set_ms(&self, ms: u64) {
+        self.0.store(ms, Ordering::SeqCst);
+    }
+
+    pub fn advance_ms(&self, ms: u64) {
+        self.0.fetch_add(ms, Ordering::SeqCst);
+    }
+}
+
+enum TimerAction {
+    Call(RelType),
+    /// `Delay(ms)`: resolve this promise with Void.
+    Settle(PromiseSettler),
+}
+
+struct Timer {
+    id: i64,
+    due_ms: u64,
+    interval_ms: Option<u64>,
+    action: TimerAction,
+}
+
+/// Engine-owned timers. Timers due at the same time fire in creation order.
+#[derive(Default)]
+pub struct TimerQueue {
+    next_id: i64,
+    timers: Vec<Timer>,
+}
+
+impl TimerQueue {
+    fn schedule(&mut self, due_ms: u64, interval_ms: Option<u64>, action: TimerAction) -> i64 {
+        self.next_id += 1;
+        self.timers.push(Timer { id: self.next_id, due_ms, interval_ms, action });
+        self.next_id
+    }
+
+    pub fn clear(&mut self, id: i64) -> bool {
+        let before = self.timers.len();
+        self.timers.retain(|t| t.id != id);
+        self.timers.len() != before
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.timers.is_empty()
+    }
+
+    /// Functions that pending timers will call.
+    pub(crate) fn callbacks_mut(&mut self) -> impl Iterator<Item = &mut RelType> {
+        self.timers.iter_mut().filter_map(|t| match &mut t.action {
+            TimerAction::Call(callback) => Some(callback),
+            TimerAction::Settle(_) => None,
+        })
+    }
+
+    pub fn next_due_ms(&self) -> Option<u64> {
+        self.timers.iter().map(|t| t.due_ms).min()
+    }
+
+    /// When the next `Delay` promise is due.
+    fn next_settle_due_ms(&self) -> Option<u64> {
+        self.timers.iter().filter(|t| matches!(t.action, TimerAction::Settle(_))).map(|t| t.due_ms).min()
+    }
+
+    /// Removes the `Delay` timers due at `now_ms`, leaving callbacks queued.
+    fn take_due_settlers(&mut self, now_ms: u64) -> Vec<PromiseSettler> {
+        let (due, pending): (Vec<Timer>, Vec<Timer>) = std::mem::take(&mut self.timers)
+            .into_iter()
+            .partition(|t| t.due_ms <= now_ms && matches!(t.action, TimerAction::Settle(_)));
+        self.timers = pending;
+        due.into_iter()
+            .filter_map(|t| match t.action {
+                TimerAction::Settle(settler) => Some(settler),
+                TimerAction::Call(_) => None,
+            })
+            .collect()
+    }
+
+    /// Removes and returns the earliest timer due at `now_ms`, if any.
+    fn pop_due(&mut self, now_ms: u64) -> Option<Timer> {
+        let idx = self
+            .timers
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| t.due_ms <= now_ms)
+            .min_by_key(|(_, t)| (t.due_ms, t.id))
+            .map(|(i, _)| i)?;
+        Some(self.timers.remove(idx))
+    }
+}
+
+impl ExecutionEngine {
+    /// Time since the engine started, per its clock.
+    pub fn elapsed(&self) -> Duration {
+        match &self.clock {
+            Clock::Real => self.startup_time.elapsed(),
+            Clock::Virtual(v) => Duration::from_millis(v.now_ms()),
+        }
+    }
+
+    pub fn now_ms(&self) -> u64 {
+        self.elapsed().as_millis() as u64
+    }
+
+    /// Switches to a virtual clock starting at 0 ms and returns a handle to drive it.
+    pub fn use_virtual_clock(&mut self) -> VirtualClock {
+        let clock = VirtualClock::default();
+        self.clock = Clock::Virtual(clock.clone());
+        clock
+    }
+
+    /// `SetTimeout(fn, ms)`, `SetInterval(fn, ms)`, `ClearTimer(id)`, `Delay(ms)`
+    /// and `StopEventLoop()`. `fn` is a function value or a function name.
+    pub(crate) fn call_timer_native(&mut self, name: &str, args: &[RelType]) -> Option<ExecResult> {
+        let res = match name {
+            "SetTimeout" | "SetInterval" => (|| {
+                expect_args(args, &["callback", "ms"], name)?;
+                let ms = arg::<u64>(args, 1, "ms", name)?;
+                let callback = args[0].clone();
+                if !matches!(callback, RelType::FnDef(..) | RelType::Str(_)) {
+                    return Err(ExecResult::Fault {
+                        msg: format!("{}: argument 1 'callback' expected Function, found {}", name, crate::natives::convert::rel_type_name(&callback)),
+                        node: format!("Native::{}", name),
+                    });
+                }
+                // A zero interval would starve everything else in the loop.
+                let interval = (name == "SetInterval").then_some(ms.max(1));
+                let id = self.timers.schedule(self.now_ms() + ms, interval, TimerAction::Call(callback));
+                Ok(RelType::Int(id))
+            })(),
+            "ClearTimer" => expect_args(args, &["id"], name)
+                .and_then(|_| arg::<i64>(args, 0, "id", name))
+                .map(|id| RelType::Bool(self.timers.clear(id))),
+            "Delay" => expect_args(args, &["ms"], name).and_then(|_| arg::<u64>(args, 0, "ms", name)).map(|ms| {
+                let (promise, settler) = PromiseSettler::new();
+                self.timers.schedule(self.now_ms() + ms, None, TimerAction::Settle(settler));
+                RelType::Handle(promise)
+            }),
+            "StopEventLoop" => expect_args(args, &[], name).map(|_| {
+                self.event_loop_stopped = true;
+                RelType::Void
+            }),
+            _ => return None,
+        };
+        Some(native_result(res))
+    }
+
+    /// One pass over every event source: a pending hot reload, async bridge
+    /// deliveries, host actions and bus messages, then due timers. A fault raised
+    /// by a callback is returned.
+    pub fn service_events(&mut self) -> Result<(), ExecResult> {
+        self.apply_hot_reload();
+        self.poll_async_bridge();
+        self.reap_tasks();
+
+        self.pump_bus()?;
+
+        let now = self.now_ms();
+        while let Some(timer) = self.timers.pop_due(now) {
+            let action = match timer.action {
+                TimerAction::Settle(settler) => {
+                    settler.settle(Ok(RelType::Void));
+                    continue;
+                }
+                TimerAction::Call(callback) => callback,
+            };
+            if let Some(interval) = timer.interval_ms {
+                // Re-arm before running so the callback can clear its own interval.
+                self.timers.timers.push(Timer {
+                    id: timer.id,
+                    due_ms: timer.due_ms + interval,
+                    interval_ms: timer.interval_ms,
+                    action: TimerAction::Call(action.clone()),
+                });
+            }
+            if let fault @ ExecResult::Fault { .. } = self.call_value(&action, Vec::new()) {
+                return Err(fault);
+            }
+        }
+        Ok(())
+    }
+
+    /// Settles what `await` may be waiting on without running script code:
+    /// due `Delay` timers and finished `Fetch(method, url)` requests. Timer
+    /// callbacks, bus handlers, legacy fetch blocks and hot reloads stay queued
+    /// for the next `service_events`.
+    pub(crate) fn settle_ready_promises(&mut self) {
+        let now = self.now_ms();
+        for settler in self.timers.take_due_settlers(now) {
+            settler.settle(Ok(RelType::Void));
+        }
+        self.settle_fetch_promises();
+    }
+
+    /// Blocks until an awaited promise might have settled. Unlike
+    /// `wait_for_events`, a virtual clock only jumps to the next `Delay`,
+    /// since no timer callback runs while awaiting.
+    pub(crate) fn wait_for_promises(&mut self) {
+        self.sleep_until(self.timers.next_settle_due_ms());
+    }
+
+    /// Blocks until the next event might be ready. A virtual clock jumps straight
+    /// to the next due timer unless network requests are still in flight.
+    /// Returns immediately while bus messages or fetch blocks are still queued.
+    pub fn wait_for_events(&mut self) {
+        if self.bus.pending() > 0 || !self.deferred_fetches.is_empty() {
+            return;
+        }
+        self.sleep_until(self.timers.next_due_ms());
+    }
+
+    fn sleep_until(&self, next_due: Option<u64>) {
+        let in_flight = self.async_bridge.as_ref().is_some_and(|b| b.in_flight() > 0);
+        match &self.clock {
+            Clock::Virtual(v) => match next_due {
+                Some(due) if !in_flight => v.set_ms(due.max(v.now_ms())),
+                _ => std::thread::sleep(Duration::from_millis(1)),
+            },
+            Clock::Real => {
+                let wait = next_due.map_or(5, |due| due.saturating_sub(self.now_ms()));
+                let wait = if in_flight { wait.min(5) } else { wait };
+                std::thread::sleep(Duration::from_millis(wait.min(50)));
+            }
+        }
+    }
+
+    /// Drives timers, fetch callbacks, host actions and bus messages until nothing is left to
+    /// wait for or the script calls `StopEventLoop()`.
+    pub fn run_event_loop(&mut self) -> ExecResult {
+        self.event_loop_stopped = false;
+        loop {
+            if let Err(cancelled) = self.check_interrupt() {
+                return cancelled;
+            }
+            if let Err(fault) = self.service_events() {
+                return fault;
+            }
+            let in_flight = self.async_bridge.as_ref().is_some_and(|b| b.in_flight() > 0);
+            // Bus subscriptions alone don't keep the loop alive; queued messages do.
+            let idle = self.timers.is_empty() && !in_flight && self.bus.pending() == 0 && self.deferred_fetches.is_empty();
+            if self.event_loop_stopped || idle {
+                return ExecResult::Value(RelType::Void);
+            }
+            self.wait_for_events();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(engine: &mut ExecutionEngine, src: &str) -> ExecResult {
+        engine.execute(&crate::parser::try_parse(src).unwrap())
+    }
+
+    #[test]
+    fn test_timers_on_virtual_clock() {
+        let mut engine = ExecutionEngine::new();
+        let clock = engine.use_virtual_clock();
+        let src = "let log = [];\n\
+            fn tick() { ArrayPush(log, Time()); if (ArrayLen(log) == 4) { ClearTimer(ticker); } }\n\
+            fn late() { ArrayPush(log, 1000); }\n\
+            let ticker = SetInterval(tick, 500);\n\
+            SetTimeout(late, 1200);\n\
+            let gone = SetTimeout(late, 100);\n\
+            ClearTimer(gone);";
+        assert!(!matches!(run(&mut engine, src), ExecResult::Fault { .. }));
+        assert!(matches!(engine.run_event_loop(), ExecResult::Value(RelType::Void)));
+        let expected: Vec<RelType> = vec![RelType::Float(0.5), RelType::Float(1.0), RelType::Int(1000), RelType::Float(1.5)];
+        assert_eq!(engine.memory["log"], RelType::Array(expected));
+        assert_eq!(clock.now_ms(), 1500);
+    }
+
+    #[test]
+    fn test_await_delay_and_stop() {
+        let mut engine = ExecutionEngine::new();
+        let clock = engine.use_virtual_clock();
+        assert!(!matches!(run(&mut engine, "await Delay(250);"), ExecResult::Fault { .. }));
+        assert_eq!(clock.now_ms(), 250);
+
+        let src = "fn stop() { StopEventLoop(); }\nfn never() { Print(\"unreachable\"); }\nSetTimeout(stop, 10);\nSetTimeout(never, 5000);";
+        run(&mut engine, src);
+        engine.run_event_loop();
+        assert_eq!(clock.now_ms(), 260);
+        assert!(!engine.timers.is_empty());
+    }
+}
@@ -6,6 +6,9 @@ use std::path::Path;
 pub struct Validator {
     pub errors: Vec<String>,
     import_stack: HashSet<String>,
+    /// Whether the node being checked is a statement, or the value a statement
+    /// assigns or returns: the only places `Await` may appear.
+    at_statement: bool,
 }
 
 impl Default for Validator {
@@ -19,12 +22,14 @@ impl Validator {
         Self {
             errors: Vec::new(),
             import_stack: HashSet::new(),
+            at_statement: false,
         }
     }
 
     pub fn validate(&mut self, node: &Node) -> Result<(), Vec<String>> {
         self.errors.clear();
         self.import_stack.clear();
+        self.at_statement = true;
         self.check_node(node);
         if self.errors.is_empty() {
             Ok(())
@@ -34,23 +39,41 @@ impl Validator {
     }
 
     fn check_node(&mut self, node: &Node) {
+        let at_statement = std::mem::take(&mut self.at_statement);
         match node {
             Node::Assign(name, val) => {
                 if name.is_empty() {
                     self.errors
                         .push("Assign: Identifier name cannot be empty".to_string());
                 }
+                self.at_statement = at_statement;
+                self.check_node(val);
+            }
+            Node::Return(val) => {
+                self.at_statement = at_statement;
+                self.check_node(val);
+            }
+            Node::Await(val) => {
+                if !at_statement {
+                    self.errors.push(
+                        "Await: only allowed as a statement, or as the value a statement assigns or returns".to_string(),
+                    );
+                }
                 self.check_node(val);
             }
             Node::Store { key, value } => {
                 if key.is_empty() {
                     self.errors.push("Store: Key cannot be empty".to_string());
+                } else if let Err(e) = crate::vm::storage::check_name("key", key) {
+                    self.errors.push(format!("Store: {}", e));
                 }
                 self.check_node(value);
             }
             Node::Load { key } => {
                 if key.is_empty() {
                     self.errors.push("Load: Key cannot be empty".to_string());
+                } else if let Err(e) = crate::vm::storage::check_name("key", key) {
+                    self.errors.push(format!("Load: {}", e));
                 }
             }
             Node::Add(l, r)
@@ -121,6 +144,7 @@ impl Validator {
             Node::Sin(n)
             | Node::Cos(n)
             | Node::FileRead(n)
+            | Node::FileReadBytes(n)
             | Node::FSRead(n)
             | Node::Print(n)
             | Node::EvalJSONNative(n)
@@ -138,11 +162,10 @@ impl Validator {
             | Node::DrawVoxelGrid(n)
             | Node::EnableInteraction(n)
             | Node::EnablePhysics(n)
-            | Node::Return(n)
             | Node::Abs(n) => {
                 self.check_node(n);
             }
-            Node::FileWrite(f, d) | Node::FSWrite(f, d) => {
+            Node::FileWrite(f, d) | Node::FileWriteBytes(f, d) | Node::FSWrite(f, d) => {
                 self.check_node(f);
                 self.check_node(d);
             }
@@ -159,6 +182,14 @@ impl Validator {
                 }
                 self.check_node(body);
             }
+            Node::Lambda(params, body) => {
+                for param in params {
+                    if param.is_empty() {
+                        self.errors.push("Lambda: Parameter name cannot be empty".to_string());
+                    }
+                }
+                self.check_node(body);
+            }
             Node::Call(name, args) | Node::NativeCall(name, args) => {
                 if name.is_empty() {
                     self.errors
@@ -181,7 +212,13 @@ impl Validator {
                     self.check_node(arg);
                 }
             }
-            Node::Block(nodes) | Node::ArrayCreate(nodes) => {
+            Node::Block(nodes) => {
+                for n in nodes {
+                    self.at_statement = true;
+                    self.check_node(n);
+                }
+            }
+            Node::ArrayCreate(nodes) => {
                 for n in nodes {
                     self.check_node(n);
                 }
@@ -17,6 +17,8 @@ impl Compiler {
     }
 
     /// Recursively flattens an AST math/logic tree into linear opcodes.
+    /// Every compiled node leaves exactly one value on the stack, so a block yields
+    /// its last statement's value like the evaluator does.
     /// Returns false if the node cannot be compiled (e.g. it contains side-effects or variables).
     pub fn compile_node(&mut self, node: &Node) -> bool {
         match node {
@@ -76,8 +78,14 @@ impl Compiler {
                 true
             }
             Node::Block(stmts) => {
-                for stmt in stmts {
+                if stmts.is_empty() {
+                    self.push_void();
+                }
+                for (i, stmt) in stmts.iter().enumerate() {
                     if !self.compile_node(stmt) { return false; }
+                    if i + 1 < stmts.len() {
+                        self.instructions.push(OpCode::Pop);
+                    }
                 }
                 true
             }
@@ -88,21 +96,22 @@ impl Compiler {
 
                 if !self.compile_node(then_block) { return false; }
 
-                if let Some(else_branch) = else_block {
-                    let jump_idx = self.instructions.len();
-                    self.instructions.push(OpCode::Jump(0)); // Placeholder
-
-                    // Backpatch JumpIfFalse to jump here (start of else block)
-                    self.instructions[jump_if_false_idx] = OpCode::JumpIfFalse(self.instructions.len());
+                let jump_idx = self.instructions.len();
+                self.instructions.push(OpCode::Jump(0)); // Placeholder
 
-                    if !self.compile_node(else_branch) { return false; }
+                // Backpatch JumpIfFalse to jump here (start of else block)
+                self.instructions[jump_if_false_idx] = OpCode::JumpIfFalse(self.instructions.len());
 
-                    // Backpatch unconditional Jump to jump past the else block
-                    self.instructions[jump_idx] = OpCode::Jump(self.instructions.len());
-                } else {
-                    // Backpatch JumpIfFalse to jump past the then block
-                    self.instructions[jump_if_false_idx] = OpCode::JumpIfFalse(self.instructions.len());
+                // A missing else branch still yields Void, as in the evaluator
+                match else_block {
+                    Some(else_branch) => {
+                        if !self.compile_node(else_branch) { return false; }
+                    }
+                    None => self.push_void(),
                 }
+
+                // Backpatch unconditional Jump to jump past the else block
+                self.instructions[jump_idx] = OpCode::Jump(self.instructions.len());
                 true
             }
             Node::Print(expr) => {
@@ -119,6 +128,11 @@ impl Compiler {
         }
     }
 
+    fn push_void(&mut self) {
+        let idx = self.add_constant(RelType::Void);
+        self.instructions.push(OpCode::Constant(idx));
+    }
+
     fn add_constant(&mut self, val: RelType) -> usize {
         if let Some(idx) = self.constants.iter().position(|c| c == &val) {
             return idx;
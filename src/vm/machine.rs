@@ -1,10 +1,16 @@
 use crate::executor::RelType;
+use crate::interrupt::InterruptHandle;
+use crate::numeric::{self, BinOp, CmpOp};
 use crate::vm::opcode::OpCode;
 
 #[derive(Default)]
 pub struct VM {
     stack: Vec<RelType>,
     pub ip: usize,
+    /// Checked on backward jumps; when set, `run` fails with the cancellation message.
+    pub interrupt: InterruptHandle,
+    /// When set, `Print` appends here instead of writing to stdout.
+    pub output: Option<Vec<String>>,
 }
 
 impl VM {
@@ -12,9 +18,39 @@ impl VM {
         Self {
             stack: Vec::with_capacity(256),
             ip: 0,
+            interrupt: InterruptHandle::default(),
+            output: None,
         }
     }
 
+    fn jump(&mut self, target_ip: usize) -> Result<(), String> {
+        if target_ip < self.ip && self.interrupt.is_interrupted() {
+            return Err(crate::interrupt::CANCELLED_MSG.into());
+        }
+        self.ip = target_ip;
+        Ok(())
+    }
+
+    fn pop_pair(&mut self) -> (RelType, RelType) {
+        let r = self.stack.pop().unwrap_or(RelType::Void);
+        let l = self.stack.pop().unwrap_or(RelType::Void);
+        (l, r)
+    }
+
+    fn binary(&mut self, op: BinOp) -> Result<(), String> {
+        let (l, r) = self.pop_pair();
+        let v = numeric::arith(op, &l, &r).map_err(|e| e.to_string())?;
+        self.stack.push(v);
+        Ok(())
+    }
+
+    fn comparison(&mut self, op: CmpOp) -> Result<(), String> {
+        let (l, r) = self.pop_pair();
+        let b = numeric::compare(op, &l, &r).map_err(|e| e.to_string())?;
+        self.stack.push(RelType::Bool(b));
+        Ok(())
+    }
+
     #[inline(always)]
     pub fn run(&mut self, instructions: &[OpCode], constants: &[RelType]) -> Result<RelType, String> {
         self.stack.clear();
@@ -32,78 +68,13 @@ impl VM {
                         return Err("Constant index out of bounds".into());
                     }
                 }
-                OpCode::Add => {
-                    let r = self.stack.pop().unwrap_or(RelType::Void);
-                    let l = self.stack.pop().unwrap_or(RelType::Void);
-                    match (l, r) {
-                        (RelType::Int(a), RelType::Int(b)) => self.stack.push(RelType::Int(a + b)),
-                        (RelType::Float(a), RelType::Float(b)) => self.stack.push(RelType::Float(a + b)),
-                        (RelType::Int(a), RelType::Float(b)) => self.stack.push(RelType::Float(a as f64 + b)),
-                        (RelType::Float(a), RelType::Int(b)) => self.stack.push(RelType::Float(a + b as f64)),
-                        (RelType::Str(a), RelType::Str(b)) => self.stack.push(RelType::Str(a + &b)),
-                        _ => return Err("Invalid types for Add".into()),
-                    }
-                }
-                OpCode::Subtract => {
-                    let r = self.stack.pop().unwrap_or(RelType::Void);
-                    let l = self.stack.pop().unwrap_or(RelType::Void);
-                    match (l, r) {
-                        (RelType::Int(a), RelType::Int(b)) => self.stack.push(RelType::Int(a - b)),
-                        (RelType::Float(a), RelType::Float(b)) => self.stack.push(RelType::Float(a - b)),
-                        (RelType::Int(a), RelType::Float(b)) => self.stack.push(RelType::Float(a as f64 - b)),
-                        (RelType::Float(a), RelType::Int(b)) => self.stack.push(RelType::Float(a - b as f64)),
-                        _ => return Err("Invalid types for Subtract".into()),
-                    }
-                }
-                OpCode::Multiply => {
-                    let r = self.stack.pop().unwrap_or(RelType::Void);
-                    let l = self.stack.pop().unwrap_or(RelType::Void);
-                    match (l, r) {
-                        (RelType::Int(a), RelType::Int(b)) => self.stack.push(RelType::Int(a * b)),
-                        (RelType::Float(a), RelType::Float(b)) => self.stack.push(RelType::Float(a * b)),
-                        (RelType::Int(a), RelType::Float(b)) => self.stack.push(RelType::Float(a as f64 * b)),
-                        (RelType::Float(a), RelType::Int(b)) => self.stack.push(RelType::Float(a * b as f64)),
-                        _ => return Err("Invalid types for Multiply".into()),
-                    }
-                }
-                OpCode::Divide => {
-                    let r = self.stack.pop().unwrap_or(RelType::Void);
-                    let l = self.stack.pop().unwrap_or(RelType::Void);
-                    match (l, r) {
-                        (RelType::Int(a), RelType::Int(b)) => {
-                            if b == 0 { return Err("Div by zero".into()); }
-                            self.stack.push(RelType::Int(a / b))
-                        },
-                        (RelType::Float(a), RelType::Float(b)) => {
-                            if b == 0.0 { return Err("Div by zero".into()); }
-                            self.stack.push(RelType::Float(a / b))
-                        },
-                        _ => return Err("Invalid types for Divide".into()),
-                    }
-                }
-                OpCode::Equal => {
-                    let r = self.stack.pop().unwrap_or(RelType::Void);
-                    let l = self.stack.pop().unwrap_or(RelType::Void);
-                    self.stack.push(RelType::Bool(l == r));
-                }
-                OpCode::Less => {
-                    let r = self.stack.pop().unwrap_or(RelType::Void);
-                    let l = self.stack.pop().unwrap_or(RelType::Void);
-                    match (l, r) {
-                        (RelType::Int(a), RelType::Int(b)) => self.stack.push(RelType::Bool(a < b)),
-                        (RelType::Float(a), RelType::Float(b)) => self.stack.push(RelType::Bool(a < b)),
-                        _ => self.stack.push(RelType::Bool(false)),
-                    }
-                }
-                OpCode::Greater => {
-                    let r = self.stack.pop().unwrap_or(RelType::Void);
-                    let l = self.stack.pop().unwrap_or(RelType::Void);
-                    match (l, r) {
-                        (RelType::Int(a), RelType::Int(b)) => self.stack.push(RelType::Bool(a > b)),
-                        (RelType::Float(a), RelType::Float(b)) => self.stack.push(RelType::Bool(a > b)),
-                        _ => self.stack.push(RelType::Bool(false)),
-                    }
-                }
+                OpCode::Add => self.binary(BinOp::Add)?,
+                OpCode::Subtract => self.binary(BinOp::Sub)?,
+                OpCode::Multiply => self.binary(BinOp::Mul)?,
+                OpCode::Divide => self.binary(BinOp::Div)?,
+                OpCode::Equal => self.comparison(CmpOp::Eq)?,
+                OpCode::Less => self.comparison(CmpOp::Lt)?,
+                OpCode::Greater => self.comparison(CmpOp::Gt)?,
                 OpCode::JumpIfFalse(target_ip) => {
                     let cond = self.stack.pop().unwrap_or(RelType::Void);
                     let is_true = match cond {
@@ -112,15 +83,22 @@ impl VM {
                         _ => false,
                     };
                     if !is_true {
-                        self.ip = *target_ip;
+                        self.jump(*target_ip)?;
                     }
                 }
                 OpCode::Jump(target_ip) => {
-                    self.ip = *target_ip;
+                    self.jump(*target_ip)?;
                 }
                 OpCode::Print => {
                     let val = self.stack.pop().unwrap_or(RelType::Void);
-                    println!("{}", val);
+                    match &mut self.output {
+                        Some(out) => out.push(val.to_string()),
+                        None => println!("{}", val),
+                    }
+                    self.stack.push(RelType::Void);
+                }
+                OpCode::Pop => {
+                    self.stack.pop();
                 }
                 OpCode::Return => {
                     return Ok(self.stack.pop().unwrap_or(RelType::Void));
@@ -189,4 +167,15 @@ mod tests {
         let result = vm.run(&instructions, &constants).unwrap();
         assert_eq!(result, RelType::Int(20));
     }
+
+    #[test]
+    fn test_vm_interrupt_on_back_edge() {
+        let mut vm = VM::new();
+        vm.interrupt.interrupt();
+        // Represents: loop { 1 }
+        let instructions = vec![OpCode::Constant(0), OpCode::Jump(0)];
+        let constants = vec![RelType::Int(1)];
+
+        assert_eq!(vm.run(&instructions, &constants), Err(crate::interrupt::CANCELLED_MSG.to_string()));
+    }
 }
@@ -11,5 +11,6 @@ pub enum OpCode {
     Jump(usize),
     JumpIfFalse(usize),
     Print,
+    Pop,
     Return,
 }
@@ -1,24 +1,225 @@
+//! Persistent key-value store behind `Node::Store`/`Node::Load`, shared by the
+//! interpreter and transpiled programs so both read the same data.
+//!
+//! Each script gets a namespace (a directory under [`STORAGE_DIR`]) and each
+//! key one JSON file holding the externally tagged `RelType`, so an Int stays
+//! an Int and a `Vec3` stays a `Vec3` across runs. Writes go to a temporary
+//! file that is renamed over the old one, so a crash never leaves half a
+//! value behind.
+
+use crate::executor::RelType;
 use std::fs;
-use std::path::Path;
-use serde_json::Value;
-
-const STORAGE_DIR: &str = ".knoten_data/storage";
-
-pub fn store_value(key: &str, value: &Value) -> Result<(), String> {
-    // Stellt sicher, dass das Verzeichnis existiert
-    fs::create_dir_all(STORAGE_DIR).map_err(|e| e.to_string())?;
-    
-    let path = format!("{}/{}.json", STORAGE_DIR, key);
-    let data = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
-    fs::write(path, data).map_err(|e| e.to_string())?;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+pub const STORAGE_DIR: &str = ".knoten_data/storage";
+
+const MAX_NAME_LEN: usize = 128;
+
+/// Checks a key or namespace before it becomes part of a file name: ASCII
+/// letters, digits, `_`, `-` and `.`, not starting with `.`.
+pub fn check_name(kind: &str, name: &str) -> Result<(), String> {
+    let valid_chars = name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+    if name.is_empty() || name.len() > MAX_NAME_LEN || !valid_chars || name.starts_with('.') {
+        return Err(format!(
+            "invalid {} '{}': use 1-{} letters, digits, '_', '-' or '.', not starting with '.'",
+            kind, name, MAX_NAME_LEN
+        ));
+    }
     Ok(())
 }
 
-pub fn load_value(key: &str) -> Result<Value, String> {
-    let path = format!("{}/{}.json", STORAGE_DIR, key);
-    if !Path::new(&path).exists() {
-        return Ok(Value::Null); // Fallback, falls noch nichts gespeichert wurde
+/// Values that can't survive a round trip: handles are process-local and
+/// JSON has no NaN or infinity.
+fn check_storable(value: &RelType) -> Result<(), String> {
+    match value {
+        RelType::Handle(_) => Err("native handles cannot be stored".into()),
+        RelType::Float(f) if !f.is_finite() => Err(format!("cannot store non-finite Float {}", f)),
+        RelType::Array(items) => items.iter().try_for_each(check_storable),
+        RelType::Object(map) => map.values().try_for_each(check_storable),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct KvStore {
+    dir: PathBuf,
+    namespace: String,
+}
+
+impl Default for KvStore {
+    fn default() -> Self {
+        Self { dir: Path::new(STORAGE_DIR).join("default"), namespace: "default".into() }
+    }
+}
+
+impl KvStore {
+    pub fn new(root: impl AsRef<Path>, namespace: &str) -> Result<Self, String> {
+        check_name("namespace", namespace)?;
+        Ok(Self { dir: root.as_ref().join(namespace), namespace: namespace.to_string() })
+    }
+
+    /// The store for a script file, namespaced by its file stem with any
+    /// character not allowed in a name replaced by `_`.
+    pub fn for_script(script_path: &str) -> Self {
+        let stem = Path::new(script_path).file_stem().map(|s| s.to_string_lossy()).unwrap_or_default();
+        let mut namespace: String = stem
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.') { c } else { '_' })
+            .skip_while(|c| *c == '.')
+            .take(MAX_NAME_LEN)
+            .collect();
+        if namespace.is_empty() {
+            namespace = "default".into();
+        }
+        Self { dir: Path::new(STORAGE_DIR).join(&namespace), namespace }
+    }
+
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, String> {
+        check_name("key", key)?;
+        Ok(self.dir.join(format!("{}.json", key)))
+    }
+
+    pub fn put(&self, key: &str, value: &RelType) -> Result<(), String> {
+        static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+        let path = self.path(key)?;
+        check_storable(value).map_err(|e| format!("'{}': {}", key, e))?;
+        let data = serde_json::to_string(value).map_err(|e| format!("'{}': {}", key, e))?;
+        fs::create_dir_all(&self.dir).map_err(|e| format!("cannot create {}: {}", self.dir.display(), e))?;
+
+        let tmp = self.dir.join(format!(
+            ".{}.{}.{}.tmp",
+            key,
+            std::process::id(),
+            TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let written = fs::File::create(&tmp).and_then(|mut file| {
+            use std::io::Write;
+            file.write_all(data.as_bytes())?;
+            file.sync_all()
+        });
+        if let Err(e) = written.and_then(|_| fs::rename(&tmp, &path)) {
+            let _ = fs::remove_file(&tmp);
+            return Err(format!("cannot write '{}': {}", key, e));
+        }
+        Ok(())
+    }
+
+    /// `None` if the key was never stored.
+    pub fn get(&self, key: &str) -> Result<Option<RelType>, String> {
+        let path = self.path(key)?;
+        let data = match fs::read_to_string(&path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("cannot read '{}': {}", key, e)),
+        };
+        serde_json::from_str(&data).map(Some).map_err(|e| format!("'{}' holds no stored value: {}", key, e))
+    }
+
+    /// Whether the key existed.
+    pub fn delete(&self, key: &str) -> Result<bool, String> {
+        match fs::remove_file(self.path(key)?) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(format!("cannot delete '{}': {}", key, e)),
+        }
+    }
+
+    /// Stored keys in sorted order.
+    pub fn keys(&self) -> Result<Vec<String>, String> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(format!("cannot list {}: {}", self.dir.display(), e)),
+        };
+        let mut keys: Vec<String> = entries
+            .filter_map(|entry| entry.ok()?.file_name().to_str()?.strip_suffix(".json").map(str::to_string))
+            .filter(|key| check_name("key", key).is_ok())
+            .collect();
+        keys.sort();
+        Ok(keys)
+    }
+}
+
+/// Entry points for transpiled programs. They have no fault channel, so
+/// errors panic with the message the interpreter would fault with.
+pub mod rt {
+    use super::KvStore;
+    use crate::executor::RelType;
+    use crate::natives::convert::{FromRel, IntoRel};
+
+    fn open(namespace: &str) -> KvStore {
+        KvStore::new(super::STORAGE_DIR, namespace).unwrap_or_else(|e| panic!("Store: {}", e))
+    }
+
+    pub fn store<T: IntoRel + Clone>(namespace: &str, key: &str, value: &T) {
+        open(namespace).put(key, &value.clone().into_rel()).unwrap_or_else(|e| panic!("Store: {}", e))
+    }
+
+    /// A key that was never stored loads as `T::default()`.
+    pub fn load<T: FromRel + Default>(namespace: &str, key: &str) -> T {
+        match load_value(namespace, key) {
+            RelType::Void => T::default(),
+            value => T::from_rel(&value).unwrap_or_else(|e| panic!("Load: '{}': {}", key, e)),
+        }
+    }
+
+    /// Untyped load, for keys codegen has no type for; Void if never stored.
+    pub fn load_value(namespace: &str, key: &str) -> RelType {
+        match open(namespace).get(key) {
+            Ok(value) => value.unwrap_or(RelType::Void),
+            Err(e) => panic!("Load: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store(name: &str) -> KvStore {
+        let root = Path::new("target/kv_test").join(name);
+        let _ = fs::remove_dir_all(&root);
+        KvStore::new(root, "script").unwrap()
+    }
+
+    #[test]
+    fn test_round_trips_types_and_lists_keys() {
+        let kv = store("round_trip");
+        let value = RelType::Array(vec![
+            RelType::Float(2.0),
+            RelType::Int(2),
+            RelType::Bytes(vec![0, 255]),
+            RelType::Vec3(glam::Vec3::new(1.0, 2.0, 3.0)),
+        ]);
+        assert_eq!(kv.get("board").unwrap(), None);
+        kv.put("board", &value).unwrap();
+        kv.put("turn", &RelType::Int(3)).unwrap();
+        kv.put("turn", &RelType::Int(4)).unwrap();
+        assert_eq!(kv.get("board").unwrap(), Some(value));
+        assert_eq!(kv.get("turn").unwrap(), Some(RelType::Int(4)));
+        assert_eq!(kv.keys().unwrap(), vec!["board".to_string(), "turn".to_string()]);
+        assert!(kv.delete("board").unwrap());
+        assert!(!kv.delete("board").unwrap());
+        assert_eq!(kv.keys().unwrap(), vec!["turn".to_string()]);
+
+        assert_eq!(KvStore::for_script("games/My Chess!.knoten").namespace(), "My_Chess_");
+        assert_eq!(KvStore::for_script("../.hidden.nod").namespace(), "hidden");
+    }
+
+    #[test]
+    fn test_rejects_unsafe_keys_and_values() {
+        let kv = store("unsafe");
+        for key in ["../escape", "a/b", "", ".tmp", "a\\b"] {
+            assert!(kv.put(key, &RelType::Int(1)).unwrap_err().starts_with("invalid key"), "{:?}", key);
+            assert!(kv.get(key).is_err());
+        }
+        assert!(KvStore::new("target", "..").is_err());
+        assert_eq!(kv.put("x", &RelType::Float(f64::NAN)).unwrap_err(), "'x': cannot store non-finite Float NaN");
+        assert!(kv.keys().unwrap().is_empty());
     }
-    let data = fs::read_to_string(path).map_err(|e| e.to_string())?;
-    serde_json::from_str(&data).map_err(|e| e.to_string())
 }
@@ -10,6 +10,8 @@ use wgpu::util::DeviceExt;
 pub struct KnotenApp {
     pub windows: HashMap<usize, RegistryWindowState>,
     pub window_id_map: HashMap<WindowId, usize>,
+    /// Triggered when the last window is closed, so the script stops cleanly.
+    pub on_close: Option<crate::interrupt::InterruptHandle>,
 }
 
 impl Default for KnotenApp {
@@ -23,9 +25,15 @@ impl KnotenApp {
         Self {
             windows: HashMap::new(),
             window_id_map: HashMap::new(),
+            on_close: None,
         }
     }
 
+    pub fn with_interrupt(mut self, interrupt: crate::interrupt::InterruptHandle) -> Self {
+        self.on_close = Some(interrupt);
+        self
+    }
+
     fn handle_command(&mut self, event_loop: &ActiveEventLoop, cmd: RenderCommand) {
         match cmd {
             RenderCommand::CreateWindow { id, title, width, height } => {
@@ -390,6 +398,9 @@ impl ApplicationHandler<RenderCommand> for KnotenApp {
             WindowEvent::CloseRequested => {
                 self.windows.remove(&registry_id);
                 if self.windows.is_empty() {
+                    if let Some(interrupt) = &self.on_close {
+                        interrupt.interrupt();
+                    }
                     event_loop.exit();
                 }
             }
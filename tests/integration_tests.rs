@@ -54,6 +54,12 @@ macro_rules! knoten_test {
                         knoten_core::executor::RelType::Call(_, _) => "call",
                         knoten_core::executor::RelType::Handle(_) => "handle",
                         knoten_core::executor::RelType::Void => "void",
+                        knoten_core::executor::RelType::Vec2(_) => "Vec2",
+                        knoten_core::executor::RelType::Vec3(_) => "Vec3",
+                        knoten_core::executor::RelType::Vec4(_) => "Vec4",
+                        knoten_core::executor::RelType::Quat(_) => "Quat",
+                        knoten_core::executor::RelType::Mat4(_) => "Mat4",
+                        knoten_core::executor::RelType::Bytes(_) => "Bytes",
                     };
                     if let knoten_core::executor::RelType::Str(s) = val {
                         out.push_str(&format!("Return: \"{}\" ({})", s, typ_name));
@@ -629,3 +635,165 @@ knoten_test!(
     ),
     "Return: \"hello world\" (String)"
 );
+
+// ------------------------------------------------------------------
+// Test 55: stdlib string helpers on the String native module
+// ------------------------------------------------------------------
+knoten_test!(
+    test_55_stdlib_string_is_not_empty,
+    Node::Block(vec![
+        serde_json::from_str(&fs::read_to_string("stdlib/string_utils.nod").unwrap()).unwrap(),
+        Node::Call("String.IsNotEmpty".to_string(), vec![Node::StringLiteral("hi".to_string())])
+    ]),
+    "Return: true (bool)"
+);
+
+// ------------------------------------------------------------------
+// Test 56: CheckCollision accepts Vec3 values alongside arrays
+// ------------------------------------------------------------------
+knoten_test!(
+    test_56_check_collision_vec3,
+    Node::CheckCollision {
+        a_min: Box::new(Node::Call("Vec3".to_string(), vec![Node::IntLiteral(0), Node::IntLiteral(0), Node::IntLiteral(0)])),
+        a_max: Box::new(Node::Call("Vec3".to_string(), vec![Node::IntLiteral(1), Node::IntLiteral(1), Node::IntLiteral(1)])),
+        b_min: Box::new(Node::ArrayCreate(vec![Node::FloatLiteral(0.5), Node::FloatLiteral(0.5), Node::FloatLiteral(0.5)])),
+        b_max: Box::new(Node::Call("Vec3".to_string(), vec![Node::IntLiteral(2), Node::IntLiteral(2), Node::IntLiteral(2)])),
+    },
+    "Return: 1 (i64)"
+);
+
+// ------------------------------------------------------------------
+// TEST 57: Extract queries a parsed JSON body with a filter path
+// ------------------------------------------------------------------
+knoten_test!(
+    test_57_extract_json_path,
+    Node::Extract {
+        source: Box::new(Node::Call(
+            "JSON.Parse".to_string(),
+            vec![Node::StringLiteral(r#"{"users": [{"name": "ada", "age": 36}, {"name": "bob", "age": 17}]}"#.to_string())],
+        )),
+        path: Box::new(Node::StringLiteral("$.users[?(@.age >= 18)].name".to_string())),
+    },
+    "Return: [ada] (Array)"
+);
+
+// ------------------------------------------------------------------
+// TEST 58: JSON.Stringify emits valid JSON with sorted keys
+// ------------------------------------------------------------------
+knoten_test!(
+    test_58_json_stringify,
+    Node::Call(
+        "JSON.Stringify".to_string(),
+        vec![
+            Node::EvalJSONNative(Box::new(Node::StringLiteral(r#"{"name": "a \"b\"", "id": 7, "tags": []}"#.to_string()))),
+            Node::BoolLiteral(false),
+        ]
+    ),
+    r#"Return: "{"id":7,"name":"a \"b\"","tags":[]}" (String)"#
+);
+
+// ------------------------------------------------------------------
+// TEST 59: Bytes concatenate, index and unpack big-endian integers
+// ------------------------------------------------------------------
+knoten_test!(
+    test_59_bytes_pack_unpack,
+    Node::Call(
+        "Bytes.UnpackInt".to_string(),
+        vec![
+            Node::Concat(
+                Box::new(Node::Call("Bytes.FromHex".to_string(), vec![Node::StringLiteral("ff".to_string())])),
+                Box::new(Node::Call(
+                    "Bytes.PackInt".to_string(),
+                    vec![Node::IntLiteral(258), Node::IntLiteral(2), Node::StringLiteral("be".to_string())]
+                )),
+            ),
+            Node::Index(
+                Box::new(Node::Call("Bytes.FromArray".to_string(), vec![Node::ArrayCreate(vec![Node::IntLiteral(1)])])),
+                Box::new(Node::IntLiteral(0)),
+            ),
+            Node::IntLiteral(2),
+            Node::StringLiteral("be".to_string()),
+        ]
+    ),
+    "Return: 258 (i64)"
+);
+
+// ------------------------------------------------------------------
+// TEST 60: CSV.Parse quotes, escapes and infers column types
+// ------------------------------------------------------------------
+knoten_test!(
+    test_60_csv_parse_typed_columns,
+    Node::MapGet(
+        Box::new(Node::Index(
+            Box::new(Node::Call(
+                "CSV.Parse".to_string(),
+                vec![Node::StringLiteral("name,qty,price\n\"Bolt, M4\",10,0.25\nNut,\"200\",1\n".to_string())]
+            )),
+            Box::new(Node::IntLiteral(1)),
+        )),
+        Box::new(Node::StringLiteral("price".to_string())),
+    ),
+    "Return: 1.0 (f64)"
+);
+
+// ------------------------------------------------------------------
+// TEST 61: Template.Render iterates sections and escapes HTML
+// ------------------------------------------------------------------
+knoten_test!(
+    test_61_template_render_sections,
+    Node::Call(
+        "Template.Render".to_string(),
+        vec![
+            Node::StringLiteral("{{#users}}\n<li>{{name}}</li>\n{{/users}}\n{{^users}}none\n{{/users}}".to_string()),
+            Node::EvalJSONNative(Box::new(Node::StringLiteral(r#"{"users": [{"name": "Ada"}, {"name": "<bob>"}]}"#.to_string()))),
+        ]
+    ),
+    "Return: \"<li>Ada</li>\n<li>&lt;bob&gt;</li>\n\" (String)"
+);
+
+// ------------------------------------------------------------------
+// TEST 62: Store persists to disk, so it needs the FS write permission
+// ------------------------------------------------------------------
+knoten_test!(
+    test_62_store_requires_fs_write,
+    Node::Store { key: "high_score".to_string(), value: Box::new(Node::IntLiteral(9000)) },
+    "Fault: Permission Denied: allow_fs_write is false"
+);
+
+// ------------------------------------------------------------------
+// TEST 63: Mat4Mul reads arrays column-major, like Mat4.ToArray
+// ------------------------------------------------------------------
+fn mat4_array(values: [f64; 16]) -> Node {
+    Node::ArrayCreate(values.iter().map(|v| Node::FloatLiteral(*v)).collect())
+}
+
+knoten_test!(
+    test_63_mat4mul_arrays_are_column_major,
+    Node::Mat4Mul(
+        // translate(1, 2, 3) * scale(2)
+        Box::new(mat4_array([1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0, 2.0, 3.0, 1.0])),
+        Box::new(mat4_array([2.0, 0.0, 0.0, 0.0, 0.0, 2.0, 0.0, 0.0, 0.0, 0.0, 2.0, 0.0, 0.0, 0.0, 0.0, 1.0])),
+    ),
+    "Return: [2.0, 0.0, 0.0, 0.0, 0.0, 2.0, 0.0, 0.0, 0.0, 0.0, 2.0, 0.0, 1.0, 2.0, 3.0, 1.0] (Array)"
+);
+
+// ------------------------------------------------------------------
+// TESTS 64-65: Extract queries strings as strings, even ones holding JSON
+// ------------------------------------------------------------------
+knoten_test!(
+    test_64_extract_plain_string,
+    Node::Extract {
+        source: Box::new(Node::StringLiteral("not json".to_string())),
+        path: Box::new(Node::StringLiteral("$".to_string())),
+    },
+    "Return: \"not json\" (String)"
+);
+
+knoten_test!(
+    test_65_extract_json_text_stays_a_string,
+    Node::Extract {
+        source: Box::new(Node::StringLiteral("[draft] report".to_string())),
+        path: Box::new(Node::StringLiteral("$".to_string())),
+    },
+    "Return: \"[draft] report\" (String)"
+);